@@ -32,6 +32,10 @@ fn main() {
 
     println!("{:?}", ecvrf.verify(&alpha, &proof));
 
+    // Anyone could verify the proof with the public key only
+    let verifier = ECVRF::new_verifier(proof.pk);
+    println!("{:?}", verifier.verify(&alpha, &proof));
+
     let smart_contract_proof = ecvrf.prove_contract(&alpha);
 
     println!("result: {:#?}", smart_contract_proof);
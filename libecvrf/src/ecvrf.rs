@@ -2,20 +2,66 @@ extern crate alloc;
 use crate::{
     error,
     extends::{AffineExtend, ScalarExtend},
-    hash::{hash_points, hash_points_prefix, hash_to_curve, hash_to_curve_prefix},
+    hash::{
+        hash_points, hash_points_compressed, hash_points_prefix, hash_to_curve,
+        hash_to_curve_prefix, hash_to_curve_with_context,
+    },
     helper::*,
 };
-use alloc::string::String;
+use alloc::{string::String, vec::Vec};
 use libsecp256k1::{
     curve::{Affine, ECMultContext, ECMultGenContext, Field, Jacobian, Scalar, AFFINE_G},
     util::{FULL_PUBLIC_KEY_SIZE, SECRET_KEY_SIZE},
     PublicKey, SecretKey, ECMULT_CONTEXT, ECMULT_GEN_CONTEXT,
 };
 use rand::thread_rng;
+use subtle::ConstantTimeEq;
+use zeroize::Zeroize;
 
 /// Max retries for randomize scalar or repeat hash
 pub const MAX_RETRIES: u32 = 100;
 
+/// Challenge hash of an ordinary proof: G, H, public key, gamma, U and V
+type PointsHasher = fn(&Affine, &Affine, &Affine, &Affine, &Affine, &Affine) -> Scalar;
+
+/// Size of a serialized [ECVRFContractProof]: public key, gamma, c, s, y, alpha,
+/// witness address, witness gamma, witness hash and inverse z
+pub const CONTRACT_PROOF_SIZE: usize = FULL_PUBLIC_KEY_SIZE + 64 + 32 * 4 + 20 + 64 * 2 + 32;
+
+/// Size of [ECVRFContractProof::abi_encode], ten 32 bytes words
+pub const CONTRACT_PROOF_ABI_SIZE: usize = 32 * 10;
+
+/// Read a 32 bytes big endian scalar, it must be less than the group order
+fn read_scalar(bytes: &[u8]) -> Result<Scalar, error::Error> {
+    let mut buf = [0u8; 32];
+    buf.copy_from_slice(bytes);
+    let mut scalar = Scalar::default();
+    if scalar.set_b32(&buf).unwrap_u8() == 1 {
+        return Err(error::Error::OutOfRange);
+    }
+    Ok(scalar)
+}
+
+/// Read a 32 bytes big endian field element, it must be less than the field size
+fn read_field(bytes: &[u8]) -> Result<Field, error::Error> {
+    let mut buf = [0u8; 32];
+    buf.copy_from_slice(bytes);
+    let mut field = Field::default();
+    if !field.set_b32(&buf) {
+        return Err(error::Error::OutOfRange);
+    }
+    Ok(field)
+}
+
+/// Read a point from its 64 bytes x and y coordinates
+fn read_affine(bytes: &[u8]) -> Result<Affine, error::Error> {
+    let point = Affine::compose(&read_field(&bytes[0..32])?, &read_field(&bytes[32..64])?);
+    if !point.is_valid_var() {
+        return Err(error::Error::InvalidPoint);
+    }
+    Ok(point)
+}
+
 /// Zeroable trait
 pub trait Zeroable {
     /// Zeroize self
@@ -107,12 +153,30 @@ impl From<&[u8; SECRET_KEY_SIZE]> for KeyPair {
     }
 }
 
+impl TryFrom<&str> for KeyPair {
+    type Error = error::Error;
+
+    /// Parse a hex encoded secret key, an optional `0x` prefix is allowed
+    fn try_from(value: &str) -> Result<Self, Self::Error> {
+        let value = value.trim();
+        let value = value
+            .strip_prefix("0x")
+            .or_else(|| value.strip_prefix("0X"))
+            .unwrap_or(value);
+        if value.len() != SECRET_KEY_SIZE * 2 {
+            return Err(error::Error::InvalidLength);
+        }
+        let mut secret_key = [0u8; SECRET_KEY_SIZE];
+        hex::decode_to_slice(value, &mut secret_key).map_err(|_| error::Error::InvalidHexString)?;
+        let secret_instance =
+            SecretKey::parse(&secret_key).map_err(|_| error::Error::InvalidSecretKey)?;
+        Ok(Self::from(secret_instance))
+    }
+}
+
 impl From<String> for KeyPair {
     fn from(value: String) -> Self {
-        let mut secret_key = [0u8; SECRET_KEY_SIZE];
-        hex::decode_to_slice(value.trim(), &mut secret_key)
-            .expect("Unable to convert secret key to [u8; SECRET_KEY_SIZE]");
-        Self::from(&secret_key)
+        Self::try_from(value.as_str()).expect("Unable to parse secret key")
     }
 }
 
@@ -151,6 +215,47 @@ pub struct ECVRFProof {
     pub pk: PublicKey,
 }
 
+impl ECVRFProof {
+    /// Check that y is keccak256(gamma), it does not verify the proof itself
+    pub fn check_output_binding(&self) -> bool {
+        Scalar::from_bytes(&self.gamma.keccak256()).eq(&self.y)
+    }
+
+    /// Cheap structural check before [verify](Self::verify): gamma and the public key
+    /// are valid points and c, s are in [1, n). No scalar multiplication is done, a
+    /// well-formed proof may still fail to verify
+    pub fn is_well_formed(&self) -> bool {
+        let mut pub_affine: Affine = self.pk.into();
+        pub_affine.x.normalize();
+        pub_affine.y.normalize();
+        let in_range = |scalar: &Scalar| !scalar.is_zero() && !scalar.gte(&GROUP_ORDER);
+        !pub_affine.is_infinity()
+            && pub_affine.is_valid_var()
+            && is_valid_gamma(&self.gamma)
+            && in_range(&self.c)
+            && in_range(&self.s)
+    }
+
+    /// Verify the proof against its embedded public key
+    pub fn verify(&self, alpha: &Scalar) -> bool {
+        ECVRF::new_verifier(self.pk).verify(alpha, self)
+    }
+}
+
+impl From<&ECVRFContractProof> for ECVRFProof {
+    /// Drop the witnesses of a contract proof, the result keeps the contract transcript
+    /// so it only verifies with [ECVRFContractProof::from_ordinary], not [ECVRF::verify]
+    fn from(value: &ECVRFContractProof) -> Self {
+        ECVRFProof {
+            gamma: value.gamma,
+            c: value.c,
+            s: value.s,
+            y: value.y,
+            pk: value.pk,
+        }
+    }
+}
+
 /// EC-VRF contract proof that compatible and verifiable with Solidity contract
 #[derive(Clone, Copy, Debug)]
 pub struct ECVRFContractProof {
@@ -176,25 +281,277 @@ pub struct ECVRFContractProof {
     pub inverse_z: Field,
 }
 
-/// ECVRF
+impl ECVRFContractProof {
+    /// Check that inverse_z is the inverse of z of V = witness_gamma + witness_hash
+    /// in projective coordinate, it does not verify the proof itself
+    pub fn validate_inverse_z(&self) -> bool {
+        let v = projective_ec_add(&self.witness_gamma, &self.witness_hash);
+        let mut one = v.z * self.inverse_z;
+        one.normalize();
+        one == Field::from_int(1)
+    }
+
+    /// Recompute the witnesses of a contract proof from `(gamma, c, s)` of an [ECVRFProof].
+    /// The ordinary prover hashes alpha with [hash_to_curve] and the challenge with
+    /// [hash_points] while the contract prover uses [hash_to_curve_prefix] and
+    /// [hash_points_prefix], the challenge c binds H so an ordinary proof can't be turned
+    /// into a contract proof without the secret key. The conversion is only valid for a
+    /// proof that was produced with the contract transcript (e.g. a contract proof whose
+    /// witnesses were dropped), otherwise [IncompatibleProof](error::Error::IncompatibleProof)
+    /// is returned
+    pub fn from_ordinary(proof: &ECVRFProof, alpha: &Scalar) -> Result<Self, error::Error> {
+        let mut pub_affine: Affine = proof.pk.into();
+        pub_affine.x.normalize();
+        pub_affine.y.normalize();
+        if !pub_affine.is_valid_var() || !is_valid_gamma(&proof.gamma) {
+            return Err(error::Error::InvalidPoint);
+        }
+
+        // H = ECVRF_hash_to_curve_prefix(alpha, pk)
+        let h = hash_to_curve_prefix(alpha, &pub_affine);
+
+        // U = c * pk + s * G = k * G
+        let mut u = Jacobian::default();
+        ECMULT_CONTEXT.ecmult(&mut u, &Jacobian::from_ge(&pub_affine), &proof.c, &proof.s);
+        let u_witness = calculate_witness_address(&Affine::from_jacobian(&u));
+
+        // witness_gamma = c * gamma, witness_hash = s * H
+        let witness_gamma = ecmult(&ECMULT_CONTEXT, &proof.gamma, &proof.c);
+        let witness_hash = ecmult(&ECMULT_CONTEXT, &h, &proof.s);
+
+        // V = witness_gamma + witness_hash
+        let v = projective_ec_add(&witness_gamma, &witness_hash);
+        let mut inverse_z = v.z.inv();
+        inverse_z.normalize();
+
+        let mut v_x = v.x * inverse_z;
+        let mut v_y = v.y * inverse_z;
+        v_x.normalize();
+        v_y.normalize();
+        let computed_c = hash_points_prefix(
+            &h,
+            &pub_affine,
+            &proof.gamma,
+            &u_witness,
+            &Affine::new(v_x, v_y),
+        );
+        if computed_c != proof.c {
+            return Err(error::Error::IncompatibleProof);
+        }
+
+        Ok(ECVRFContractProof {
+            pk: proof.pk,
+            gamma: proof.gamma,
+            c: proof.c,
+            s: proof.s,
+            y: proof.y,
+            alpha: *alpha,
+            witness_address: address_to_scalar(&u_witness),
+            witness_gamma,
+            witness_hash,
+            inverse_z,
+        })
+    }
+
+    /// Serialize the proof to [CONTRACT_PROOF_SIZE] bytes, the public key is
+    /// uncompressed, the witness address takes 20 bytes and the rest are 32 bytes
+    /// big endian numbers, points are encoded as x then y
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut bytes = Vec::with_capacity(CONTRACT_PROOF_SIZE);
+        bytes.extend_from_slice(&self.pk.serialize());
+        bytes.extend_from_slice(&self.gamma.x.b32());
+        bytes.extend_from_slice(&self.gamma.y.b32());
+        bytes.extend_from_slice(&self.c.b32());
+        bytes.extend_from_slice(&self.s.b32());
+        bytes.extend_from_slice(&self.y.b32());
+        bytes.extend_from_slice(&self.alpha.b32());
+        bytes.extend_from_slice(&self.witness_address.to_eth_address());
+        bytes.extend_from_slice(&self.witness_gamma.x.b32());
+        bytes.extend_from_slice(&self.witness_gamma.y.b32());
+        bytes.extend_from_slice(&self.witness_hash.x.b32());
+        bytes.extend_from_slice(&self.witness_hash.y.b32());
+        bytes.extend_from_slice(&self.inverse_z.b32());
+        bytes
+    }
+
+    /// Solidity ABI encoding of the arguments of the verifier contract, the words are
+    /// gamma, c, s, witness address, witness gamma, witness hash and inverse z. Points
+    /// take two words x then y, the address is left padded with zeros like `address`
+    pub fn abi_encode(&self) -> Vec<u8> {
+        let mut bytes = Vec::with_capacity(CONTRACT_PROOF_ABI_SIZE);
+        bytes.extend_from_slice(&self.gamma.x.b32());
+        bytes.extend_from_slice(&self.gamma.y.b32());
+        bytes.extend_from_slice(&self.c.b32());
+        bytes.extend_from_slice(&self.s.b32());
+        bytes.extend_from_slice(&[0u8; 12]);
+        bytes.extend_from_slice(&self.witness_address.to_eth_address());
+        bytes.extend_from_slice(&self.witness_gamma.x.b32());
+        bytes.extend_from_slice(&self.witness_gamma.y.b32());
+        bytes.extend_from_slice(&self.witness_hash.x.b32());
+        bytes.extend_from_slice(&self.witness_hash.y.b32());
+        bytes.extend_from_slice(&self.inverse_z.b32());
+        bytes
+    }
+
+    /// Deserialize a proof produced by [ECVRFContractProof::to_bytes], every
+    /// component is range checked and points must be on the curve
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, error::Error> {
+        if bytes.len() != CONTRACT_PROOF_SIZE {
+            return Err(error::Error::InvalidLength);
+        }
+        let (pk, rest) = bytes.split_at(FULL_PUBLIC_KEY_SIZE);
+        let (gamma, rest) = rest.split_at(64);
+        let (c, rest) = rest.split_at(32);
+        let (s, rest) = rest.split_at(32);
+        let (y, rest) = rest.split_at(32);
+        let (alpha, rest) = rest.split_at(32);
+        let (witness_address, rest) = rest.split_at(20);
+        let (witness_gamma, rest) = rest.split_at(64);
+        let (witness_hash, inverse_z) = rest.split_at(64);
+
+        let mut address = [0u8; 20];
+        address.copy_from_slice(witness_address);
+        Ok(Self {
+            pk: PublicKey::parse_slice(pk, None).map_err(|_| error::Error::InvalidPoint)?,
+            gamma: read_affine(gamma)?,
+            c: read_scalar(c)?,
+            s: read_scalar(s)?,
+            y: read_scalar(y)?,
+            alpha: read_scalar(alpha)?,
+            witness_address: address_to_scalar(&address),
+            witness_gamma: read_affine(witness_gamma)?,
+            witness_hash: read_affine(witness_hash)?,
+            inverse_z: read_field(inverse_z)?,
+        })
+    }
+}
+
+impl Zeroable for ECVRF<'_> {
+    /// Clear the secret key in place, the instance becomes verify-only
+    fn zeroize(&mut self) {
+        if let Some(secret_key) = self.secret_key.as_mut() {
+            // Volatile writes, the wipe is not elided even if the instance is about to be dropped
+            secret_key.0.zeroize();
+        }
+    }
+
+    /// A zero scalar is never a valid secret key, a wiped instance holds one
+    fn is_zero(&self) -> bool {
+        self.secret_key
+            .map_or(true, |secret_key| secret_key.is_zero())
+    }
+}
+
+impl Drop for ECVRF<'_> {
+    fn drop(&mut self) {
+        self.zeroize();
+    }
+}
+
+/// Ciphersuite of the ordinary proofs, secp256k1 with keccak256
+pub const CIPHERSUITE: &str = "ecvrf-keccak256";
+
+/// Everything a light client needs to verify the proofs of an operator
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct PublicParams {
+    /// Compressed public key, hex encoded
+    pub public_key: String,
+    /// Ciphersuite identifier, see [CIPHERSUITE]
+    pub ciphersuite: String,
+}
+
+/// ECVRF, the secret key is zeroized once the instance is dropped
 pub struct ECVRF<'a> {
-    secret_key: SecretKey,
+    secret_key: Option<Scalar>,
     public_key: PublicKey,
     ctx_mul: &'a ECMultContext,
-    ctx_gen: &'a ECMultGenContext,
+    ctx_gen: Option<&'a ECMultGenContext>,
 }
 
 impl<'a> ECVRF<'a> {
     /// Create new instance of ECVRF from a secret key
     pub fn new(secret_key: SecretKey) -> Self {
+        Self::with_contexts(secret_key, &ECMULT_CONTEXT, &ECMULT_GEN_CONTEXT)
+    }
+
+    /// Create new instance of ECVRF from a secret key with caller provided contexts.
+    /// [SecretKey] is `Copy`, only the copy held by this instance is zeroized on drop
+    pub fn with_contexts(
+        secret_key: SecretKey,
+        ctx_mul: &'a ECMultContext,
+        ctx_gen: &'a ECMultGenContext,
+    ) -> Self {
         ECVRF {
-            secret_key,
-            public_key: PublicKey::from_secret_key(&secret_key),
-            ctx_gen: &ECMULT_GEN_CONTEXT,
-            ctx_mul: &ECMULT_CONTEXT,
+            secret_key: Some(secret_key.into()),
+            public_key: PublicKey::from_secret_key_with_context(&secret_key, ctx_gen),
+            ctx_gen: Some(ctx_gen),
+            ctx_mul,
+        }
+    }
+
+    /// Create new verify-only instance of ECVRF from a public key,
+    /// proving with this instance returns [MissingSecretKey](error::Error::MissingSecretKey)
+    pub fn new_verifier(public_key: PublicKey) -> Self {
+        Self::new_verifier_with_context(public_key, &ECMULT_CONTEXT)
+    }
+
+    /// Create new verify-only instance of ECVRF with a caller provided multiplication context.
+    /// Verification only needs the multiplication context, the generator context is never
+    /// referenced so it's not initialized (or even linked) by a pure verifier
+    pub fn new_verifier_with_context(public_key: PublicKey, ctx_mul: &'a ECMultContext) -> Self {
+        ECVRF {
+            secret_key: None,
+            public_key,
+            ctx_gen: None,
+            ctx_mul,
+        }
+    }
+
+    /// Secret key and generator context that are required to prove
+    fn prover(&self) -> Result<(Scalar, &'a ECMultGenContext), error::Error> {
+        match (self.secret_key, self.ctx_gen) {
+            (Some(secret_key), Some(ctx_gen)) if !secret_key.is_zero() => Ok((secret_key, ctx_gen)),
+            _ => Err(error::Error::MissingSecretKey),
+        }
+    }
+
+    /// Check if this instance is able to prove
+    pub fn can_prove(&self) -> bool {
+        !self.is_zero()
+    }
+
+    /// Public parameters of this instance for a light client
+    pub fn public_params(&self) -> PublicParams {
+        PublicParams {
+            public_key: hex::encode(self.public_key.serialize_compressed()),
+            ciphersuite: String::from(CIPHERSUITE),
         }
     }
 
+    /// Hash many alphas to curve under the public key, same as calling
+    /// [hash_to_curve](crate::hash::hash_to_curve) for each alpha but the public key
+    /// is normalized once
+    pub fn hash_to_curve_many(&self, alphas: &[Scalar]) -> Vec<Affine> {
+        let mut pub_affine: Affine = self.public_key.into();
+        pub_affine.x.normalize();
+        pub_affine.y.normalize();
+        alphas
+            .iter()
+            .map(|alpha| hash_to_curve_with_context(self.ctx_mul, alpha, Some(&pub_affine)))
+            .collect()
+    }
+
+    /// H of the ordinary prover and verifier for alpha under the public key, a verifier
+    /// may compute it for a known alpha, e.g. alpha of the next epoch, before the proof
+    /// arrives
+    pub fn expected_h(&self, alpha: &Scalar) -> Affine {
+        let mut pub_affine: Affine = self.public_key.into();
+        pub_affine.x.normalize();
+        pub_affine.y.normalize();
+        hash_to_curve_with_context(self.ctx_mul, alpha, Some(&pub_affine))
+    }
+
     /// We use this method to prove a randomness for L1 smart contract
     /// This prover was optimized for on-chain verification
     /// u_witness is a represent of u, used ecrecover to minimize gas cost
@@ -202,7 +559,7 @@ impl<'a> ECVRF<'a> {
     /// on-chain verifier.
     pub fn prove_contract(&self, alpha: &Scalar) -> Result<ECVRFContractProof, error::Error> {
         let mut pub_affine: Affine = self.public_key.into();
-        let mut secret_key: Scalar = self.secret_key.into();
+        let (mut secret_key, ctx_gen) = self.prover()?;
         pub_affine.x.normalize();
         pub_affine.y.normalize();
 
@@ -227,7 +584,7 @@ impl<'a> ECVRF<'a> {
         }
 
         // Calculate k * G = u
-        let kg = ecmult_gen(self.ctx_gen, &k);
+        let kg = ecmult_gen(ctx_gen, &k);
         // U = c * pk + s * G
         // u_witness = ecrecover(c * pk + s * G)
         // this value equal to address(keccak256(U))
@@ -274,7 +631,7 @@ impl<'a> ECVRF<'a> {
             s,
             y: Scalar::from_bytes(&gamma.keccak256()),
             alpha: *alpha,
-            witness_address: Scalar::from_bytes(&u_witness),
+            witness_address: address_to_scalar(&u_witness),
             witness_gamma,
             witness_hash,
             inverse_z,
@@ -283,8 +640,24 @@ impl<'a> ECVRF<'a> {
 
     /// Ordinary prover
     pub fn prove(&self, alpha: &Scalar) -> Result<ECVRFProof, error::Error> {
+        self.prove_with(alpha, hash_points)
+    }
+
+    /// Ordinary prover with the challenge hashed over compressed points, see
+    /// [hash_points_compressed]. Verify it with [ECVRF::verify_compressed], it's
+    /// rejected by [ECVRF::verify]
+    pub fn prove_compressed(&self, alpha: &Scalar) -> Result<ECVRFProof, error::Error> {
+        self.prove_with(alpha, hash_points_compressed)
+    }
+
+    /// Ordinary prover with the given challenge hash
+    fn prove_with(
+        &self,
+        alpha: &Scalar,
+        hash_challenge: PointsHasher,
+    ) -> Result<ECVRFProof, error::Error> {
         let mut pub_affine: Affine = self.public_key.into();
-        let mut secret_key: Scalar = self.secret_key.into();
+        let (mut secret_key, ctx_gen) = self.prover()?;
         pub_affine.x.normalize();
         pub_affine.y.normalize();
 
@@ -307,13 +680,13 @@ impl<'a> ECVRF<'a> {
         }
 
         // Calculate k * G <=> u
-        let kg = ecmult_gen(self.ctx_gen, &k);
+        let kg = ecmult_gen(ctx_gen, &k);
 
         // Calculate k * H <=> v
         let kh = ecmult(self.ctx_mul, &h, &k);
 
         // c = ECVRF_hash_points(G, H, public_key, gamma, k * G, k * H)
-        let c = hash_points(&AFFINE_G, &h, &pub_affine, &gamma, &kg, &kh);
+        let c = hash_challenge(&AFFINE_G, &h, &pub_affine, &gamma, &kg, &kh);
 
         // s = (k - c * secret_key) mod p
         let mut neg_c = c;
@@ -333,17 +706,201 @@ impl<'a> ECVRF<'a> {
         })
     }
 
-    /// Ordinary verifier
+    /// Prove a message of any length, alpha is keccak256 of the message reduced
+    /// modulo the group order, verify it with [ECVRF::verify_message]
+    pub fn prove_message(&self, alpha_bytes: &[u8]) -> Result<ECVRFProof, error::Error> {
+        self.prove(&Scalar::keccak256(alpha_bytes))
+    }
+
+    /// Prove both the ordinary and the contract proof of the same alpha.
+    /// The two proofs use different hash to curve (the contract proof is prefixed),
+    /// so H and gamma can't be shared between them. Each proof also draws its own nonce,
+    /// reusing k for two different challenges would leak the secret key:
+    /// sk = (s1 - s2) / (c2 - c1).
+    pub fn prove_both(
+        &self,
+        alpha: &Scalar,
+    ) -> Result<(ECVRFProof, ECVRFContractProof), error::Error> {
+        Ok((self.prove(alpha)?, self.prove_contract(alpha)?))
+    }
+
+    /// Generate a chain of `n` contract proofs offline, the first epoch is proven for
+    /// `genesis_alpha` and each following epoch uses the `y` of the previous one as alpha
+    pub fn generate_chain(
+        secret: &SecretKey,
+        genesis_alpha: &Scalar,
+        n: usize,
+    ) -> Result<Vec<ECVRFContractProof>, error::Error> {
+        let ecvrf = ECVRF::new(*secret);
+        let mut chain = Vec::with_capacity(n);
+        let mut alpha = *genesis_alpha;
+        for _ in 0..n {
+            let proof = ecvrf.prove_contract(&alpha)?;
+            alpha = proof.y;
+            chain.push(proof);
+        }
+        Ok(chain)
+    }
+
+    /// Contract verifier, perform the same checks as the on-chain verifier
+    pub fn verify_contract(&self, vrf_proof: &ECVRFContractProof) -> bool {
+        if vrf_proof.pk != self.public_key {
+            return false;
+        }
+        let mut pub_affine: Affine = self.public_key.into();
+        pub_affine.x.normalize();
+        pub_affine.y.normalize();
+
+        if !pub_affine.is_valid_var() || !is_valid_gamma(&vrf_proof.gamma) {
+            return false;
+        }
+
+        // H = ECVRF_hash_to_curve_prefix(alpha, pk)
+        let h = hash_to_curve_prefix(&vrf_proof.alpha, &pub_affine);
+
+        // U = c * pk + s * G = k * G, its address must match u_witness
+        let mut u = Jacobian::default();
+        self.ctx_mul.ecmult(
+            &mut u,
+            &Jacobian::from_ge(&pub_affine),
+            &vrf_proof.c,
+            &vrf_proof.s,
+        );
+        let u_witness = calculate_witness_address(&Affine::from_jacobian(&u));
+        if address_to_scalar(&u_witness) != vrf_proof.witness_address {
+            return false;
+        }
+
+        // witness_gamma = c * gamma and witness_hash = s * H
+        if ecmult(self.ctx_mul, &vrf_proof.gamma, &vrf_proof.c) != vrf_proof.witness_gamma
+            || ecmult(self.ctx_mul, &h, &vrf_proof.s) != vrf_proof.witness_hash
+        {
+            return false;
+        }
+
+        // V = witness_gamma + witness_hash in projective coordinate, inverse_z must be 1/z
+        let v = projective_ec_add(&vrf_proof.witness_gamma, &vrf_proof.witness_hash);
+        let mut one = v.z * vrf_proof.inverse_z;
+        one.normalize();
+        if one != Field::from_int(1) {
+            return false;
+        }
+        let mut v_x = v.x * vrf_proof.inverse_z;
+        let mut v_y = v.y * vrf_proof.inverse_z;
+        v_x.normalize();
+        v_y.normalize();
+
+        // c_prime = ECVRF_hash_points_prefix(H, pk, gamma, u_witness, V)
+        let computed_c = hash_points_prefix(
+            &h,
+            &pub_affine,
+            &vrf_proof.gamma,
+            &u_witness,
+            &Affine::new(v_x, v_y),
+        );
+
+        // y = keccak256(gama.encode())
+        let computed_y = Scalar::from_bytes(&vrf_proof.gamma.keccak256());
+
+        computed_c.eq(&vrf_proof.c) && computed_y.eq(&vrf_proof.y)
+    }
+
+    /// Ordinary verifier, c and y are compared in variable time and the comparison stops at
+    /// the first mismatch, use [ECVRF::verify_ct] if the timing must not depend on the proof
     pub fn verify(&self, alpha: &Scalar, vrf_proof: &ECVRFProof) -> bool {
+        self.verify_with(alpha, vrf_proof, hash_points)
+    }
+
+    /// Verify every `(alpha, proof)` item with [ECVRF::verify], results are in the order
+    /// of the items. Items are verified concurrently with the `parallel` feature
+    pub fn verify_batch(&self, items: &[(Scalar, ECVRFProof)]) -> Vec<bool> {
+        #[cfg(feature = "parallel")]
+        {
+            use rayon::prelude::*;
+            items
+                .par_iter()
+                .map(|(alpha, vrf_proof)| self.verify(alpha, vrf_proof))
+                .collect()
+        }
+        #[cfg(not(feature = "parallel"))]
+        self.verify_batch_sequential(items)
+    }
+
+    /// Verify every item of a batch one after another
+    #[cfg(any(test, not(feature = "parallel")))]
+    fn verify_batch_sequential(&self, items: &[(Scalar, ECVRFProof)]) -> Vec<bool> {
+        items
+            .iter()
+            .map(|(alpha, vrf_proof)| self.verify(alpha, vrf_proof))
+            .collect()
+    }
+
+    /// Verify a proof produced by [ECVRF::prove_compressed]
+    pub fn verify_compressed(&self, alpha: &Scalar, vrf_proof: &ECVRFProof) -> bool {
+        self.verify_with(alpha, vrf_proof, hash_points_compressed)
+    }
+
+    /// Ordinary verifier with the given challenge hash
+    fn verify_with(
+        &self,
+        alpha: &Scalar,
+        vrf_proof: &ECVRFProof,
+        hash_challenge: PointsHasher,
+    ) -> bool {
+        match self.recompute(alpha, vrf_proof, hash_challenge) {
+            Some((computed_c, computed_y)) => {
+                computed_c.eq(&vrf_proof.c) && computed_y.eq(&vrf_proof.y)
+            }
+            None => false,
+        }
+    }
+
+    /// Verify a proof and that its output is `expected_y`, e.g. the randomness of an
+    /// on-chain event. y of a valid proof is bound to gamma, so it can't be swapped
+    pub fn verify_output(
+        &self,
+        alpha: &Scalar,
+        vrf_proof: &ECVRFProof,
+        expected_y: &[u8; 32],
+    ) -> bool {
+        self.verify(alpha, vrf_proof) && vrf_proof.y.b32() == *expected_y
+    }
+
+    /// Verify a proof produced by [ECVRF::prove_message], the message is reduced to
+    /// alpha the same way
+    pub fn verify_message(&self, alpha_bytes: &[u8], vrf_proof: &ECVRFProof) -> bool {
+        self.verify(&Scalar::keccak256(alpha_bytes), vrf_proof)
+    }
+
+    /// Ordinary verifier in constant time, c and y are compared with [ConstantTimeEq] and
+    /// both comparisons are always performed
+    pub fn verify_ct(&self, alpha: &Scalar, vrf_proof: &ECVRFProof) -> bool {
+        let (computed_c, computed_y) = match self.recompute(alpha, vrf_proof, hash_points) {
+            Some(computed) => computed,
+            None => return false,
+        };
+        let c_eq = computed_c.b32().ct_eq(&vrf_proof.c.b32());
+        let y_eq = computed_y.b32().ct_eq(&vrf_proof.y.b32());
+        (c_eq & y_eq).into()
+    }
+
+    /// Recompute c and y of an ordinary proof, [None] if the public key or gamma is invalid
+    fn recompute(
+        &self,
+        alpha: &Scalar,
+        vrf_proof: &ECVRFProof,
+        hash_challenge: PointsHasher,
+    ) -> Option<(Scalar, Scalar)> {
         let mut pub_affine: Affine = self.public_key.into();
         pub_affine.x.normalize();
         pub_affine.y.normalize();
 
-        assert!(pub_affine.is_valid_var());
-        assert!(vrf_proof.gamma.is_valid_var());
+        if !pub_affine.is_valid_var() || !is_valid_gamma(&vrf_proof.gamma) {
+            return None;
+        }
 
-        // H = ECVRF_hash_to_curve(alpha, pk)
-        let h = hash_to_curve(alpha, Some(&pub_affine));
+        // H = ECVRF_hash_to_curve(alpha, pk), only the multiplication context is used
+        let h = self.expected_h(alpha);
         let mut jh = Jacobian::default();
         jh.set_ge(&h);
 
@@ -366,7 +923,7 @@ impl<'a> ECVRF<'a> {
         let v = Jacobian::from_ge(&witness_gamma).add_ge(&witness_hash);
 
         // c_prime = ECVRF_hash_points(G, H, pk, gamma, U, V)
-        let computed_c = hash_points(
+        let computed_c = hash_challenge(
             &AFFINE_G,
             &h,
             &pub_affine,
@@ -378,15 +935,27 @@ impl<'a> ECVRF<'a> {
         // y = keccak256(gama.encode())
         let computed_y = Scalar::from_bytes(&vrf_proof.gamma.keccak256());
 
-        // computed values should equal to the real one
-        computed_c.eq(&vrf_proof.c) && computed_y.eq(&vrf_proof.y)
+        Some((computed_c, computed_y))
     }
 }
 
 #[cfg(test)]
 mod tests {
-    use crate::{extends::ScalarExtend, ECVRF};
-    use libsecp256k1::{curve::Scalar, SecretKey};
+    use crate::{
+        error::Error,
+        extends::ScalarExtend,
+        hash::{hash_points, hash_to_curve},
+        helper::{address_to_scalar, ecmult, GROUP_ORDER},
+        ECVRFContractProof, ECVRFProof, KeyPair, Zeroable, CONTRACT_PROOF_ABI_SIZE,
+        CONTRACT_PROOF_SIZE, ECVRF,
+    };
+    extern crate alloc;
+    use alloc::{format, vec::Vec};
+    use libsecp256k1::{
+        curve::{Affine, Field, Jacobian, Scalar, AFFINE_G},
+        util::FULL_PUBLIC_KEY_SIZE,
+        PublicKey, SecretKey, ECMULT_CONTEXT, ECMULT_GEN_CONTEXT,
+    };
     use rand::thread_rng;
 
     #[test]
@@ -408,4 +977,572 @@ mod tests {
 
         assert!(r2);
     }
+
+    #[test]
+    fn keypair_should_parse_hex_secret_key() {
+        let keypair = KeyPair::new();
+        let hex_secret_key = hex::encode(keypair.secret_key.serialize());
+
+        let plain = KeyPair::try_from(hex_secret_key.as_str()).expect("Unable to parse");
+        let prefixed = KeyPair::try_from(format!("0x{}", hex_secret_key).as_str())
+            .expect("Unable to parse prefixed secret key");
+        let padded = KeyPair::try_from(format!("  0X{}\n", hex_secret_key).as_str())
+            .expect("Unable to parse padded secret key");
+
+        assert_eq!(plain, keypair);
+        assert_eq!(prefixed, keypair);
+        assert_eq!(padded, keypair);
+    }
+
+    #[test]
+    fn keypair_should_reject_malformed_secret_key() {
+        assert_eq!(KeyPair::try_from("0x1234"), Err(Error::InvalidLength));
+        assert_eq!(
+            KeyPair::try_from("zz".repeat(32).as_str()),
+            Err(Error::InvalidHexString)
+        );
+        assert_eq!(
+            KeyPair::try_from("00".repeat(32).as_str()),
+            Err(Error::InvalidSecretKey)
+        );
+    }
+
+    #[test]
+    fn proof_should_bind_output_to_gamma() {
+        let ecvrf = ECVRF::new(SecretKey::random(&mut thread_rng()));
+        let alpha = Scalar::randomize();
+        let proof = ecvrf.prove(&alpha).expect("Can not prove the randomness");
+        assert!(proof.check_output_binding());
+
+        // Mangle y, the binding check and the full verification must both fail
+        let mut mangled = proof;
+        mangled.y = mangled.y + Scalar::from_int(1);
+        assert!(!mangled.check_output_binding());
+        assert!(!ecvrf.verify(&alpha, &mangled));
+    }
+
+    #[test]
+    fn ecvrf_with_contexts_should_match_new() {
+        let secret_key = SecretKey::random(&mut thread_rng());
+        let ecvrf = ECVRF::new(secret_key);
+        let ecvrf_ctx = ECVRF::with_contexts(secret_key, &ECMULT_CONTEXT, &ECMULT_GEN_CONTEXT);
+        let alpha = Scalar::randomize();
+
+        let proof = ecvrf.prove(&alpha).expect("Can not prove the randomness");
+        let proof_ctx = ecvrf_ctx
+            .prove(&alpha)
+            .expect("Can not prove the randomness");
+
+        // Nonce is random, gamma and y are deterministic
+        assert_eq!(proof.gamma, proof_ctx.gamma);
+        assert_eq!(proof.y, proof_ctx.y);
+        assert_eq!(proof.pk, proof_ctx.pk);
+        assert!(ecvrf.verify(&alpha, &proof_ctx));
+        assert!(ecvrf_ctx.verify(&alpha, &proof));
+    }
+
+    #[test]
+    fn hash_to_curve_many_should_match_hash_to_curve() {
+        let secret_key = SecretKey::random(&mut thread_rng());
+        let ecvrf = ECVRF::new(secret_key);
+        let mut pub_affine: Affine = ecvrf.public_key.into();
+        pub_affine.x.normalize();
+        pub_affine.y.normalize();
+
+        let alphas = (0..8).map(|_| Scalar::randomize()).collect::<Vec<Scalar>>();
+        let batched = ecvrf.hash_to_curve_many(&alphas);
+        assert_eq!(batched.len(), alphas.len());
+        for (alpha, h) in alphas.iter().zip(batched.iter()) {
+            assert_eq!(*h, hash_to_curve(alpha, Some(&pub_affine)));
+        }
+        assert!(ecvrf.hash_to_curve_many(&[]).is_empty());
+    }
+
+    #[test]
+    fn expected_h_should_match_h_of_verify() {
+        let secret_key = SecretKey::random(&mut thread_rng());
+        let ecvrf = ECVRF::new(secret_key);
+        let verifier = ECVRF::new_verifier(ecvrf.public_key);
+        let mut pub_affine: Affine = ecvrf.public_key.into();
+        pub_affine.x.normalize();
+        pub_affine.y.normalize();
+
+        let alpha = Scalar::randomize();
+        let h = verifier.expected_h(&alpha);
+        assert_eq!(h, hash_to_curve(&alpha, Some(&pub_affine)));
+        assert_eq!(h, ecvrf.hash_to_curve_many(&[alpha])[0]);
+
+        // The challenge of a valid proof commits to the same H
+        let proof = ecvrf.prove(&alpha).expect("Unable to prove");
+        assert!(verifier.verify(&alpha, &proof));
+        let mut u = Jacobian::default();
+        ECMULT_CONTEXT.ecmult(&mut u, &Jacobian::from_ge(&pub_affine), &proof.c, &proof.s);
+        let witness_gamma = ecmult(&ECMULT_CONTEXT, &proof.gamma, &proof.c);
+        let witness_hash = ecmult(&ECMULT_CONTEXT, &h, &proof.s);
+        let v = Jacobian::from_ge(&witness_gamma).add_ge(&witness_hash);
+        let c = hash_points(
+            &AFFINE_G,
+            &h,
+            &pub_affine,
+            &proof.gamma,
+            &Affine::from_jacobian(&u),
+            &Affine::from_jacobian(&v),
+        );
+        assert_eq!(c, proof.c);
+
+        // H of another alpha doesn't
+        assert_ne!(verifier.expected_h(&Scalar::randomize()), h);
+    }
+
+    #[test]
+    fn prove_both_should_return_verifiable_proofs() {
+        let ecvrf = ECVRF::new(SecretKey::random(&mut thread_rng()));
+        let alpha = Scalar::randomize();
+
+        let (proof, contract_proof) = ecvrf.prove_both(&alpha).expect("Can not prove");
+        assert!(ecvrf.verify(&alpha, &proof));
+        assert!(ecvrf.verify_contract(&contract_proof));
+        assert_eq!(contract_proof.alpha, alpha);
+
+        // Nonces are independent
+        assert_ne!(proof.s, contract_proof.s);
+
+        // Tampered contract proof must be rejected
+        let mut tampered = contract_proof;
+        tampered.c = tampered.c + Scalar::from_int(1);
+        assert!(!ecvrf.verify_contract(&tampered));
+    }
+
+    #[test]
+    fn contract_proof_should_be_rebuilt_from_ordinary_proof() {
+        let ecvrf = ECVRF::new(SecretKey::random(&mut thread_rng()));
+        let alpha = Scalar::randomize();
+
+        // Witnesses are recomputed from (gamma, c, s) of the contract transcript
+        let contract_proof = ecvrf.prove_contract(&alpha).expect("Can not prove");
+        let stripped = ECVRFProof::from(&contract_proof);
+        let rebuilt =
+            ECVRFContractProof::from_ordinary(&stripped, &alpha).expect("Unable to convert");
+        assert!(ecvrf.verify_contract(&rebuilt));
+        assert_eq!(rebuilt.to_bytes(), contract_proof.to_bytes());
+
+        // Ordinary transcript is bound to a different H and challenge
+        let proof = ecvrf.prove(&alpha).expect("Can not prove");
+        assert_eq!(
+            ECVRFContractProof::from_ordinary(&proof, &alpha).unwrap_err(),
+            Error::IncompatibleProof
+        );
+
+        // Alpha must match the one that was proven
+        assert_eq!(
+            ECVRFContractProof::from_ordinary(&stripped, &Scalar::randomize()).unwrap_err(),
+            Error::IncompatibleProof
+        );
+    }
+
+    #[test]
+    fn contract_proof_should_round_trip_through_bytes() {
+        let ecvrf = ECVRF::new(SecretKey::random(&mut thread_rng()));
+        let proof = ecvrf
+            .prove_contract(&Scalar::randomize())
+            .expect("Unable to prove");
+        let bytes = proof.to_bytes();
+        assert_eq!(bytes.len(), CONTRACT_PROOF_SIZE);
+
+        let decoded = ECVRFContractProof::from_bytes(&bytes).expect("Unable to decode proof");
+        assert_eq!(decoded.to_bytes(), bytes);
+        assert_eq!(decoded.pk, proof.pk);
+        assert_eq!(decoded.y, proof.y);
+        assert!(ecvrf.verify_contract(&decoded));
+    }
+
+    #[test]
+    fn contract_proof_from_bytes_should_reject_invalid_input() {
+        let ecvrf = ECVRF::new(SecretKey::random(&mut thread_rng()));
+        let bytes = ecvrf
+            .prove_contract(&Scalar::randomize())
+            .expect("Unable to prove")
+            .to_bytes();
+        assert_eq!(
+            ECVRFContractProof::from_bytes(&bytes[1..]).unwrap_err(),
+            Error::InvalidLength
+        );
+
+        // Gamma is moved off the curve
+        let mut invalid = bytes.clone();
+        invalid[FULL_PUBLIC_KEY_SIZE + 63] ^= 1;
+        assert_eq!(
+            ECVRFContractProof::from_bytes(&invalid).unwrap_err(),
+            Error::InvalidPoint
+        );
+
+        // c is larger than the group order
+        let mut invalid = bytes;
+        invalid[FULL_PUBLIC_KEY_SIZE + 64..FULL_PUBLIC_KEY_SIZE + 96].copy_from_slice(&[0xff; 32]);
+        assert_eq!(
+            ECVRFContractProof::from_bytes(&invalid).unwrap_err(),
+            Error::OutOfRange
+        );
+    }
+
+    #[test]
+    fn validate_inverse_z_should_detect_mutated_inverse() {
+        let ecvrf = ECVRF::new(SecretKey::random(&mut thread_rng()));
+        let contract_proof = ecvrf
+            .prove_contract(&Scalar::randomize())
+            .expect("Can not prove");
+        assert!(contract_proof.validate_inverse_z());
+
+        let mut mutated = contract_proof;
+        mutated.inverse_z = mutated.inverse_z * Field::from_int(2);
+        mutated.inverse_z.normalize();
+        assert!(!mutated.validate_inverse_z());
+        assert!(!ecvrf.verify_contract(&mutated));
+    }
+
+    #[test]
+    fn verifier_should_verify_but_not_prove() {
+        let ecvrf = ECVRF::new(SecretKey::random(&mut thread_rng()));
+        let alpha = Scalar::randomize();
+        let (proof, contract_proof) = ecvrf.prove_both(&alpha).expect("Can not prove");
+
+        let verifier = ECVRF::new_verifier(proof.pk);
+        assert!(ecvrf.can_prove());
+        assert!(!verifier.can_prove());
+        assert!(verifier.verify(&alpha, &proof));
+        assert!(verifier.verify_contract(&contract_proof));
+        assert_eq!(verifier.prove(&alpha).unwrap_err(), Error::MissingSecretKey);
+        assert_eq!(
+            verifier.prove_contract(&alpha).unwrap_err(),
+            Error::MissingSecretKey
+        );
+    }
+
+    #[test]
+    fn verify_ct_should_agree_with_verify() {
+        let ecvrf = ECVRF::new(SecretKey::random(&mut thread_rng()));
+        let alpha = Scalar::randomize();
+        let proof = ecvrf.prove(&alpha).expect("Can not prove");
+        assert!(ecvrf.verify(&alpha, &proof));
+        assert!(ecvrf.verify_ct(&alpha, &proof));
+
+        let mut wrong_c = proof;
+        wrong_c.c = wrong_c.c + Scalar::from_int(1);
+        let mut wrong_y = proof;
+        wrong_y.y = wrong_y.y + Scalar::from_int(1);
+        for (alpha, proof) in [
+            (alpha, wrong_c),
+            (alpha, wrong_y),
+            (Scalar::randomize(), proof),
+        ] {
+            assert!(!ecvrf.verify(&alpha, &proof));
+            assert!(!ecvrf.verify_ct(&alpha, &proof));
+        }
+    }
+
+    #[test]
+    fn verify_should_reject_degenerate_gamma() {
+        let ecvrf = ECVRF::new(SecretKey::random(&mut thread_rng()));
+        let alpha = Scalar::randomize();
+        let proof = ecvrf.prove(&alpha).expect("Can not prove");
+        let contract_proof = ecvrf.prove_contract(&alpha).expect("Can not prove");
+
+        let mut infinity = proof;
+        infinity.gamma.infinity = true;
+        let mut generator = proof;
+        generator.gamma = AFFINE_G;
+        for degenerate in [infinity, generator] {
+            assert!(!ecvrf.verify(&alpha, &degenerate));
+            assert!(!ecvrf.verify_ct(&alpha, &degenerate));
+            assert!(!degenerate.verify(&alpha));
+            assert_eq!(
+                ECVRFContractProof::from_ordinary(&degenerate, &alpha).unwrap_err(),
+                Error::InvalidPoint
+            );
+
+            let mut degenerate_contract = contract_proof;
+            degenerate_contract.gamma = degenerate.gamma;
+            assert!(!ecvrf.verify_contract(&degenerate_contract));
+        }
+    }
+
+    #[test]
+    fn proof_should_be_well_formed() {
+        let ecvrf = ECVRF::new(SecretKey::random(&mut thread_rng()));
+        let alpha = Scalar::randomize();
+        let proof = ecvrf.prove(&alpha).expect("Can not prove");
+        assert!(proof.is_well_formed());
+
+        // gamma is moved off the curve
+        let mut off_curve = proof;
+        off_curve.gamma.y.set_int(1);
+        assert!(!off_curve.is_well_formed());
+        assert!(!off_curve.verify(&alpha));
+
+        // Zero and the group order itself are out of [1, n)
+        for out_of_range in [Scalar::default(), GROUP_ORDER] {
+            let mut bad_c = proof;
+            bad_c.c = out_of_range;
+            assert!(!bad_c.is_well_formed());
+            let mut bad_s = proof;
+            bad_s.s = out_of_range;
+            assert!(!bad_s.is_well_formed());
+        }
+    }
+
+    #[test]
+    fn verify_batch_should_match_sequential_verify() {
+        let ecvrf = ECVRF::new(SecretKey::random(&mut thread_rng()));
+        let items = (0..16)
+            .map(|i| {
+                let alpha = Scalar::randomize();
+                let mut proof = ecvrf.prove(&alpha).expect("Can not prove");
+                match i % 4 {
+                    // Wrong alpha
+                    1 => return (Scalar::randomize(), proof),
+                    // Tampered challenge
+                    2 => proof.c = Scalar::randomize(),
+                    _ => {}
+                }
+                (alpha, proof)
+            })
+            .collect::<Vec<_>>();
+
+        let results = ecvrf.verify_batch(&items);
+        assert_eq!(results, ecvrf.verify_batch_sequential(&items));
+        for (i, valid) in results.iter().enumerate() {
+            assert_eq!(*valid, i % 4 != 1 && i % 4 != 2, "Item {}", i);
+        }
+        assert!(ecvrf.verify_batch(&[]).is_empty());
+    }
+
+    #[test]
+    fn public_params_should_have_compressed_public_key() {
+        let secret_key = SecretKey::parse(&[1u8; 32]).expect("Valid secret key");
+        let public_key = PublicKey::from_secret_key(&secret_key);
+        let params = ECVRF::new(secret_key).public_params();
+        assert_eq!(
+            params.public_key,
+            hex::encode(public_key.serialize_compressed())
+        );
+        assert_eq!(params.public_key.len(), 66);
+        assert_eq!(params.ciphersuite, "ecvrf-keccak256");
+        // A verifier has the same parameters
+        assert_eq!(ECVRF::new_verifier(public_key).public_params(), params);
+    }
+
+    #[test]
+    fn contract_proof_should_be_abi_encoded() {
+        let secret_key = SecretKey::parse(&[1u8; 32]).expect("Valid secret key");
+        let proof = ECVRFContractProof {
+            pk: PublicKey::from_secret_key(&secret_key),
+            gamma: AFFINE_G,
+            c: Scalar::from_int(1),
+            s: Scalar::from_int(2),
+            y: Scalar::from_int(3),
+            alpha: Scalar::from_int(4),
+            witness_address: address_to_scalar(&[0x11u8; 20]),
+            witness_gamma: AFFINE_G,
+            witness_hash: AFFINE_G,
+            inverse_z: Field::from_int(5),
+        };
+        let g = concat!(
+            "79be667ef9dcbbac55a06295ce870b07029bfcdb2dce28d959f2815b16f81798",
+            "483ada7726a3c4655da4fbfc0e1108a8fd17b448a68554199c47d08ffb10d4b8",
+        );
+        let expected = [
+            g,
+            "0000000000000000000000000000000000000000000000000000000000000001",
+            "0000000000000000000000000000000000000000000000000000000000000002",
+            "0000000000000000000000001111111111111111111111111111111111111111",
+            g,
+            g,
+            "0000000000000000000000000000000000000000000000000000000000000005",
+        ]
+        .concat();
+
+        let encoded = proof.abi_encode();
+        assert_eq!(encoded.len(), CONTRACT_PROOF_ABI_SIZE);
+        assert_eq!(hex::encode(&encoded), expected);
+    }
+
+    #[test]
+    fn generated_chain_should_link_and_verify() {
+        let secret_key = SecretKey::random(&mut thread_rng());
+        let genesis_alpha = Scalar::randomize();
+        let chain =
+            ECVRF::generate_chain(&secret_key, &genesis_alpha, 5).expect("Can not generate chain");
+        assert_eq!(chain.len(), 5);
+        assert_eq!(chain[0].alpha, genesis_alpha);
+        for pair in chain.windows(2) {
+            assert_eq!(pair[1].alpha, pair[0].y);
+        }
+
+        let verifier = ECVRF::new_verifier(PublicKey::from_secret_key(&secret_key));
+        for proof in chain.iter() {
+            assert!(verifier.verify_contract(proof));
+        }
+        assert!(ECVRF::generate_chain(&secret_key, &genesis_alpha, 0)
+            .expect("Can not generate chain")
+            .is_empty());
+    }
+
+    #[test]
+    fn message_should_be_proven_and_verified() {
+        let ecvrf = ECVRF::new(SecretKey::random(&mut thread_rng()));
+        let message = b"Orochi Network randomness of an arbitrary length message";
+        let proof = ecvrf.prove_message(message).expect("Can not prove");
+        assert!(ecvrf.verify_message(message, &proof));
+        assert!(ecvrf.verify(&Scalar::keccak256(message), &proof));
+        assert!(!ecvrf.verify_message(b"Another message", &proof));
+        assert!(!ecvrf.verify_message(&[], &proof));
+    }
+
+    #[test]
+    fn secret_key_should_be_zeroized() {
+        let mut ecvrf = ECVRF::new(SecretKey::random(&mut thread_rng()));
+        let alpha = Scalar::randomize();
+        let proof = ecvrf.prove(&alpha).expect("Can not prove");
+        assert!(!ecvrf.is_zero());
+        assert!(!ecvrf.secret_key.expect("Secret key must exist").is_zero());
+
+        // Drop clears the secret key the same way, every limb of the key reads as zero
+        assert!(core::mem::needs_drop::<ECVRF<'_>>());
+        ecvrf.zeroize();
+        assert_eq!(ecvrf.secret_key.expect("Wiped key is kept").0, [0u32; 8]);
+        assert!(ecvrf.is_zero());
+        assert!(!ecvrf.can_prove());
+        assert_eq!(ecvrf.prove(&alpha).unwrap_err(), Error::MissingSecretKey);
+
+        // Public key is kept, the instance is still able to verify
+        assert!(ecvrf.verify(&alpha, &proof));
+
+        // Verifier has no secret to clear
+        assert!(ECVRF::new_verifier(proof.pk).is_zero());
+    }
+
+    #[test]
+    fn verifier_should_verify_without_generator_context() {
+        let ecvrf = ECVRF::new(SecretKey::random(&mut thread_rng()));
+        let alpha = Scalar::randomize();
+        let proof = ecvrf.prove(&alpha).expect("Can not prove");
+
+        let verifier = ECVRF::new_verifier_with_context(proof.pk, &ECMULT_CONTEXT);
+        assert!(verifier.verify(&alpha, &proof));
+        assert!(!verifier.verify(&Scalar::randomize(), &proof));
+        assert_eq!(verifier.prove(&alpha).unwrap_err(), Error::MissingSecretKey);
+    }
+
+    #[test]
+    fn proof_should_verify_itself() {
+        let ecvrf = ECVRF::new(SecretKey::random(&mut thread_rng()));
+        let alpha = Scalar::randomize();
+        let proof = ecvrf.prove(&alpha).expect("Can not prove");
+        assert!(proof.verify(&alpha));
+        assert!(!proof.verify(&Scalar::randomize()));
+
+        // Proof is bound to its public key
+        let mut forged = proof;
+        forged.pk = KeyPair::new().public_key;
+        assert!(!forged.verify(&alpha));
+    }
+
+    #[test]
+    fn prove_and_verify_should_round_trip_over_random_inputs() {
+        use crate::helper::GROUP_ORDER;
+        use proptest::{
+            prelude::*,
+            test_runner::{Config, RngAlgorithm, TestCaseError, TestRng, TestRunner},
+        };
+
+        // Random alphas and alphas just below the group order
+        let alpha = prop_oneof![
+            any::<[u8; 32]>(),
+            (1u8..=16).prop_map(|k| {
+                let mut bytes = GROUP_ORDER.b32();
+                bytes[31] -= k;
+                bytes
+            }),
+        ];
+        let mut runner = TestRunner::new_with_rng(
+            Config {
+                cases: 32,
+                ..Config::default()
+            },
+            TestRng::deterministic_rng(RngAlgorithm::ChaCha),
+        );
+        runner
+            .run(
+                &(any::<[u8; 32]>(), alpha, 0usize..256),
+                |(secret_key, alpha, bit)| {
+                    // Zero or out of range secret keys are not keys
+                    let secret_key = match SecretKey::parse(&secret_key) {
+                        Ok(secret_key) => secret_key,
+                        Err(_) => return Ok(()),
+                    };
+                    let alpha = Scalar::from_bytes(&alpha);
+                    let proof = ECVRF::new(secret_key)
+                        .prove(&alpha)
+                        .map_err(|_| TestCaseError::fail("Can not prove"))?;
+                    let verifier = ECVRF::new_verifier(proof.pk);
+                    prop_assert!(verifier.verify(&alpha, &proof));
+
+                    let flip = |mut bytes: [u8; 32]| {
+                        bytes[bit / 8] ^= 1 << (bit % 8);
+                        bytes
+                    };
+                    let mut tampered = proof;
+                    tampered.c = Scalar::from_bytes(&flip(proof.c.b32()));
+                    prop_assert!(!verifier.verify(&alpha, &tampered));
+
+                    let mut tampered = proof;
+                    tampered.s = Scalar::from_bytes(&flip(proof.s.b32()));
+                    prop_assert!(!verifier.verify(&alpha, &tampered));
+
+                    let mut tampered = proof;
+                    let mut x = proof.gamma.x;
+                    x.normalize();
+                    let _ = tampered.gamma.x.set_b32(&flip(x.b32()));
+                    prop_assert!(!verifier.verify(&alpha, &tampered));
+                    Ok(())
+                },
+            )
+            .expect("Prove and verify should round trip");
+    }
+
+    #[test]
+    fn compressed_points_proof_should_not_verify_as_full() {
+        let ecvrf = ECVRF::new(SecretKey::random(&mut thread_rng()));
+        let verifier = ECVRF::new_verifier(ecvrf.public_key);
+        let alpha = Scalar::randomize();
+
+        let compressed = ecvrf.prove_compressed(&alpha).expect("Can not prove");
+        assert!(verifier.verify_compressed(&alpha, &compressed));
+        assert!(!verifier.verify_compressed(&Scalar::randomize(), &compressed));
+        assert!(!verifier.verify(&alpha, &compressed));
+
+        let full = ecvrf.prove(&alpha).expect("Can not prove");
+        assert!(verifier.verify(&alpha, &full));
+        assert!(!verifier.verify_compressed(&alpha, &full));
+        // gamma doesn't depend on the challenge hash, the output is the same
+        assert_eq!(compressed.gamma, full.gamma);
+        assert_eq!(compressed.y, full.y);
+    }
+
+    #[test]
+    fn verify_output_should_compare_expected_y() {
+        let ecvrf = ECVRF::new(SecretKey::random(&mut thread_rng()));
+        let verifier = ECVRF::new_verifier(ecvrf.public_key);
+        let alpha = Scalar::randomize();
+        let proof = ecvrf.prove(&alpha).expect("Can not prove");
+
+        let expected_y = proof.y.b32();
+        assert!(verifier.verify_output(&alpha, &proof, &expected_y));
+
+        let mut other_y = expected_y;
+        other_y[31] ^= 1;
+        assert!(!verifier.verify_output(&alpha, &proof, &other_y));
+        // The expected y doesn't make an invalid proof valid
+        assert!(!verifier.verify_output(&Scalar::randomize(), &proof, &expected_y));
+    }
 }
@@ -9,6 +9,22 @@ pub enum Error {
     UnableToConvertBytesToScalar,
     /// Retries exceeded
     RetriesExceeded,
+    /// Input has an unexpected length
+    InvalidLength,
+    /// Input is not a valid hex string
+    InvalidHexString,
+    /// Secret key is out of range
+    InvalidSecretKey,
+    /// Secret key is required but the instance is verify-only
+    MissingSecretKey,
+    /// Point is not on the curve
+    InvalidPoint,
+    /// Proof transcript is not compatible with the contract verifier
+    IncompatibleProof,
+    /// Scalar is greater than or equal to the group order
+    ScalarOutOfRange,
+    /// Partial proofs or commitments of a threshold proof don't belong together
+    InvalidPartialProofs,
 }
 
 #[cfg(feature = "std")]
@@ -21,6 +37,14 @@ impl core::fmt::Display for Error {
             Error::OutOfRange => write!(f, "Out of range"),
             Error::UnableToConvertBytesToScalar => write!(f, "Unable to convert bytes to scalar"),
             Error::RetriesExceeded => write!(f, "Retries exceeded"),
+            Error::InvalidLength => write!(f, "Invalid length"),
+            Error::InvalidHexString => write!(f, "Invalid hex string"),
+            Error::InvalidSecretKey => write!(f, "Invalid secret key"),
+            Error::MissingSecretKey => write!(f, "Missing secret key"),
+            Error::InvalidPoint => write!(f, "Invalid point"),
+            Error::IncompatibleProof => write!(f, "Incompatible proof"),
+            Error::ScalarOutOfRange => write!(f, "Scalar out of range"),
+            Error::InvalidPartialProofs => write!(f, "Invalid partial proofs"),
         }
     }
 }
@@ -1,7 +1,10 @@
 extern crate alloc;
-use crate::helper::random_bytes;
+use crate::helper::{ecmult, random_bytes};
 use alloc::string::String;
-use libsecp256k1::curve::{Affine, Field, Jacobian, Scalar};
+use libsecp256k1::{
+    curve::{Affine, Field, Jacobian, Scalar},
+    ECMULT_CONTEXT,
+};
 use tiny_keccak::{Hasher, Keccak};
 
 /// Extend Affine
@@ -17,6 +20,12 @@ pub trait AffineExtend {
 
     /// Keccak Affine to bytes array
     fn keccak256(&self) -> [u8; 32];
+
+    /// Add two points, return normalized Affine
+    fn add(&self, other: &Affine) -> Affine;
+
+    /// Multiply point with a scalar using the global context, return normalized Affine
+    fn mul(&self, scalar: &Scalar) -> Affine;
 }
 
 /// Extend Scalar
@@ -35,6 +44,12 @@ pub trait ScalarExtend {
 
     /// Make sure self >= b
     fn gte(&self, b: &Scalar) -> bool;
+
+    /// Modular inverse over the group order, return None for zero
+    fn inverse(&self) -> Option<Scalar>;
+
+    /// Get the address encoded by [address_to_scalar](crate::helper::address_to_scalar)
+    fn to_eth_address(&self) -> [u8; 20];
 }
 
 impl AffineExtend for Affine {
@@ -65,6 +80,14 @@ impl AffineExtend for Affine {
         ra.y.normalize();
         ra
     }
+    fn add(&self, other: &Affine) -> Affine {
+        let r = Jacobian::from_ge(self).add_ge_var(other, None);
+        Affine::from_jacobian(&r)
+    }
+
+    fn mul(&self, scalar: &Scalar) -> Affine {
+        ecmult(&ECMULT_CONTEXT, self, scalar)
+    }
 }
 
 impl ScalarExtend for Scalar {
@@ -118,4 +141,68 @@ impl ScalarExtend for Scalar {
         random_bytes(&mut buf);
         Self::from_bytes(&buf)
     }
+
+    fn inverse(&self) -> Option<Scalar> {
+        if self.is_zero() {
+            None
+        } else {
+            Some(self.inv())
+        }
+    }
+
+    fn to_eth_address(&self) -> [u8; 20] {
+        let mut address = [0u8; 20];
+        address.copy_from_slice(&self.b32()[0..20]);
+        address
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        extends::{AffineExtend, ScalarExtend},
+        helper::{address_to_scalar, calculate_witness_address, ecmult},
+    };
+    use libsecp256k1::{
+        curve::{Affine, Scalar, AFFINE_G},
+        ECMULT_CONTEXT,
+    };
+
+    #[test]
+    fn affine_add_and_mul_should_agree() {
+        let two = Scalar::from_int(2);
+        assert_eq!(AFFINE_G.mul(&two), AFFINE_G.add(&AFFINE_G));
+
+        let three = Scalar::from_int(3);
+        assert_eq!(AFFINE_G.mul(&three), AFFINE_G.mul(&two).add(&AFFINE_G));
+    }
+
+    #[test]
+    fn affine_mul_should_match_ecmult() {
+        let p: Affine = AFFINE_G.mul(&Scalar::randomize());
+        let scalar = Scalar::randomize();
+        assert_eq!(p.mul(&scalar), ecmult(&ECMULT_CONTEXT, &p, &scalar));
+    }
+
+    #[test]
+    fn scalar_inverse_should_handle_zero() {
+        for _ in 0..10 {
+            let a = Scalar::randomize();
+            if a.is_zero() {
+                continue;
+            }
+            assert_eq!(a.inverse().unwrap() * a, Scalar::from_int(1));
+        }
+        assert_eq!(Scalar::from_int(0).inverse(), None);
+    }
+
+    #[test]
+    fn eth_address_should_round_trip_through_scalar() {
+        for _ in 0..10 {
+            let address = calculate_witness_address(&AFFINE_G.mul(&Scalar::randomize()));
+            assert_eq!(address_to_scalar(&address).to_eth_address(), address);
+        }
+        let address = [0u8; 20];
+        assert_eq!(address_to_scalar(&address).to_eth_address(), address);
+    }
 }
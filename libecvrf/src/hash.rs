@@ -3,7 +3,7 @@ use crate::{
     helper::FIELD_SIZE,
 };
 use libsecp256k1::{
-    curve::{Affine, Field, Jacobian, Scalar},
+    curve::{Affine, ECMultContext, Field, Jacobian, Scalar, AFFINE_G},
     ECMULT_GEN_CONTEXT,
 };
 use tiny_keccak::{Hasher, Keccak};
@@ -61,6 +61,23 @@ pub fn hash_to_curve_prefix(alpha: &Scalar, pk: &Affine) -> Affine {
     rv
 }
 
+/// Hash to curve with prefix and alpha only, the public key is not bound.
+/// It's not compatible with the on-chain verifier, use [hash_to_curve_prefix] for proofs
+pub fn hash_to_curve_keyless(alpha: &Scalar) -> Affine {
+    let packed = [
+        // HASH_TO_CURVE_HASH_PREFIX = 1
+        Field::from_int(1).b32().to_vec(),
+        // seed
+        alpha.b32().to_vec(),
+    ]
+    .concat();
+    let mut rv = new_candidate_point(&packed);
+    while !is_on_curve(&rv) {
+        rv = new_candidate_point(rv.x.b32().as_ref());
+    }
+    rv
+}
+
 /// Hash bytes array to a field
 pub fn field_hash(b: &[u8]) -> Field {
     let mut output = [0u8; 32];
@@ -101,6 +118,29 @@ pub fn hash_points(
     Scalar::from_bytes(&output)
 }
 
+/// Hash point to Scalar, each point is fed in its 33 bytes compressed encoding
+/// (parity prefix and x) instead of x and y. It's a distinct ciphersuite, the challenge
+/// differs from [hash_points] for the same points
+pub fn hash_points_compressed(
+    g: &Affine,
+    h: &Affine,
+    pk: &Affine,
+    gamma: &Affine,
+    kg: &Affine,
+    kh: &Affine,
+) -> Scalar {
+    let mut output = [0u8; 32];
+    let mut hasher = Keccak::v256();
+    let all_points = [g, h, pk, gamma, kg, kh];
+    for point in all_points {
+        let prefix = if point.y.is_odd() { 0x03u8 } else { 0x02u8 };
+        hasher.update(&[prefix]);
+        hasher.update(point.x.b32().as_ref());
+    }
+    hasher.finalize(&mut output);
+    Scalar::from_bytes(&output)
+}
+
 /// Hash points with prefix
 /// SCALAR_FROM_CURVE_POINTS_HASH_PREFIX = 2
 pub fn hash_points_prefix(
@@ -138,14 +178,80 @@ pub fn hash_to_curve(alpha: &Scalar, y: Option<&Affine>) -> Affine {
     Affine::from_jacobian(&r)
 }
 
+/// Hash to curve with the multiplication context only, same result as [hash_to_curve].
+/// `alpha * G + y` is computed by a single `ecmult`, so the generator context is never
+/// touched. A verifier does not need to build or link the generator context, with the
+/// `lazy-static-context` feature of libsecp256k1 it's never initialized.
+pub fn hash_to_curve_with_context(
+    ctx_mul: &ECMultContext,
+    alpha: &Scalar,
+    y: Option<&Affine>,
+) -> Affine {
+    let mut r = Jacobian::default();
+    match y {
+        // r = 1 * y + alpha * G
+        Some(v) => ctx_mul.ecmult(&mut r, &Jacobian::from_ge(v), &Scalar::from_int(1), alpha),
+        // r = alpha * G + 0 * G
+        None => ctx_mul.ecmult(
+            &mut r,
+            &Jacobian::from_ge(&AFFINE_G),
+            alpha,
+            &Scalar::from_int(0),
+        ),
+    }
+    Affine::from_jacobian(&r)
+}
+
 #[cfg(test)]
 mod tests {
     use crate::{
         extends::ScalarExtend,
-        hash::{is_on_curve, new_candidate_point},
+        hash::{
+            hash_to_curve, hash_to_curve_keyless, hash_to_curve_prefix, hash_to_curve_with_context,
+            is_on_curve, new_candidate_point,
+        },
         helper::random_bytes,
     };
-    use libsecp256k1::curve::Scalar;
+    use libsecp256k1::{
+        curve::{Affine, Scalar},
+        PublicKey, SecretKey, ECMULT_CONTEXT,
+    };
+    use rand::thread_rng;
+
+    #[test]
+    fn hash_to_curve_keyless_should_be_deterministic_and_on_curve() {
+        let public_key = PublicKey::from_secret_key(&SecretKey::random(&mut thread_rng()));
+        let pub_affine: Affine = public_key.into();
+        for _ in 0..8 {
+            let alpha = Scalar::randomize();
+            let point = hash_to_curve_keyless(&alpha);
+            assert!(is_on_curve(&point));
+            assert!(point.is_valid_var());
+            assert_eq!(point, hash_to_curve_keyless(&alpha));
+            // Binding the public key gives another point
+            assert_ne!(point, hash_to_curve_prefix(&alpha, &pub_affine));
+            assert_ne!(point, hash_to_curve_keyless(&(alpha + Scalar::from_int(1))));
+        }
+    }
+
+    #[test]
+    fn hash_to_curve_with_context_should_match_hash_to_curve() {
+        let public_key = PublicKey::from_secret_key(&SecretKey::random(&mut thread_rng()));
+        let mut pub_affine: Affine = public_key.into();
+        pub_affine.x.normalize();
+        pub_affine.y.normalize();
+        for _ in 0..8 {
+            let alpha = Scalar::randomize();
+            assert_eq!(
+                hash_to_curve_with_context(&ECMULT_CONTEXT, &alpha, Some(&pub_affine)),
+                hash_to_curve(&alpha, Some(&pub_affine))
+            );
+            assert_eq!(
+                hash_to_curve_with_context(&ECMULT_CONTEXT, &alpha, None),
+                hash_to_curve(&alpha, None)
+            );
+        }
+    }
 
     #[test]
     fn point_must_be_on_curve() {
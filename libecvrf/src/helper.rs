@@ -1,7 +1,12 @@
-use crate::extends::AffineExtend;
+extern crate alloc;
+use crate::{
+    error::Error,
+    extends::{AffineExtend, ScalarExtend},
+};
+use alloc::string::String;
 use libsecp256k1::{
-    curve::{Affine, ECMultContext, ECMultGenContext, Field, Jacobian, Scalar},
-    PublicKey,
+    curve::{Affine, ECMultContext, ECMultGenContext, Field, Jacobian, Scalar, AFFINE_G},
+    util, PublicKey, PublicKeyFormat,
 };
 use rand::{thread_rng, RngCore};
 
@@ -81,6 +86,15 @@ pub fn ecmult_gen(context: &ECMultGenContext, ng: &Scalar) -> Affine {
     Affine::from_jacobian(&rj)
 }
 
+/// Check that gamma of a proof is usable, gamma = sk * H can't be the point at infinity
+/// or the generator, such a proof is degenerate and must be rejected
+pub fn is_valid_gamma(gamma: &Affine) -> bool {
+    if gamma.is_infinity() || !gamma.is_valid_var() {
+        return false;
+    }
+    !(gamma.x == AFFINE_G.x && gamma.y == AFFINE_G.y)
+}
+
 /// Calculate witness address from a Affine
 pub fn calculate_witness_address(witness: &Affine) -> [u8; 20] {
     let mut result = [0u8; 20];
@@ -88,6 +102,39 @@ pub fn calculate_witness_address(witness: &Affine) -> [u8; 20] {
     result
 }
 
+/// Encode an address to Scalar the way witness address is stored in proofs,
+/// the address takes the first 20 bytes of the 32 bytes encoding. It round-trips
+/// with [to_eth_address](crate::extends::ScalarExtend::to_eth_address) unless the
+/// encoding exceeds the group order, that needs 16 leading 0xff bytes
+pub fn address_to_scalar(address: &[u8; 20]) -> Scalar {
+    Scalar::from_bytes(address)
+}
+
+/// Decode a 32 bytes big endian hex string to a scalar, the `0x` prefix is optional.
+/// Values that are greater than or equal to [GROUP_ORDER] are rejected instead of reduced
+pub fn hex_to_scalar(value: &str) -> Result<Scalar, Error> {
+    let value = value.trim();
+    let value = value
+        .strip_prefix("0x")
+        .or_else(|| value.strip_prefix("0X"))
+        .unwrap_or(value);
+    if value.len() != 64 {
+        return Err(Error::InvalidLength);
+    }
+    let mut bytes = [0u8; 32];
+    hex::decode_to_slice(value, &mut bytes).map_err(|_| Error::InvalidHexString)?;
+    let mut scalar = Scalar::default();
+    if scalar.set_b32(&bytes).unwrap_u8() == 1 {
+        return Err(Error::ScalarOutOfRange);
+    }
+    Ok(scalar)
+}
+
+/// Encode a scalar to a 32 bytes big endian hex string without `0x` prefix
+pub fn scalar_to_hex(scalar: &Scalar) -> String {
+    hex::encode(scalar.b32())
+}
+
 /// Has a Public Key and return a Ethereum address
 pub fn get_address(pub_key: &PublicKey) -> [u8; 20] {
     let mut affine_pub: Affine = (*pub_key).into();
@@ -96,8 +143,104 @@ pub fn get_address(pub_key: &PublicKey) -> [u8; 20] {
     calculate_witness_address(&affine_pub)
 }
 
+/// Ethereum address of a serialized public key, either 33 bytes compressed or 65 bytes
+/// uncompressed
+pub fn address_from_public_bytes(bytes: &[u8]) -> Result<[u8; 20], Error> {
+    let format = match bytes.len() {
+        util::COMPRESSED_PUBLIC_KEY_SIZE => PublicKeyFormat::Compressed,
+        util::FULL_PUBLIC_KEY_SIZE => PublicKeyFormat::Full,
+        _ => return Err(Error::InvalidLength),
+    };
+    let pub_key = PublicKey::parse_slice(bytes, Some(format)).map_err(|_| Error::InvalidPoint)?;
+    Ok(get_address(&pub_key))
+}
+
 /// Random bytes array
 pub fn random_bytes(buf: &mut [u8]) {
     let mut rng = thread_rng();
     rng.fill_bytes(buf);
 }
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        address_from_public_bytes, get_address, hex_to_scalar, scalar_to_hex, GROUP_ORDER,
+    };
+    use crate::{error::Error, KeyPair};
+    extern crate alloc;
+    use alloc::format;
+    use libsecp256k1::curve::Scalar;
+
+    #[test]
+    fn hex_should_round_trip_to_scalar() {
+        let scalar = Scalar::from_int(0x1234);
+        let encoded = scalar_to_hex(&scalar);
+        assert_eq!(
+            encoded,
+            "0000000000000000000000000000000000000000000000000000000000001234"
+        );
+        assert_eq!(hex_to_scalar(&encoded), Ok(scalar));
+        assert_eq!(hex_to_scalar(&format!("0x{}", encoded)), Ok(scalar));
+
+        // Largest scalar, GROUP_ORDER - 1
+        let max = "fffffffffffffffffffffffffffffffebaaedce6af48a03bbfd25e8cd0364140";
+        assert_eq!(scalar_to_hex(&hex_to_scalar(max).expect("In range")), max);
+    }
+
+    #[test]
+    fn hex_to_scalar_should_reject_invalid_input() {
+        assert_eq!(
+            hex_to_scalar(&scalar_to_hex(&GROUP_ORDER)),
+            Err(Error::ScalarOutOfRange)
+        );
+        assert_eq!(
+            hex_to_scalar(&"ff".repeat(32)),
+            Err(Error::ScalarOutOfRange)
+        );
+        assert_eq!(hex_to_scalar(&"01".repeat(31)), Err(Error::InvalidLength));
+        assert_eq!(hex_to_scalar(&"01".repeat(33)), Err(Error::InvalidLength));
+        assert_eq!(
+            hex_to_scalar(&"zz".repeat(32)),
+            Err(Error::InvalidHexString)
+        );
+    }
+
+    #[test]
+    fn address_should_be_derived_from_public_key_bytes() {
+        let keypair = KeyPair::new();
+        let address = get_address(&keypair.public_key);
+        assert_eq!(
+            address_from_public_bytes(&keypair.public_key.serialize_compressed()),
+            Ok(address)
+        );
+        assert_eq!(
+            address_from_public_bytes(&keypair.public_key.serialize()),
+            Ok(address)
+        );
+    }
+
+    #[test]
+    fn malformed_public_key_bytes_should_be_rejected() {
+        let keypair = KeyPair::new();
+        let full = keypair.public_key.serialize();
+        // Raw 64 bytes without the prefix is not accepted
+        assert_eq!(
+            address_from_public_bytes(&full[1..]),
+            Err(Error::InvalidLength)
+        );
+        assert_eq!(address_from_public_bytes(&[]), Err(Error::InvalidLength));
+
+        let mut wrong_prefix = full;
+        wrong_prefix[0] = 0x02;
+        assert_eq!(
+            address_from_public_bytes(&wrong_prefix),
+            Err(Error::InvalidPoint)
+        );
+        let mut off_curve = keypair.public_key.serialize_compressed();
+        off_curve[1..].copy_from_slice(&[0xff; 32]);
+        assert_eq!(
+            address_from_public_bytes(&off_curve),
+            Err(Error::InvalidPoint)
+        );
+    }
+}
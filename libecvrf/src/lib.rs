@@ -27,6 +27,9 @@ pub mod hash;
 /// Helper functions
 pub mod helper;
 
+/// Threshold EC-VRF with additive shares of the secret key
+pub mod threshold;
+
 /// Re-export libsecp256k1
 pub mod secp256k1 {
     pub use libsecp256k1::*;
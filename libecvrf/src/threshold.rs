@@ -0,0 +1,304 @@
+//! Threshold EC-VRF with 2-of-2 additive shares of the secret key, sk = sk_1 + sk_2.
+//! Proving takes two rounds, each operator commits to gamma_i = sk_i * H and its nonce
+//! (k_i * G, k_i * H), once all commitments are known each operator answers the common
+//! challenge c with s_i = k_i - c * sk_i. The combined proof is an ordinary [ECVRFProof]
+//! and is verified by [ECVRF::verify], no operator can produce it alone.
+extern crate alloc;
+use crate::{
+    error,
+    extends::{AffineExtend, ScalarExtend},
+    hash::{hash_points, hash_to_curve},
+    helper::*,
+    ECVRFProof, ECVRF, MAX_RETRIES,
+};
+use alloc::vec::Vec;
+use libsecp256k1::{
+    curve::{Affine, Scalar, AFFINE_G},
+    PublicKey, SecretKey, ECMULT_CONTEXT, ECMULT_GEN_CONTEXT,
+};
+use rand::thread_rng;
+
+/// Number of shares required to prove
+pub const THRESHOLD: usize = 2;
+
+/// Draw a random non zero scalar that is less than the group order
+fn random_scalar() -> Result<Scalar, error::Error> {
+    let mut k = Scalar::randomize();
+    let mut retries = 0;
+    while k.gte(&GROUP_ORDER) || k.is_zero() {
+        if retries > MAX_RETRIES {
+            return Err(error::Error::RetriesExceeded);
+        }
+        k = Scalar::randomize();
+        retries += 1;
+    }
+    Ok(k)
+}
+
+/// Normalized affine of a public key
+fn public_affine(public_key: &PublicKey) -> Affine {
+    let mut pub_affine: Affine = (*public_key).into();
+    pub_affine.x.normalize();
+    pub_affine.y.normalize();
+    pub_affine
+}
+
+/// Additive share of a secret key, the secret is cleared once the share is dropped
+pub struct SecretKeyShare {
+    secret: Scalar,
+    public_key: PublicKey,
+    group_public_key: PublicKey,
+}
+
+impl SecretKeyShare {
+    /// Split a secret key into [THRESHOLD] additive shares
+    pub fn split(secret_key: &SecretKey) -> Result<[SecretKeyShare; THRESHOLD], error::Error> {
+        let group_public_key = PublicKey::from_secret_key(secret_key);
+        let mut secret: Scalar = (*secret_key).into();
+        let first = SecretKey::random(&mut thread_rng());
+        let mut first_secret: Scalar = first.into();
+        // sk_2 = sk - sk_1
+        first_secret.cond_neg_assign(1.into());
+        let second_secret = secret + first_secret;
+        secret.clear();
+        first_secret.clear();
+        let second =
+            SecretKey::parse(&second_secret.b32()).map_err(|_| error::Error::InvalidSecretKey)?;
+        Ok([
+            SecretKeyShare::new(first, group_public_key),
+            SecretKeyShare::new(second, group_public_key),
+        ])
+    }
+
+    /// Create a share from its secret and the public key of the whole secret key
+    pub fn new(secret_key: SecretKey, group_public_key: PublicKey) -> Self {
+        Self {
+            secret: secret_key.into(),
+            public_key: PublicKey::from_secret_key(&secret_key),
+            group_public_key,
+        }
+    }
+
+    /// Public key of this share
+    pub fn public_key(&self) -> PublicKey {
+        self.public_key
+    }
+
+    /// Public key of the whole secret key, proofs are verified against it
+    pub fn group_public_key(&self) -> PublicKey {
+        self.group_public_key
+    }
+}
+
+impl Drop for SecretKeyShare {
+    fn drop(&mut self) {
+        self.secret.clear();
+    }
+}
+
+/// Secret nonce of the first round, it's consumed by [ECVRF::partial_prove] so it can't
+/// be reused for another challenge
+pub struct NonceShare(Scalar);
+
+impl Drop for NonceShare {
+    fn drop(&mut self) {
+        self.0.clear();
+    }
+}
+
+/// Public commitment of an operator in the first round
+#[derive(Clone, Copy, Debug)]
+pub struct PartialCommitment {
+    /// Public key of the share
+    pub pk: PublicKey,
+    /// gamma_i = sk_i * H
+    pub gamma: Affine,
+    /// u_i = k_i * G
+    pub u: Affine,
+    /// v_i = k_i * H
+    pub v: Affine,
+}
+
+/// Answer of an operator to the common challenge in the second round
+#[derive(Clone, Copy, Debug)]
+pub struct PartialProof {
+    /// Commitment of the operator
+    pub commitment: PartialCommitment,
+    /// Common challenge
+    pub c: Scalar,
+    /// s_i = k_i - c * sk_i
+    pub s: Scalar,
+}
+
+/// Sum of the commitments: gamma, U and V of the combined proof
+fn aggregate(commitments: &[PartialCommitment]) -> Result<(Affine, Affine, Affine), error::Error> {
+    if commitments.len() != THRESHOLD {
+        return Err(error::Error::InvalidPartialProofs);
+    }
+    let mut gamma = commitments[0].gamma;
+    let mut u = commitments[0].u;
+    let mut v = commitments[0].v;
+    for commitment in commitments[1..].iter() {
+        gamma = gamma.add(&commitment.gamma);
+        u = u.add(&commitment.u);
+        v = v.add(&commitment.v);
+    }
+    if !is_valid_gamma(&gamma) {
+        return Err(error::Error::InvalidPoint);
+    }
+    Ok((gamma, u, v))
+}
+
+impl ECVRF<'_> {
+    /// First round of threshold proving, commit to gamma_i and a fresh nonce
+    pub fn partial_commit(
+        share: &SecretKeyShare,
+        alpha: &Scalar,
+    ) -> Result<(NonceShare, PartialCommitment), error::Error> {
+        // H is derived from the group public key, the same H as an ordinary proof
+        let h = hash_to_curve(alpha, Some(&public_affine(&share.group_public_key)));
+        let k = random_scalar()?;
+        let commitment = PartialCommitment {
+            pk: share.public_key,
+            gamma: ecmult(&ECMULT_CONTEXT, &h, &share.secret),
+            u: ecmult_gen(&ECMULT_GEN_CONTEXT, &k),
+            v: ecmult(&ECMULT_CONTEXT, &h, &k),
+        };
+        Ok((NonceShare(k), commitment))
+    }
+
+    /// Second round of threshold proving, answer the challenge of all commitments,
+    /// the commitment of this share must be one of them
+    pub fn partial_prove(
+        share: &SecretKeyShare,
+        nonce: NonceShare,
+        alpha: &Scalar,
+        commitments: &[PartialCommitment],
+    ) -> Result<PartialProof, error::Error> {
+        let commitment = commitments
+            .iter()
+            .find(|commitment| commitment.pk == share.public_key)
+            .copied()
+            .ok_or(error::Error::InvalidPartialProofs)?;
+        let pk_shares = commitments.iter().map(|c| c.pk).collect::<Vec<PublicKey>>();
+        let group_public_key =
+            PublicKey::combine(&pk_shares).map_err(|_| error::Error::InvalidPoint)?;
+        if group_public_key != share.group_public_key {
+            return Err(error::Error::InvalidPartialProofs);
+        }
+        let pub_affine = public_affine(&group_public_key);
+        let h = hash_to_curve(alpha, Some(&pub_affine));
+        let (gamma, u, v) = aggregate(commitments)?;
+
+        // c = ECVRF_hash_points(G, H, public_key, gamma, U, V)
+        let c = hash_points(&AFFINE_G, &h, &pub_affine, &gamma, &u, &v);
+
+        // s_i = (k_i - c * sk_i) mod p
+        let mut neg_c = c;
+        neg_c.cond_neg_assign(1.into());
+        let s = nonce.0 + neg_c * share.secret;
+
+        Ok(PartialProof { commitment, c, s })
+    }
+
+    /// Combine the answers of all shares into an ordinary proof
+    pub fn combine_partials(partials: &[PartialProof]) -> Result<ECVRFProof, error::Error> {
+        if partials.len() != THRESHOLD || partials.iter().any(|p| p.c != partials[0].c) {
+            return Err(error::Error::InvalidPartialProofs);
+        }
+        let commitments = partials
+            .iter()
+            .map(|p| p.commitment)
+            .collect::<Vec<PartialCommitment>>();
+        let (gamma, _, _) = aggregate(&commitments)?;
+        let pk_shares = commitments.iter().map(|c| c.pk).collect::<Vec<PublicKey>>();
+        let pk = PublicKey::combine(&pk_shares).map_err(|_| error::Error::InvalidPoint)?;
+
+        // s = sum(s_i) = sum(k_i) - c * sum(sk_i) = k - c * sk
+        let s = partials[1..]
+            .iter()
+            .fold(partials[0].s, |acc, partial| acc + partial.s);
+
+        Ok(ECVRFProof {
+            gamma,
+            c: partials[0].c,
+            s,
+            y: Scalar::from_bytes(&gamma.keccak256()),
+            pk,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{PartialCommitment, SecretKeyShare};
+    use crate::{error::Error, extends::ScalarExtend, ECVRF};
+    use libsecp256k1::{curve::Scalar, PublicKey, SecretKey};
+    use rand::thread_rng;
+
+    #[test]
+    fn two_shares_should_combine_into_valid_proof() {
+        let secret_key = SecretKey::random(&mut thread_rng());
+        let public_key = PublicKey::from_secret_key(&secret_key);
+        let [first, second] = SecretKeyShare::split(&secret_key).expect("Unable to split key");
+        assert_eq!(first.group_public_key(), public_key);
+        assert_ne!(first.public_key(), public_key);
+        assert_ne!(second.public_key(), public_key);
+
+        let alpha = Scalar::randomize();
+        let (first_nonce, first_commitment) =
+            ECVRF::partial_commit(&first, &alpha).expect("Unable to commit");
+        let (second_nonce, second_commitment) =
+            ECVRF::partial_commit(&second, &alpha).expect("Unable to commit");
+        let commitments = [first_commitment, second_commitment];
+
+        let partials = [
+            ECVRF::partial_prove(&first, first_nonce, &alpha, &commitments)
+                .expect("Unable to prove"),
+            ECVRF::partial_prove(&second, second_nonce, &alpha, &commitments)
+                .expect("Unable to prove"),
+        ];
+        let proof = ECVRF::combine_partials(&partials).expect("Unable to combine");
+
+        let verifier = ECVRF::new_verifier(public_key);
+        assert!(verifier.verify(&alpha, &proof));
+        assert_eq!(proof.pk, public_key);
+        // gamma is deterministic, the output is the same as a single operator
+        let single = ECVRF::new(secret_key)
+            .prove(&alpha)
+            .expect("Unable to prove");
+        assert_eq!(proof.y, single.y);
+
+        // A single share is not enough
+        assert_eq!(
+            ECVRF::combine_partials(&partials[..1]).unwrap_err(),
+            Error::InvalidPartialProofs
+        );
+        let mut mismatched = partials;
+        mismatched[1].c = mismatched[1].c + Scalar::from_int(1);
+        assert_eq!(
+            ECVRF::combine_partials(&mismatched).unwrap_err(),
+            Error::InvalidPartialProofs
+        );
+    }
+
+    #[test]
+    fn partial_prove_should_reject_foreign_commitments() {
+        let secret_key = SecretKey::random(&mut thread_rng());
+        let [first, _] = SecretKeyShare::split(&secret_key).expect("Unable to split key");
+        let [other, _] = SecretKeyShare::split(&secret_key).expect("Unable to split key");
+        let alpha = Scalar::randomize();
+
+        let (_, first_commitment) =
+            ECVRF::partial_commit(&first, &alpha).expect("Unable to commit");
+        let (other_nonce, other_commitment) =
+            ECVRF::partial_commit(&other, &alpha).expect("Unable to commit");
+
+        // Shares of two different splits don't add up to the group key
+        let commitments: [PartialCommitment; 2] = [first_commitment, other_commitment];
+        assert_eq!(
+            ECVRF::partial_prove(&other, other_nonce, &alpha, &commitments).unwrap_err(),
+            Error::InvalidPartialProofs
+        );
+    }
+}
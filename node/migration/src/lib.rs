@@ -3,6 +3,11 @@ pub use sea_orm_migration::prelude::*;
 mod m20220101_000001_create_table_keyring;
 mod m20221229_005309_create_table_receiver;
 mod m20230115_172637_create_table_randomness;
+mod m20241001_000001_add_request_id_to_randomness;
+mod m20241002_000001_add_unique_epoch_to_randomness;
+mod m20241003_000001_add_ciphersuite_to_randomness;
+mod m20241004_000001_add_proof_to_randomness;
+mod m20241005_000001_create_table_key_rotation;
 
 pub struct Migrator;
 
@@ -13,6 +18,11 @@ impl MigratorTrait for Migrator {
             Box::new(m20220101_000001_create_table_keyring::Migration),
             Box::new(m20221229_005309_create_table_receiver::Migration),
             Box::new(m20230115_172637_create_table_randomness::Migration),
+            Box::new(m20241001_000001_add_request_id_to_randomness::Migration),
+            Box::new(m20241002_000001_add_unique_epoch_to_randomness::Migration),
+            Box::new(m20241003_000001_add_ciphersuite_to_randomness::Migration),
+            Box::new(m20241004_000001_add_proof_to_randomness::Migration),
+            Box::new(m20241005_000001_create_table_key_rotation::Migration),
         ]
     }
 }
@@ -0,0 +1,57 @@
+use sea_orm_migration::prelude::*;
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        // Client supplied request id, it's optional so old records are kept as NULL
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Randomness::Table)
+                    .add_column(ColumnDef::new(Randomness::RequestId).string().null())
+                    .to_owned(),
+            )
+            .await?;
+        // A request id can only be used once per receiver, NULL values are not compared
+        manager
+            .create_index(
+                Index::create()
+                    .name("index_receiver_request_id")
+                    .table(Randomness::Table)
+                    .col(Randomness::ReceiverId)
+                    .col(Randomness::RequestId)
+                    .unique()
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_index(
+                Index::drop()
+                    .name("index_receiver_request_id")
+                    .table(Randomness::Table)
+                    .to_owned(),
+            )
+            .await?;
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Randomness::Table)
+                    .drop_column(Randomness::RequestId)
+                    .to_owned(),
+            )
+            .await
+    }
+}
+
+/// Learn more at https://docs.rs/sea-query#iden
+#[derive(Iden)]
+enum Randomness {
+    Table,
+    ReceiverId,
+    RequestId,
+}
@@ -0,0 +1,40 @@
+use sea_orm_migration::prelude::*;
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        // Two racing inserts must not create the same epoch for a receiver
+        manager
+            .create_index(
+                Index::create()
+                    .name("index_receiver_epoch")
+                    .table(Randomness::Table)
+                    .col(Randomness::ReceiverId)
+                    .col(Randomness::Epoch)
+                    .unique()
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_index(
+                Index::drop()
+                    .name("index_receiver_epoch")
+                    .table(Randomness::Table)
+                    .to_owned(),
+            )
+            .await
+    }
+}
+
+/// Learn more at https://docs.rs/sea-query#iden
+#[derive(Iden)]
+enum Randomness {
+    Table,
+    ReceiverId,
+    Epoch,
+}
@@ -0,0 +1,79 @@
+use sea_orm_migration::prelude::*;
+
+use crate::m20220101_000001_create_table_keyring::Keyring;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        // Each row retires a key, randomness rows up to last_randomness_id were proven with old_public_key
+        manager
+            .create_table(
+                Table::create()
+                    .table(KeyRotation::Table)
+                    .if_not_exists()
+                    .col(
+                        ColumnDef::new(KeyRotation::Id)
+                            .big_integer()
+                            .auto_increment()
+                            .primary_key(),
+                    )
+                    .col(
+                        ColumnDef::new(KeyRotation::KeyringId)
+                            .big_integer()
+                            .not_null(),
+                    )
+                    .col(
+                        ColumnDef::new(KeyRotation::OldPublicKey)
+                            .string()
+                            .not_null(),
+                    )
+                    .col(
+                        ColumnDef::new(KeyRotation::NewPublicKey)
+                            .string()
+                            .not_null(),
+                    )
+                    .col(
+                        ColumnDef::new(KeyRotation::LastRandomnessId)
+                            .big_integer()
+                            .not_null(),
+                    )
+                    .col(
+                        ColumnDef::new(KeyRotation::CreatedDate)
+                            .timestamp()
+                            .extra("DEFAULT CURRENT_TIMESTAMP".to_string())
+                            .not_null(),
+                    )
+                    .foreign_key(
+                        ForeignKeyCreateStatement::new()
+                            .name("link_key_rotation_to_keyring")
+                            .from_tbl(KeyRotation::Table)
+                            .from_col(KeyRotation::KeyringId)
+                            .to_tbl(Keyring::Table)
+                            .to_col(Keyring::Id),
+                    )
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(KeyRotation::Table).to_owned())
+            .await
+    }
+}
+
+/// Learn more at https://docs.rs/sea-query#iden
+#[derive(Iden)]
+pub enum KeyRotation {
+    Table,
+    Id,
+    KeyringId,
+    OldPublicKey,
+    NewPublicKey,
+    LastRandomnessId,
+    CreatedDate,
+}
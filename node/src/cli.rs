@@ -1,9 +1,14 @@
-use clap::{arg, Command};
+use clap::{arg, value_parser, Command};
 use dotenv::dotenv;
-use libecvrf::{helper::random_bytes, KeyPair};
+use libecvrf::{
+    extends::{AffineExtend, ScalarExtend},
+    helper::{hex_to_scalar, scalar_to_hex},
+    KeyPair, ECVRF,
+};
 use node::{
+    ethereum::EthAddress,
     postgres_sql::Postgres,
-    rpc::{decode_address, decode_i64, decode_name},
+    rpc::{check_private_receiver, decode_name, decode_network},
 };
 use serde_json::json;
 use std::env;
@@ -28,12 +33,72 @@ fn cli() -> Command {
                 .arg(arg!(network: <NETWORK> "Network ID of target platform"))
                 .arg_required_else_help(true),
         )
+        .subcommand(
+            Command::new("gen-chain")
+                .about("Generate a chain of epochs offline and print it as JSON")
+                .arg(arg!(secret_key: <SECRET_KEY> "Hex encoded secret key"))
+                .arg(arg!(alpha: <ALPHA> "Hex encoded 32 bytes genesis alpha"))
+                .arg(arg!(count: <COUNT> "Number of epochs").value_parser(value_parser!(usize)))
+                .arg_required_else_help(true),
+        )
+}
+
+/// Generate a chain of epochs, no database is needed
+fn gen_chain(
+    secret_key: &str,
+    alpha: &str,
+    count: usize,
+) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+    let keypair = KeyPair::try_from(secret_key).map_err(|e| e.to_string())?;
+    let genesis_alpha = hex_to_scalar(alpha).map_err(|e| e.to_string())?;
+
+    let chain = ECVRF::generate_chain(&keypair.secret_key, &genesis_alpha, count)
+        .map_err(|e| e.to_string())?;
+    let epochs = chain
+        .iter()
+        .enumerate()
+        .map(|(epoch, proof)| {
+            json!({
+                "epoch": epoch,
+                "alpha": scalar_to_hex(&proof.alpha),
+                "gamma": proof.gamma.to_hex_string(),
+                "c": scalar_to_hex(&proof.c),
+                "s": scalar_to_hex(&proof.s),
+                "y": scalar_to_hex(&proof.y),
+                "witness_address": hex::encode(proof.witness_address.to_eth_address()),
+                "witness_gamma": proof.witness_gamma.to_hex_string(),
+                "witness_hash": proof.witness_hash.to_hex_string(),
+                "inverse_z": hex::encode(proof.inverse_z.b32()),
+            })
+        })
+        .collect::<Vec<_>>();
+    println!(
+        "{}",
+        serde_json::to_string_pretty(&json!({
+            "public_key": hex::encode(keypair.public_key.serialize()),
+            "epochs": epochs,
+        }))?
+    );
+    Ok(())
 }
 
 #[tokio::main]
 async fn main() -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
     dotenv().ok();
     let matches = cli().get_matches();
+    if let Some(("gen-chain", sub_matches)) = matches.subcommand() {
+        return gen_chain(
+            sub_matches
+                .get_one::<String>("secret_key")
+                .expect("Unable to get secret key"),
+            sub_matches
+                .get_one::<String>("alpha")
+                .expect("Unable to get alpha"),
+            *sub_matches
+                .get_one::<usize>("count")
+                .expect("Unable to get count"),
+        );
+    }
     let database_url = env::var("DATABASE_URL").expect("Can not connect to the database");
     // @todo: Move these to another module, we should separate between KEYS and API
     let postgres = Postgres::new(database_url).await;
@@ -41,33 +106,17 @@ async fn main() -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
     match matches.subcommand() {
         Some(("user", sub_matches)) => {
             let keyring = postgres.table_keyring();
-            let new_key_pair = KeyPair::new();
             let username = sub_matches
                 .get_one::<String>("username")
                 .expect("Unable to get username from argument")
                 .trim()
                 .to_string();
-            let username = decode_name(username);
-            let mut bytes = [0u8; 24];
-            random_bytes(&mut bytes);
-            keyring
-                .insert(json!({
-                    "username": username,
-                    "hmac_secret": hex::encode(bytes),
-                    "public_key": hex::encode(new_key_pair.public_key.serialize()),
-                    "secret_key": hex::encode(new_key_pair.secret_key.serialize()),
-                }))
-                .await?;
+            let username = decode_name(&username)?;
+            let record = keyring.bootstrap_user(username.clone(), None).await?;
             println!("Add new user: {}", username);
-            println!(" - hmac_secret: {}", hex::encode(bytes));
-            println!(
-                " - public_key: {}",
-                hex::encode(new_key_pair.public_key.serialize())
-            );
-            println!(
-                " - secret_key: {}",
-                hex::encode(new_key_pair.secret_key.serialize())
-            );
+            println!(" - hmac_secret: {}", record.hmac_secret);
+            println!(" - public_key: {}", record.public_key);
+            println!(" - secret_key: {}", record.secret_key);
         }
         Some(("receiver", sub_matches)) => {
             let table_receiver = postgres.table_receiver();
@@ -87,13 +136,14 @@ async fn main() -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
                 .trim()
                 .to_string();
 
-            let name = decode_name(name);
-            let address = decode_address(address);
-            let network_id = decode_i64(network_id);
+            let name = decode_name(&name)?;
+            let address = address.parse::<EthAddress>()?;
+            check_private_receiver(&address)?;
+            let network_id = decode_network(&network_id)?;
             table_receiver
                 .insert(json!({
                     "name": name,
-                    "address": address,
+                    "address": address.to_string(),
                     "network": network_id,
                     "nonce": 0,
                 }))
@@ -0,0 +1,508 @@
+use crate::{randomness::Model, Error, ErrorCode};
+use libecvrf::{
+    extends::{AffineExtend, ScalarExtend},
+    helper::{address_to_scalar, calculate_witness_address, hex_to_scalar},
+    secp256k1::{
+        curve::{Affine, Field, Jacobian, Scalar},
+        PublicKey, ECMULT_CONTEXT,
+    },
+    ECVRFContractProof, ECVRFProof, ECVRF,
+};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+/// ECVRF ciphersuites that an epoch could be produced with
+#[derive(Debug, Clone, Copy, Default, Eq, PartialEq)]
+pub enum Ciphersuite {
+    /// secp256k1 with Keccak256, the suite verified by Orand smart contracts
+    #[default]
+    EcvrfKeccak256,
+}
+
+impl Ciphersuite {
+    /// Get the name of the ciphersuite that is stored along with the epoch
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Ciphersuite::EcvrfKeccak256 => "ecvrf-keccak256",
+        }
+    }
+}
+
+impl TryFrom<&str> for Ciphersuite {
+    type Error = Error;
+
+    fn try_from(value: &str) -> Result<Self, Self::Error> {
+        match value {
+            "ecvrf-keccak256" => Ok(Ciphersuite::EcvrfKeccak256),
+            _ => Err(Error(ErrorCode::NotImplemented, "Unsupported ciphersuite")),
+        }
+    }
+}
+
+/// How proofs are stored in the randomness table
+#[derive(Debug, Clone, Copy, Default, Eq, PartialEq)]
+pub enum ProofFormat {
+    /// Every proof component is stored in its own hex column
+    #[default]
+    Full,
+    /// Proof is stored as a single binary blob, only alpha and y keep their columns
+    Compact,
+}
+
+impl TryFrom<&str> for ProofFormat {
+    type Error = Error;
+
+    fn try_from(value: &str) -> Result<Self, Self::Error> {
+        match value.trim().to_lowercase().as_str() {
+            "full" => Ok(ProofFormat::Full),
+            "compact" => Ok(ProofFormat::Compact),
+            _ => Err(Error(
+                ErrorCode::BadRequest,
+                "Proof format must be full or compact",
+            )),
+        }
+    }
+}
+
+/// Fill the proof columns of a compactly stored epoch from its blob,
+/// epochs stored in full format are returned as is
+pub fn expand_proof(mut record: Model) -> Result<Model, Error> {
+    let proof = match record.proof.take() {
+        Some(proof) => proof,
+        None => return Ok(record),
+    };
+    let contract_proof = ECVRFContractProof::from_bytes(&proof)
+        .map_err(|_| Error(ErrorCode::Internal, "Stored proof is malformed"))?;
+    record.gamma = contract_proof.gamma.to_hex_string();
+    record.c = hex::encode(contract_proof.c.b32());
+    record.s = hex::encode(contract_proof.s.b32());
+    record.witness_address = hex::encode(contract_proof.witness_address.to_eth_address());
+    record.witness_gamma = contract_proof.witness_gamma.to_hex_string();
+    record.witness_hash = contract_proof.witness_hash.to_hex_string();
+    record.inverse_z = hex::encode(contract_proof.inverse_z.b32());
+    Ok(record)
+}
+
+/// Decode a stored 32 bytes scalar
+fn decode_scalar(value: &str) -> Result<Scalar, Error> {
+    hex_to_scalar(value).map_err(|e| match e {
+        libecvrf::error::Error::ScalarOutOfRange => {
+            Error(ErrorCode::Internal, "Stored scalar is out of range")
+        }
+        _ => Error(ErrorCode::Internal, "Stored scalar is malformed"),
+    })
+}
+
+/// Decode a stored 32 bytes field element
+fn decode_field(value: &str) -> Result<Field, Error> {
+    let mut bytes = [0u8; 32];
+    hex::decode_to_slice(value, &mut bytes)
+        .map_err(|_| Error(ErrorCode::Internal, "Stored field element is malformed"))?;
+    let mut field = Field::default();
+    if !field.set_b32(&bytes) {
+        return Err(Error(
+            ErrorCode::Internal,
+            "Stored field element is out of range",
+        ));
+    }
+    Ok(field)
+}
+
+/// Decode a stored point, it's encoded as x and y coordinates
+fn decode_affine(value: &str) -> Result<Affine, Error> {
+    let mut bytes = [0u8; 64];
+    hex::decode_to_slice(value, &mut bytes)
+        .map_err(|_| Error(ErrorCode::Internal, "Stored point is malformed"))?;
+    let mut x = Field::default();
+    let mut y = Field::default();
+    let mut x_bytes = [0u8; 32];
+    let mut y_bytes = [0u8; 32];
+    x_bytes.copy_from_slice(&bytes[..32]);
+    y_bytes.copy_from_slice(&bytes[32..]);
+    if !x.set_b32(&x_bytes) || !y.set_b32(&y_bytes) {
+        return Err(Error(ErrorCode::Internal, "Stored point is out of range"));
+    }
+    let mut point = Affine::default();
+    point.set_xy(&x, &y);
+    if !point.is_valid_var() {
+        return Err(Error(
+            ErrorCode::Internal,
+            "Stored point is not on the curve",
+        ));
+    }
+    Ok(point)
+}
+
+/// Rebuild the smart contract proof of a stored epoch, the witness address
+/// is stored as its first 20 bytes, the same way it was produced
+pub fn decode_contract_proof(
+    public_key: &PublicKey,
+    record: &Model,
+) -> Result<ECVRFContractProof, Error> {
+    let mut witness_address = [0u8; 20];
+    hex::decode_to_slice(&record.witness_address, &mut witness_address)
+        .map_err(|_| Error(ErrorCode::Internal, "Stored witness address is malformed"))?;
+    Ok(ECVRFContractProof {
+        pk: *public_key,
+        gamma: decode_affine(&record.gamma)?,
+        c: decode_scalar(&record.c)?,
+        s: decode_scalar(&record.s)?,
+        y: decode_scalar(&record.y)?,
+        alpha: decode_scalar(&record.alpha)?,
+        witness_address: address_to_scalar(&witness_address),
+        witness_gamma: decode_affine(&record.witness_gamma)?,
+        witness_hash: decode_affine(&record.witness_hash)?,
+        inverse_z: decode_field(&record.inverse_z)?,
+    })
+}
+
+/// Verify a stored epoch against the given public key, the verify path is
+/// picked by the ciphersuite recorded for the epoch
+pub fn verify_epoch(public_key: &PublicKey, record: &Model) -> Result<bool, Error> {
+    match Ciphersuite::try_from(record.ciphersuite.as_str())? {
+        // Epochs are proven for the smart contract verifier
+        Ciphersuite::EcvrfKeccak256 => {
+            let proof = decode_contract_proof(public_key, record)?;
+            Ok(ECVRF::new_verifier(*public_key).verify_contract(&proof))
+        }
+    }
+}
+
+/// Check the stored witness address of an epoch, it must be the address of
+/// U = c * pk + s * G recomputed from the stored proof. A mismatch means the row
+/// was corrupted even if the other columns are intact
+pub fn verify_witness_address(public_key: &PublicKey, record: &Model) -> Result<bool, Error> {
+    let mut witness_address = [0u8; 20];
+    hex::decode_to_slice(&record.witness_address, &mut witness_address)
+        .map_err(|_| Error(ErrorCode::Internal, "Stored witness address is malformed"))?;
+    let c = decode_scalar(&record.c)?;
+    let s = decode_scalar(&record.s)?;
+    let mut pub_affine: Affine = (*public_key).into();
+    pub_affine.x.normalize();
+    pub_affine.y.normalize();
+    let mut u = Jacobian::default();
+    ECMULT_CONTEXT.ecmult(&mut u, &Jacobian::from_ge(&pub_affine), &c, &s);
+    Ok(calculate_witness_address(&Affine::from_jacobian(&u)) == witness_address)
+}
+
+/// Domain separator of the genesis alpha of public epochs
+const GENESIS_ALPHA_DOMAIN: &[u8] = b"orand-genesis-alpha";
+
+/// Alpha of epoch 0 of the public chain of a network, keccak256 of the domain, the
+/// network and the operator public key. The public chain could be reproduced from
+/// the key alone and restarting before the first epoch doesn't change it
+pub fn genesis_alpha(public_key: &PublicKey, network: u64) -> Scalar {
+    let mut buf = Vec::with_capacity(GENESIS_ALPHA_DOMAIN.len() + 8 + 65);
+    buf.extend_from_slice(GENESIS_ALPHA_DOMAIN);
+    buf.extend_from_slice(&network.to_be_bytes());
+    buf.extend_from_slice(&public_key.serialize());
+    Scalar::keccak256(&buf)
+}
+
+/// Largest number of epochs that are verified in a single request
+pub const MAX_VERIFIED_EPOCHS: usize = 20;
+
+/// Serialize epochs along with their verification status, an epoch that can't be
+/// decoded or was produced with an unsupported ciphersuite is reported as not verified.
+/// The stored witness address is cross-checked separately as `witness_valid`.
+/// Only the first [MAX_VERIFIED_EPOCHS] epochs are checked, both flags of the rest are `null`
+pub fn verified_epochs(public_key: &PublicKey, records: &[Model]) -> Result<Vec<Value>, Error> {
+    verified_epochs_by(records, |_| *public_key)
+}
+
+/// Same as [verified_epochs] but each epoch is verified with the key that proved it,
+/// epochs of a rotated operator key span more than one public key
+pub fn verified_epochs_by<F>(records: &[Model], public_key_of: F) -> Result<Vec<Value>, Error>
+where
+    F: Fn(&Model) -> PublicKey,
+{
+    records
+        .iter()
+        .enumerate()
+        .map(|(i, record)| {
+            let mut value = serde_json::to_value(record)
+                .map_err(|_| Error(ErrorCode::Internal, "Unable to serialize epoch"))?;
+            if i >= MAX_VERIFIED_EPOCHS {
+                value["verified"] = Value::Null;
+                value["witness_valid"] = Value::Null;
+                return Ok(value);
+            }
+            let public_key = public_key_of(record);
+            value["verified"] = Value::Bool(verify_epoch(&public_key, record).unwrap_or(false));
+            value["witness_valid"] =
+                Value::Bool(verify_witness_address(&public_key, record).unwrap_or(false));
+            Ok(value)
+        })
+        .collect()
+}
+
+/// Epoch of an exported chain, only the components of the ordinary proof are exported
+#[derive(Debug, Clone, Eq, PartialEq, Serialize, Deserialize)]
+pub struct ChainEpoch {
+    /// Epoch id
+    pub epoch: i64,
+    /// Seed alpha, it's y of the previous epoch
+    pub alpha: String,
+    /// gamma
+    pub gamma: String,
+    /// c
+    pub c: String,
+    /// s
+    pub s: String,
+    /// Result y
+    pub y: String,
+    /// Uncompressed public key that proved this epoch if it isn't the key of the bundle,
+    /// epochs before a key rotation were proven with the retired key
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub public_key: Option<String>,
+}
+
+impl From<&Model> for ChainEpoch {
+    fn from(value: &Model) -> Self {
+        ChainEpoch {
+            epoch: value.epoch,
+            alpha: value.alpha.clone(),
+            gamma: value.gamma.clone(),
+            c: value.c.clone(),
+            s: value.s.clone(),
+            y: value.y.clone(),
+            public_key: None,
+        }
+    }
+}
+
+/// Epoch chain of a receiver with the public key to verify it offline
+#[derive(Debug, Clone, Eq, PartialEq, Serialize, Deserialize)]
+pub struct ChainBundle {
+    /// Uncompressed public key of the node
+    pub public_key: String,
+    /// Epochs in ascending order
+    pub epochs: Vec<ChainEpoch>,
+}
+
+/// Parse a hex encoded public key of an exported chain
+fn parse_chain_public_key(value: &str) -> Result<PublicKey, Error> {
+    hex::decode(value)
+        .ok()
+        .and_then(|bytes| PublicKey::parse_slice(&bytes, None).ok())
+        .ok_or(Error(ErrorCode::BadRequest, "Public key is malformed"))
+}
+
+/// Verify an exported chain offline, every proof must verify and each epoch must be
+/// seeded by the result of the previous one. An epoch is verified with its own public
+/// key if it carries one, otherwise with the key of the bundle. The contract witnesses
+/// are not exported, they are recomputed from the proof
+pub fn verify_chain(bundle: &ChainBundle) -> Result<bool, Error> {
+    let bundle_public_key = parse_chain_public_key(&bundle.public_key)?;
+    for (i, epoch) in bundle.epochs.iter().enumerate() {
+        if i > 0 {
+            let previous = &bundle.epochs[i - 1];
+            if epoch.epoch != previous.epoch + 1 || epoch.alpha != previous.y {
+                return Ok(false);
+            }
+        }
+        let public_key = match &epoch.public_key {
+            Some(public_key) => parse_chain_public_key(public_key)?,
+            None => bundle_public_key,
+        };
+        let alpha = decode_scalar(&epoch.alpha)?;
+        let proof = ECVRFProof {
+            gamma: decode_affine(&epoch.gamma)?,
+            c: decode_scalar(&epoch.c)?,
+            s: decode_scalar(&epoch.s)?,
+            y: decode_scalar(&epoch.y)?,
+            pk: public_key,
+        };
+        match ECVRFContractProof::from_ordinary(&proof, &alpha) {
+            Ok(contract_proof)
+                if ECVRF::new_verifier(public_key).verify_contract(&contract_proof) => {}
+            _ => return Ok(false),
+        }
+    }
+    Ok(true)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        expand_proof, verified_epochs, verify_epoch, verify_witness_address, Ciphersuite,
+        ProofFormat, MAX_VERIFIED_EPOCHS,
+    };
+    use crate::{randomness::Model, ErrorCode};
+    use libecvrf::{
+        extends::{AffineExtend, ScalarExtend},
+        secp256k1::curve::Scalar,
+        KeyPair, ECVRF,
+    };
+    use sea_orm::prelude::DateTime;
+    use serde_json::Value;
+
+    fn epoch_record(keypair: &KeyPair, ciphersuite: &str) -> Model {
+        let alpha = Scalar::randomize();
+        let proof = ECVRF::new(keypair.secret_key)
+            .prove_contract(&alpha)
+            .expect("Unable to prove");
+        Model {
+            id: 1,
+            keyring_id: 1,
+            receiver_id: 1,
+            epoch: 0,
+            alpha: hex::encode(alpha.b32()),
+            gamma: proof.gamma.to_hex_string(),
+            c: hex::encode(proof.c.b32()),
+            s: hex::encode(proof.s.b32()),
+            y: hex::encode(proof.y.b32()),
+            witness_address: hex::encode(proof.witness_address.to_eth_address()),
+            witness_gamma: proof.witness_gamma.to_hex_string(),
+            witness_hash: proof.witness_hash.to_hex_string(),
+            inverse_z: hex::encode(proof.inverse_z.b32()),
+            signature_proof: "0".repeat(130),
+            request_id: None,
+            ciphersuite: ciphersuite.to_string(),
+            proof: None,
+            created_date: DateTime::default(),
+        }
+    }
+
+    #[test]
+    fn ciphersuite_should_round_trip() {
+        let ciphersuite = Ciphersuite::default();
+        assert_eq!(ciphersuite.as_str(), "ecvrf-keccak256");
+        assert_eq!(Ciphersuite::try_from(ciphersuite.as_str()), Ok(ciphersuite));
+    }
+
+    #[test]
+    fn epoch_should_be_verified_with_its_ciphersuite() {
+        let keypair = KeyPair::new();
+        let record = epoch_record(&keypair, "ecvrf-keccak256");
+        assert_eq!(verify_epoch(&keypair.public_key, &record), Ok(true));
+
+        // Proof does not belong to another key
+        let other = KeyPair::new();
+        assert_eq!(verify_epoch(&other.public_key, &record), Ok(false));
+    }
+
+    #[test]
+    fn unknown_ciphersuite_should_be_rejected() {
+        let keypair = KeyPair::new();
+        let record = epoch_record(&keypair, "ecvrf-sha3-256");
+        let err = verify_epoch(&keypair.public_key, &record)
+            .expect_err("Unknown ciphersuite must be rejected");
+        assert_eq!(err.code(), ErrorCode::NotImplemented);
+    }
+
+    #[test]
+    fn epochs_should_be_serialized_with_verification_status() {
+        let keypair = KeyPair::new();
+        let valid = epoch_record(&keypair, "ecvrf-keccak256");
+        let mut tampered = epoch_record(&keypair, "ecvrf-keccak256");
+        tampered.y = valid.y.clone();
+        let unknown = epoch_record(&keypair, "ecvrf-sha3-256");
+
+        let epochs = verified_epochs(&keypair.public_key, &[valid.clone(), tampered, unknown])
+            .expect("Unable to verify epochs");
+        assert_eq!(epochs[0]["verified"], true);
+        assert_eq!(epochs[0]["witness_valid"], true);
+        assert_eq!(epochs[0]["alpha"], valid.alpha);
+        assert_eq!(epochs[1]["verified"], false);
+        assert_eq!(epochs[2]["verified"], false);
+
+        // Work is bounded, epochs over the limit are returned without being verified
+        let too_many = vec![valid; MAX_VERIFIED_EPOCHS + 1];
+        let epochs =
+            verified_epochs(&keypair.public_key, &too_many).expect("Unable to verify epochs");
+        assert_eq!(epochs.len(), MAX_VERIFIED_EPOCHS + 1);
+        assert!(epochs[..MAX_VERIFIED_EPOCHS]
+            .iter()
+            .all(|epoch| epoch["verified"] == true && epoch["witness_valid"] == true));
+        assert_eq!(epochs[MAX_VERIFIED_EPOCHS]["verified"], Value::Null);
+        assert_eq!(epochs[MAX_VERIFIED_EPOCHS]["witness_valid"], Value::Null);
+    }
+
+    #[test]
+    fn corrupted_witness_address_should_be_reported() {
+        let keypair = KeyPair::new();
+        let valid = epoch_record(&keypair, "ecvrf-keccak256");
+        assert_eq!(
+            verify_witness_address(&keypair.public_key, &valid),
+            Ok(true)
+        );
+
+        let mut corrupted = valid.clone();
+        corrupted.witness_address = "00".repeat(20);
+        assert_eq!(
+            verify_witness_address(&keypair.public_key, &corrupted),
+            Ok(false)
+        );
+
+        let mut malformed = valid.clone();
+        malformed.witness_address = "zz".to_string();
+        assert_eq!(
+            verify_witness_address(&keypair.public_key, &malformed)
+                .expect_err("Malformed witness address must be rejected")
+                .code(),
+            ErrorCode::Internal
+        );
+
+        let epochs = verified_epochs(&keypair.public_key, &[valid, corrupted])
+            .expect("Unable to verify epochs");
+        assert_eq!(epochs[0]["witness_valid"], true);
+        assert_eq!(epochs[1]["witness_valid"], false);
+        assert_eq!(epochs[1]["verified"], false);
+    }
+
+    #[test]
+    fn compact_epoch_should_expand_to_full_format() {
+        let keypair = KeyPair::new();
+        let alpha = Scalar::randomize();
+        let contract_proof = ECVRF::new(keypair.secret_key)
+            .prove_contract(&alpha)
+            .expect("Unable to prove");
+        let full = Model {
+            id: 1,
+            keyring_id: 1,
+            receiver_id: 1,
+            epoch: 0,
+            alpha: hex::encode(alpha.b32()),
+            gamma: contract_proof.gamma.to_hex_string(),
+            c: hex::encode(contract_proof.c.b32()),
+            s: hex::encode(contract_proof.s.b32()),
+            y: hex::encode(contract_proof.y.b32()),
+            witness_address: hex::encode(contract_proof.witness_address.to_eth_address()),
+            witness_gamma: contract_proof.witness_gamma.to_hex_string(),
+            witness_hash: contract_proof.witness_hash.to_hex_string(),
+            inverse_z: hex::encode(contract_proof.inverse_z.b32()),
+            signature_proof: "0".repeat(130),
+            request_id: None,
+            ciphersuite: Ciphersuite::default().as_str().to_string(),
+            proof: None,
+            created_date: DateTime::default(),
+        };
+        let compact = Model {
+            gamma: String::new(),
+            c: String::new(),
+            s: String::new(),
+            witness_address: String::new(),
+            witness_gamma: String::new(),
+            witness_hash: String::new(),
+            inverse_z: String::new(),
+            proof: Some(contract_proof.to_bytes()),
+            ..full.clone()
+        };
+
+        let expanded = expand_proof(compact).expect("Unable to expand compact proof");
+        assert_eq!(expanded, full);
+        assert_eq!(
+            serde_json::to_value(&expanded).expect("Unable to serialize epoch"),
+            serde_json::to_value(&full).expect("Unable to serialize epoch")
+        );
+        assert_eq!(expand_proof(full.clone()), Ok(full));
+    }
+
+    #[test]
+    fn proof_format_should_parse() {
+        assert_eq!(ProofFormat::try_from("full"), Ok(ProofFormat::Full));
+        assert_eq!(ProofFormat::try_from(" Compact "), Ok(ProofFormat::Compact));
+        assert!(ProofFormat::try_from("binary").is_err());
+    }
+}
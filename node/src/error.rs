@@ -1,10 +1,82 @@
-/// Lib EC-VRF error
+use hyper::StatusCode;
+use sea_orm::{DbErr, SqlErr};
+
+/// Stable error codes of the node, each code maps to a string and a HTTP status
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub enum ErrorCode {
+    /// Malformed request, JSON or parameters
+    BadRequest,
+    /// Missing, malformed or expired authorization
+    Unauthorized,
+    /// Authorized but not allowed to perform the action
+    Forbidden,
+    /// Requested record was not found
+    NotFound,
+    /// Request conflicts with an existing record
+    Conflict,
+    /// Request body is too large
+    PayloadTooLarge,
+    /// Too many requests
+    RateLimited,
+    /// Unexpected server side error
+    Internal,
+    /// Method or route is not implemented
+    NotImplemented,
+    /// Node is not ready to serve requests
+    ServiceUnavailable,
+    /// Request was not handled in time
+    GatewayTimeout,
+}
+
+impl ErrorCode {
+    /// Get the stable string representation of the code
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ErrorCode::BadRequest => "BAD_REQUEST",
+            ErrorCode::Unauthorized => "UNAUTHORIZED",
+            ErrorCode::Forbidden => "ACCESS_DENIED",
+            ErrorCode::NotFound => "NOT_FOUND",
+            ErrorCode::Conflict => "CONFLICT",
+            ErrorCode::PayloadTooLarge => "PAYLOAD_TOO_LARGE",
+            ErrorCode::RateLimited => "RATE_LIMITED",
+            ErrorCode::Internal => "INTERNAL_SERVER_ERROR",
+            ErrorCode::NotImplemented => "NOT_IMPLEMENTED",
+            ErrorCode::ServiceUnavailable => "SERVICE_UNAVAILABLE",
+            ErrorCode::GatewayTimeout => "GATEWAY_TIMEOUT",
+        }
+    }
+
+    /// Get the HTTP status of the code
+    pub fn status_code(&self) -> StatusCode {
+        match self {
+            ErrorCode::BadRequest => StatusCode::BAD_REQUEST,
+            ErrorCode::Unauthorized => StatusCode::UNAUTHORIZED,
+            ErrorCode::Forbidden => StatusCode::FORBIDDEN,
+            ErrorCode::NotFound => StatusCode::NOT_FOUND,
+            ErrorCode::Conflict => StatusCode::CONFLICT,
+            ErrorCode::PayloadTooLarge => StatusCode::PAYLOAD_TOO_LARGE,
+            ErrorCode::RateLimited => StatusCode::TOO_MANY_REQUESTS,
+            ErrorCode::Internal => StatusCode::INTERNAL_SERVER_ERROR,
+            ErrorCode::NotImplemented => StatusCode::NOT_IMPLEMENTED,
+            ErrorCode::ServiceUnavailable => StatusCode::SERVICE_UNAVAILABLE,
+            ErrorCode::GatewayTimeout => StatusCode::GATEWAY_TIMEOUT,
+        }
+    }
+}
+
+impl core::fmt::Display for ErrorCode {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write!(f, "{}", self.as_str())
+    }
+}
+
+/// Node error, a stable error code and a human readable reason
 #[derive(Debug, Clone, Copy, Eq, PartialEq)]
-pub struct Error(pub &'static str, pub &'static str);
+pub struct Error(pub ErrorCode, pub &'static str);
 
 impl Error {
     /// Get error code
-    pub fn code(&self) -> &'static str {
+    pub fn code(&self) -> ErrorCode {
         self.0
     }
 
@@ -30,3 +102,120 @@ impl core::fmt::Display for Error {
         write!(f, "Code [{}]: {}", self.0, self.1)
     }
 }
+
+impl From<DbErr> for Error {
+    /// Map database errors to node errors, the details of the database error are never
+    /// returned to the client
+    fn from(value: DbErr) -> Self {
+        match value.sql_err() {
+            Some(SqlErr::UniqueConstraintViolation(_))
+            | Some(SqlErr::ForeignKeyConstraintViolation(_)) => {
+                return Error(
+                    ErrorCode::Conflict,
+                    "Record conflicts with an existing record",
+                )
+            }
+            _ => {}
+        }
+        match value {
+            DbErr::RecordNotFound(_) => Error(ErrorCode::NotFound, "Record was not found"),
+            DbErr::Conn(_) | DbErr::ConnectionAcquire(_) => {
+                Error(ErrorCode::Internal, "Unable to connect to database")
+            }
+            _ => Error(ErrorCode::Internal, "Unable to query database"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Error, ErrorCode};
+    use hyper::StatusCode;
+    use sea_orm::{ConnAcquireErr, DbErr, RuntimeErr};
+
+    #[test]
+    fn error_code_should_map_to_string_and_status() {
+        let cases = [
+            (
+                ErrorCode::BadRequest,
+                "BAD_REQUEST",
+                StatusCode::BAD_REQUEST,
+            ),
+            (
+                ErrorCode::Unauthorized,
+                "UNAUTHORIZED",
+                StatusCode::UNAUTHORIZED,
+            ),
+            (ErrorCode::Forbidden, "ACCESS_DENIED", StatusCode::FORBIDDEN),
+            (ErrorCode::NotFound, "NOT_FOUND", StatusCode::NOT_FOUND),
+            (ErrorCode::Conflict, "CONFLICT", StatusCode::CONFLICT),
+            (
+                ErrorCode::PayloadTooLarge,
+                "PAYLOAD_TOO_LARGE",
+                StatusCode::PAYLOAD_TOO_LARGE,
+            ),
+            (
+                ErrorCode::RateLimited,
+                "RATE_LIMITED",
+                StatusCode::TOO_MANY_REQUESTS,
+            ),
+            (
+                ErrorCode::Internal,
+                "INTERNAL_SERVER_ERROR",
+                StatusCode::INTERNAL_SERVER_ERROR,
+            ),
+            (
+                ErrorCode::NotImplemented,
+                "NOT_IMPLEMENTED",
+                StatusCode::NOT_IMPLEMENTED,
+            ),
+            (
+                ErrorCode::ServiceUnavailable,
+                "SERVICE_UNAVAILABLE",
+                StatusCode::SERVICE_UNAVAILABLE,
+            ),
+            (
+                ErrorCode::GatewayTimeout,
+                "GATEWAY_TIMEOUT",
+                StatusCode::GATEWAY_TIMEOUT,
+            ),
+        ];
+        for (code, name, status) in cases {
+            assert_eq!(code.as_str(), name);
+            assert_eq!(code.status_code(), status);
+        }
+    }
+
+    #[test]
+    fn error_should_render_code_and_reason() {
+        let err = Error(ErrorCode::NotFound, "Epoch was not found");
+        assert_eq!(format!("{}", err), "Code [NOT_FOUND]: Epoch was not found");
+        assert_eq!(
+            err.to_json_string(),
+            "{\"success\":false,\"message\":\"Code [NOT_FOUND]: Epoch was not found\"}"
+        );
+    }
+
+    #[test]
+    fn database_error_should_map_to_error_code() {
+        let not_found = Error::from(DbErr::RecordNotFound("randomness".to_string()));
+        assert_eq!(not_found.code(), ErrorCode::NotFound);
+
+        let conn = Error::from(DbErr::Conn(RuntimeErr::Internal("refused".to_string())));
+        assert_eq!(
+            conn,
+            Error(ErrorCode::Internal, "Unable to connect to database")
+        );
+        let acquire = Error::from(DbErr::ConnectionAcquire(ConnAcquireErr::Timeout));
+        assert_eq!(
+            acquire,
+            Error(ErrorCode::Internal, "Unable to connect to database")
+        );
+
+        let other = Error::from(DbErr::Custom("unexpected".to_string()));
+        assert_eq!(
+            other,
+            Error(ErrorCode::Internal, "Unable to query database")
+        );
+    }
+}
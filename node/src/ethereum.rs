@@ -1,26 +1,125 @@
 use bytes::{BufMut, BytesMut};
 use libecvrf::{
     extends::ScalarExtend,
+    helper::{address_from_public_bytes, get_address},
     secp256k1::{
         curve::{Affine, Scalar},
-        sign_with_context, Message, SecretKey, ECMULT_GEN_CONTEXT,
+        recover, sign_with_context, Message, RecoveryId, SecretKey, Signature, ECMULT_GEN_CONTEXT,
     },
     ECVRFContractProof,
 };
-use std::{io::Write, str};
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use std::{fmt, io::Write, str, str::FromStr};
 use tiny_keccak::{Hasher, Keccak};
 
-use crate::rpc::decode_address;
+use crate::error::{Error, ErrorCode};
 
 const ETHEREUM_MESSAGE_PREFIX: &str = "\x19Ethereum Signed Message:\n";
 
-/// Sign an Ethereum message with prefix
-pub fn sign_ethereum_message(sk: &SecretKey, message: &Vec<u8>) -> Vec<u8> {
+/// 20 bytes Ethereum address
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub struct EthAddress([u8; 20]);
+
+impl EthAddress {
+    /// Zero address, receiver of public epochs
+    pub const ZERO: EthAddress = EthAddress([0u8; 20]);
+
+    /// Get raw bytes of the address
+    pub fn as_bytes(&self) -> &[u8; 20] {
+        &self.0
+    }
+
+    /// Check if this is the zero address
+    pub fn is_zero(&self) -> bool {
+        self.0 == [0u8; 20]
+    }
+
+    /// Format the address with EIP-55 mixed case checksum
+    pub fn to_checksum_string(&self) -> String {
+        let lowercase = hex::encode(self.0);
+        let mut hash = [0u8; 32];
+        let mut hasher = Keccak::v256();
+        hasher.update(lowercase.as_bytes());
+        hasher.finalize(&mut hash);
+        let checksum = lowercase
+            .chars()
+            .enumerate()
+            .map(|(i, c)| {
+                // Each character is checked against a nibble of the hash
+                let nibble = (hash[i / 2] >> (4 * (1 - i % 2))) & 0x0f;
+                if nibble >= 8 {
+                    c.to_ascii_uppercase()
+                } else {
+                    c
+                }
+            })
+            .collect::<String>();
+        format!("0x{}", checksum)
+    }
+}
+
+impl From<[u8; 20]> for EthAddress {
+    fn from(value: [u8; 20]) -> Self {
+        Self(value)
+    }
+}
+
+impl FromStr for EthAddress {
+    type Err = Error;
+
+    /// Parse a 0x prefixed address, mixed case addresses must carry a valid EIP-55 checksum
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        let digits = value
+            .strip_prefix("0x")
+            .ok_or(Error(ErrorCode::BadRequest, "Address must start with 0x"))?;
+        if digits.len() != 40 {
+            return Err(Error(ErrorCode::BadRequest, "Invalid address length"));
+        }
+        let mut bytes = [0u8; 20];
+        hex::decode_to_slice(digits, &mut bytes)
+            .map_err(|_| Error(ErrorCode::BadRequest, "Invalid address"))?;
+        let address = Self(bytes);
+        let is_lowercase = !digits.chars().any(|c| c.is_ascii_uppercase());
+        let is_uppercase = !digits.chars().any(|c| c.is_ascii_lowercase());
+        if !is_lowercase && !is_uppercase && address.to_checksum_string() != value {
+            return Err(Error(ErrorCode::BadRequest, "Invalid address checksum"));
+        }
+        Ok(address)
+    }
+}
+
+impl fmt::Display for EthAddress {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "0x{}", hex::encode(self.0))
+    }
+}
+
+impl Serialize for EthAddress {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+impl<'de> Deserialize<'de> for EthAddress {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        String::deserialize(deserializer)?
+            .parse()
+            .map_err(serde::de::Error::custom)
+    }
+}
+
+/// Hash a message with the Ethereum signed message prefix
+fn ethereum_message_hash(message: &[u8]) -> Message {
     let mut buf = BytesMut::with_capacity(256);
     let prefix = format!("{}{}", ETHEREUM_MESSAGE_PREFIX, message.len()).into_bytes();
     buf.put(prefix.as_slice());
-    buf.put(message.as_slice());
-    let prefixed_message = Message(Scalar::keccak256(&buf));
+    buf.put(message);
+    Message(Scalar::keccak256(&buf))
+}
+
+/// Sign an Ethereum message with prefix
+pub fn sign_ethereum_message(sk: &SecretKey, message: &Vec<u8>) -> Vec<u8> {
+    let prefixed_message = ethereum_message_hash(message);
     let (signature, recovery_id) = sign_with_context(&prefixed_message, sk, &ECMULT_GEN_CONTEXT);
     let mut recover_id: u8 = recovery_id.into();
     // Recover id must be 27 or 28, if it was 0,1 we will add 27
@@ -34,8 +133,28 @@ pub fn sign_ethereum_message(sk: &SecretKey, message: &Vec<u8>) -> Vec<u8> {
     r
 }
 
+/// Recover the signer of a message signed by [sign_ethereum_message],
+/// return the signer address and the signed message
+pub fn recover_ethereum_message(signed_message: &[u8]) -> Result<(EthAddress, Vec<u8>), Error> {
+    if signed_message.len() < 65 {
+        return Err(Error(ErrorCode::BadRequest, "Signed message is too short"));
+    }
+    let (signature, message) = signed_message.split_at(65);
+    let parsed_signature = Signature::parse_standard_slice(&signature[0..64])
+        .map_err(|_| Error(ErrorCode::BadRequest, "Unable to parse signature"))?;
+    let recovery_id = RecoveryId::parse_rpc(signature[64])
+        .map_err(|_| Error(ErrorCode::BadRequest, "Invalid recovery id"))?;
+    let public_key = recover(
+        &ethereum_message_hash(message),
+        &parsed_signature,
+        &recovery_id,
+    )
+    .map_err(|_| Error(ErrorCode::BadRequest, "Unable to recover signer"))?;
+    Ok((EthAddress(get_address(&public_key)), message.to_vec()))
+}
+
 pub fn ecvrf_proof_checksum(
-    receiver_address: String,
+    receiver_address: &EthAddress,
     smart_contract_proof: &ECVRFContractProof,
 ) -> [u8; 32] {
     let mut hasher = Keccak::v256();
@@ -43,11 +162,7 @@ pub fn ecvrf_proof_checksum(
     let mut output = [0u8; 32];
 
     // Receiver address
-    buf.put_slice(
-        hex::decode(decode_address(receiver_address).replace("0x", ""))
-            .expect("Unable to decode receiver address")
-            .as_slice(),
-    );
+    buf.put_slice(receiver_address.as_bytes());
 
     // Gamma
     buf.put_slice(&smart_contract_proof.gamma.x.b32());
@@ -63,7 +178,7 @@ pub fn ecvrf_proof_checksum(
     // Witness address was calculated before, so it store in 0 -> 20
     // not 12 -> 32
     // buf.put_slice(&[0u8, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0]);
-    buf.put_slice(&smart_contract_proof.witness_address.b32()[0..20]);
+    buf.put_slice(&smart_contract_proof.witness_address.to_eth_address());
     // Gama Witness
     buf.put_slice(&smart_contract_proof.witness_gamma.x.b32());
     buf.put_slice(&smart_contract_proof.witness_gamma.y.b32());
@@ -104,7 +219,7 @@ pub fn ecvrf_proof_digest(smart_contract_proof: &ECVRFContractProof) -> [u8; 32]
     // Witness address was calculated before, so it store in 0 -> 20
     // not 12 -> 32
     // buf.put_slice(&[0u8, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0]);
-    buf.put_slice(&smart_contract_proof.witness_address.b32()[0..20]);
+    buf.put_slice(&smart_contract_proof.witness_address.to_eth_address());
     // Gama Witness
     buf.put_slice(&smart_contract_proof.witness_gamma.x.b32());
     buf.put_slice(&smart_contract_proof.witness_gamma.y.b32());
@@ -120,18 +235,140 @@ pub fn ecvrf_proof_digest(smart_contract_proof: &ECVRFContractProof) -> [u8; 32]
     output
 }
 
+/// Public key of an operator and its Ethereum address
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct OperatorAddress {
+    /// Hex encoded public key, as stored in keyring table
+    pub public_key: String,
+    /// Ethereum address of the public key
+    pub address: EthAddress,
+}
+
+/// Derive the Ethereum address from a hex encoded public key
+pub fn operator_address(public_key: &str) -> Result<OperatorAddress, Error> {
+    let raw_public_key = hex::decode(public_key.trim_start_matches("0x"))
+        .map_err(|_| Error(ErrorCode::Internal, "Unable to decode public key"))?;
+    let address = address_from_public_bytes(&raw_public_key)
+        .map_err(|_| Error(ErrorCode::Internal, "Unable to parse public key"))?;
+    Ok(OperatorAddress {
+        public_key: public_key.to_string(),
+        address: EthAddress(address),
+    })
+}
+
 /// Compose operator proof
 pub fn compose_operator_proof(
     nonce: i64,
-    receiver: &[u8; 20],
+    receiver: &EthAddress,
     ecvrf_proof_digest: &[u8; 32],
 ) -> Vec<u8> {
     let mut buf = BytesMut::with_capacity(256);
     // We don't have uint96 in Rust
-    // So we combine u32 with i64
+    // So we combine u32 with i64, the 4 zero bytes are the high bits of the
+    // uint96 nonce, they're not a network slot, network is not part of this proof
     buf.put_u32(0);
     buf.put_i64(nonce);
-    buf.put(receiver.as_slice());
+    buf.put(receiver.as_bytes().as_slice());
     buf.put(ecvrf_proof_digest.as_slice());
     buf.to_vec()
 }
+
+#[cfg(test)]
+mod tests {
+    use super::{operator_address, recover_ethereum_message, sign_ethereum_message, EthAddress};
+    use crate::ErrorCode;
+    use libecvrf::{helper::get_address, secp256k1::PublicKey, KeyPair, RawKeyPair, Zeroable};
+
+    #[test]
+    fn eth_address_should_round_trip() {
+        // Test vector of EIP-55
+        let checksum = "0x5aAeb6053F3E94C9b9A09f33669435E7Ef1BeAed";
+        let address = checksum
+            .parse::<EthAddress>()
+            .expect("Unable to parse address");
+        assert_eq!(address.to_checksum_string(), checksum);
+        assert_eq!(address.to_string(), checksum.to_lowercase());
+        assert_eq!(
+            address.to_string().parse::<EthAddress>(),
+            Ok(address),
+            "Lowercase address has no checksum"
+        );
+        assert_eq!(
+            checksum
+                .to_uppercase()
+                .replace("0X", "0x")
+                .parse::<EthAddress>(),
+            Ok(address),
+            "Uppercase address has no checksum"
+        );
+        assert_eq!(address.as_bytes()[0], 0x5a);
+        assert_eq!(
+            EthAddress::ZERO.to_string(),
+            "0x0000000000000000000000000000000000000000"
+        );
+        assert!(EthAddress::ZERO.is_zero());
+        assert_eq!(
+            serde_json::to_string(&address).expect("Unable to serialize address"),
+            format!("\"{}\"", address)
+        );
+    }
+
+    #[test]
+    fn eth_address_should_reject_invalid_inputs() {
+        for invalid in [
+            "",
+            "5aaeb6053f3e94c9b9a09f33669435e7ef1beaed",
+            "0x5aaeb6053f3e94c9b9a09f33669435e7ef1bea",
+            "0x5aaeb6053f3e94c9b9a09f33669435e7ef1beaed00",
+            "0x5aaeb6053f3e94c9b9a09f33669435e7ef1beaeg",
+            // Checksum with a single flipped character
+            "0x5aAeb6053F3E94C9b9A09f33669435E7Ef1BeAeD",
+        ] {
+            assert_eq!(
+                invalid.parse::<EthAddress>().map_err(|e| e.code()),
+                Err(ErrorCode::BadRequest),
+                "{} must be rejected",
+                invalid
+            );
+        }
+    }
+
+    #[test]
+    fn signed_message_should_recover_to_signer() {
+        let keypair = KeyPair::new();
+        let message = b"orand".to_vec();
+        let signed_message = sign_ethereum_message(&keypair.secret_key, &message);
+        let (address, recovered_message) =
+            recover_ethereum_message(&signed_message).expect("Unable to recover signer");
+        assert_eq!(address.as_bytes(), &get_address(&keypair.public_key));
+        assert_eq!(recovered_message, message);
+        assert!(recover_ethereum_message(&signed_message[0..64]).is_err());
+    }
+
+    #[test]
+    fn operator_address_should_match_get_address() {
+        let keypair = KeyPair::new();
+        let mut raw_keypair = RawKeyPair::from(&keypair);
+        let public_key = hex::encode(raw_keypair.public_key);
+        raw_keypair.zeroize();
+
+        let result = operator_address(&public_key).expect("Unable to derive operator address");
+        let parsed_public_key = PublicKey::parse_slice(
+            &hex::decode(&public_key).expect("Unable to decode public key"),
+            None,
+        )
+        .expect("Unable to parse public key");
+        assert_eq!(result.public_key, public_key);
+        assert_eq!(
+            result.address.to_string(),
+            format!("0x{}", hex::encode(get_address(&parsed_public_key)))
+        );
+        assert_eq!(parsed_public_key, keypair.public_key);
+    }
+
+    #[test]
+    fn operator_address_should_reject_invalid_public_key() {
+        assert!(operator_address("not a hex string").is_err());
+        assert!(operator_address(&hex::encode([1u8; 65])).is_err());
+    }
+}
@@ -41,7 +41,7 @@ pub fn evm_verify(smart_contract_proof: &ECVRFContractProof) -> bool {
     buf.put_slice(&smart_contract_proof.alpha.b32());
     // Witness address
     buf.put_slice(&[0u8, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0]);
-    buf.put_slice(&smart_contract_proof.witness_address.b32()[0..20]);
+    buf.put_slice(&smart_contract_proof.witness_address.to_eth_address());
     // Gama Witness
     buf.put_slice(&smart_contract_proof.witness_gamma.x.b32());
     buf.put_slice(&smart_contract_proof.witness_gamma.y.b32());
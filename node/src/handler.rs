@@ -0,0 +1,679 @@
+use crate::{
+    epoch::{verified_epochs_by, verify_epoch},
+    ethereum::{operator_address, EthAddress},
+    jwt::JWT,
+    rpc::{check_private_receiver, JSONRPCMethod},
+    table::{is_unique_violation, ReceiverTable, DEFAULT_RECENT_EPOCHS},
+    Error, ErrorCode, NodeContext, QuickResponse,
+};
+use bytes::Bytes;
+use http_body_util::{combinators::BoxBody, BodyExt};
+use hyper::{body::Body, Method, Request, Response};
+use sea_orm::{prelude::DateTime, DbErr};
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use std::{borrow::Borrow, fmt::Debug, str::from_utf8, sync::Arc, time::Instant};
+
+/// Name of the admin keyring, it proves public epochs and manages users and receivers
+pub const ORAND_KEYRING_NAME: &str = "orand";
+
+/// Return a JSON record of user
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct UserResponse {
+    /// Username
+    pub username: String,
+    /// HMAC secret
+    pub hmac_secret: String,
+    /// Public key
+    pub public_key: String,
+    /// Created date
+    pub created_date: DateTime,
+}
+
+async fn orand_get_epoch(
+    network: u64,
+    address: EthAddress,
+    epoch: i64,
+    count: Option<u64>,
+    verify: bool,
+    context: Arc<NodeContext<'_>>,
+) -> Result<Response<BoxBody<Bytes, hyper::Error>>, hyper::Error> {
+    let postgres = context.postgres();
+    let randomness = postgres.table_randomness();
+
+    let epochs = if epoch == i64::MAX {
+        randomness
+            .find_recent_epoch(network, &address, count.unwrap_or(DEFAULT_RECENT_EPOCHS))
+            .await
+            .map_err(Error::from)
+    } else {
+        randomness
+            .find_closure_epoch(network, &address, epoch)
+            .await
+            .map_err(|_| Error(ErrorCode::NotFound, "Epoch was not found"))
+    };
+    match epochs {
+        Ok(epochs) if verify => {
+            // Epochs before a key rotation are verified with the key that proved them
+            let history = match context.key_history().await {
+                Ok(history) => history,
+                Err(err) => return QuickResponse::err(err.into()),
+            };
+            match verified_epochs_by(&epochs, |record| history.public_key_for(record.id)) {
+                Ok(verified) => QuickResponse::res_json(&verified),
+                Err(e) => QuickResponse::err(e),
+            }
+        }
+        Ok(epochs) => QuickResponse::res_json(&epochs),
+        Err(e) => QuickResponse::err(e),
+    }
+}
+
+async fn orand_verify_epoch(
+    network: u64,
+    address: EthAddress,
+    epoch: i64,
+    context: Arc<NodeContext<'_>>,
+) -> Result<Response<BoxBody<Bytes, hyper::Error>>, hyper::Error> {
+    let postgres = context.postgres();
+    let randomness = postgres.table_randomness();
+
+    let history = match context.key_history().await {
+        Ok(history) => history,
+        Err(err) => return QuickResponse::err(err.into()),
+    };
+    match randomness.find_given_epoch(network, &address, epoch).await {
+        Ok(Some(record)) => match verify_epoch(&history.public_key_for(record.id), &record) {
+            Ok(verified) => QuickResponse::res_json(&json!({
+                "success": true,
+                "epoch": record.epoch,
+                "ciphersuite": record.ciphersuite,
+                "verified": verified,
+            })),
+            Err(e) => QuickResponse::err(e),
+        },
+        Ok(None) => QuickResponse::err(Error(ErrorCode::NotFound, "Epoch was not found")),
+        Err(err) => QuickResponse::err(err.into()),
+    }
+}
+
+async fn orand_export_chain(
+    network: u64,
+    address: EthAddress,
+    context: Arc<NodeContext<'_>>,
+) -> Result<Response<BoxBody<Bytes, hyper::Error>>, hyper::Error> {
+    let postgres = context.postgres();
+    // Epochs before a key rotation are exported with the key that proved them
+    let history = match context.key_history().await {
+        Ok(history) => history,
+        Err(err) => return QuickResponse::err(err.into()),
+    };
+    match postgres.table_receiver().find_one(network, &address).await {
+        Ok(Some(receiver)) => QuickResponse::stream(
+            postgres
+                .table_randomness()
+                .export_chain(history, receiver.id),
+        ),
+        Ok(None) => QuickResponse::err(Error(ErrorCode::NotFound, "Receiver was not found")),
+        Err(err) => QuickResponse::err(err.into()),
+    }
+}
+
+async fn orand_new_epoch(
+    context: Arc<NodeContext<'static>>,
+    username: String,
+    network: u64,
+    address: EthAddress,
+    request_id: Option<String>,
+) -> Result<Response<BoxBody<Bytes, hyper::Error>>, hyper::Error> {
+    let postgres = context.postgres();
+    let randomness = postgres.table_randomness();
+
+    match randomness
+        .safe_insert(Arc::clone(&context), username, network, address, request_id)
+        .await
+    {
+        Ok(randomness_returning_record) => QuickResponse::res_json(&randomness_returning_record),
+        Err(err) if is_unique_violation(&err) => QuickResponse::err(Error(
+            ErrorCode::Conflict,
+            "Epoch was already created, please retry",
+        )),
+        Err(err) => QuickResponse::err(err.into()),
+    }
+}
+
+/// Register an unknown receiver for the given user if the node policy allows it
+async fn orand_auto_register(
+    context: Arc<NodeContext<'_>>,
+    username: String,
+    network: u64,
+    address: EthAddress,
+) -> Result<(), Error> {
+    if !context.should_auto_register() {
+        return Ok(());
+    }
+    let receiver = context.postgres().table_receiver();
+    if receiver.find_one(network, &address).await?.is_some() {
+        return Ok(());
+    }
+    let model_keyring = match context
+        .postgres()
+        .table_keyring()
+        .find_by_name(username.clone())
+        .await
+    {
+        Ok(Some(keyring_record)) => keyring_record,
+        _ => {
+            return Err(Error(
+                ErrorCode::Forbidden,
+                "User may not exist or database error",
+            ))
+        }
+    };
+    log::info!(
+        "Auto register receiver address: {} network: {}",
+        address,
+        network
+    );
+    match receiver
+        .insert(json!({
+            "keyring_id": model_keyring.id,
+            "name": ReceiverTable::deterministic_name(&username, network, &address),
+            "address": address.to_string(),
+            "network": network,
+            "nonce": 0,
+        }))
+        .await
+    {
+        Ok(_) => Ok(()),
+        Err(err) => {
+            log::error!("Unable to auto register receiver {}", err);
+            Err(Error(ErrorCode::Internal, "Unable to add new receiver"))
+        }
+    }
+}
+
+/// Readiness probe, the node is ready once the database is reachable
+/// and all migrations have been applied
+async fn orand_ready(
+    context: Arc<NodeContext<'_>>,
+) -> Result<Response<BoxBody<Bytes, hyper::Error>>, hyper::Error> {
+    let postgres = context.postgres();
+    if let Err(err) = postgres.ping().await {
+        log::error!("Readiness check failed, database is unreachable: {}", err);
+        return QuickResponse::err(Error(
+            ErrorCode::ServiceUnavailable,
+            "Database is unreachable",
+        ));
+    }
+    match postgres.migrations_current().await {
+        Ok(true) => QuickResponse::res_json(&json!({"success": true, "status": "ready"})),
+        Ok(false) => QuickResponse::err(Error(
+            ErrorCode::ServiceUnavailable,
+            "Database migrations are pending",
+        )),
+        Err(err) => {
+            log::error!("Readiness check failed, unable to read migrations: {}", err);
+            QuickResponse::err(Error(
+                ErrorCode::ServiceUnavailable,
+                "Unable to read database migrations",
+            ))
+        }
+    }
+}
+
+/// This is our service handler. It receives a Request, routes on its
+/// path, and returns a Future of a Response.
+pub async fn orand<B>(
+    req: Request<B>,
+    context: Arc<NodeContext<'static>>,
+) -> Result<Response<BoxBody<Bytes, hyper::Error>>, hyper::Error>
+where
+    B: Body,
+    B::Error: Debug,
+{
+    let (header, body) = req.into_parts();
+    match (&header.method, header.uri.path()) {
+        // Liveness probe, the process is up and serving requests
+        (&Method::GET, "/health") => {
+            QuickResponse::res_json(&json!({"success": true, "status": "ok"}))
+        }
+        // Readiness probe, database and migrations are checked
+        (&Method::GET, "/ready") => orand_ready(context).await,
+        // Handle all post method to JSON RPC
+        //(&Method::OPTIONS, "/") => QuickResponse::option(),
+        (&Method::POST, "/") => {
+            let max = body.size_hint().upper().unwrap_or(u64::MAX);
+            // Body is 64 KB
+            if max > 1024 * 64 {
+                return QuickResponse::err(Error(
+                    ErrorCode::PayloadTooLarge,
+                    "Your body too big, can not fit the body bag",
+                ));
+            }
+            // Body to byte, a broken or non utf8 body is rejected instead of panicking
+            let whole_body = match body.collect().await {
+                Ok(collected) => collected.to_bytes(),
+                Err(err) => {
+                    log::debug!("Unable to collect the request body {:?}", err);
+                    return QuickResponse::err(Error(
+                        ErrorCode::BadRequest,
+                        "Unable to read the request body",
+                    ));
+                }
+            };
+            let json_string = match from_utf8(whole_body.borrow()) {
+                Ok(json_string) => json_string,
+                Err(_) => {
+                    return QuickResponse::err(Error(
+                        ErrorCode::BadRequest,
+                        "Request body is not valid utf8",
+                    ));
+                }
+            };
+            let json_rpc_payload = match JSONRPCMethod::from_json_string(json_string) {
+                Ok(payload) => payload,
+                Err(e) => {
+                    return QuickResponse::err(e);
+                }
+            };
+            if let Err(e) = context.check_public(json_rpc_payload.is_public()) {
+                return QuickResponse::err(e);
+            }
+
+            log::debug!(
+                "Request: {} {} {} ",
+                &header.method,
+                header.uri.path(),
+                &json_string,
+            );
+
+            let keyring = context.postgres().table_keyring();
+            let receiver = context.postgres().table_receiver();
+
+            let authorized_jwt = {
+                let (jwt_payload, json_web_token) = match header.headers.get("authorization") {
+                    Some(e) => match e.to_str() {
+                        Ok(s) => match JWT::decode_payload(s) {
+                            Ok(p) => (p, s),
+                            Err(e) => {
+                                return QuickResponse::err(e);
+                            }
+                        },
+                        Err(_) => {
+                            return QuickResponse::err(Error(
+                                ErrorCode::Unauthorized,
+                                "Unable to decode authorization header",
+                            ));
+                        }
+                    },
+                    None => {
+                        return QuickResponse::err(Error(
+                            ErrorCode::Unauthorized,
+                            "Access denied, this method required authorization",
+                        ));
+                    }
+                };
+
+                let user_record = match keyring.find_by_name(jwt_payload.user.clone()).await {
+                    Ok(Some(record)) => record,
+                    Err(err) => {
+                        log::error!("Unable to query keyring from database {}", err);
+                        return QuickResponse::err(err.into());
+                    }
+                    Ok(None) => {
+                        return QuickResponse::err(Error(
+                            ErrorCode::Unauthorized,
+                            "Access denied, this method required authorization",
+                        ));
+                    }
+                };
+
+                let jwt_guard = context.jwt_guard();
+                if let Err(e) = jwt_guard.check(&user_record.username, Instant::now()) {
+                    return QuickResponse::err(e);
+                }
+                let jwt = JWT::new(&user_record.hmac_secret);
+                if !jwt.verify(json_web_token) {
+                    jwt_guard.record_failure(&user_record.username, Instant::now());
+                    return QuickResponse::err(Error(
+                        ErrorCode::Forbidden,
+                        "Access denied, incorrect key",
+                    ));
+                }
+                jwt_guard.record_success(&user_record.username);
+                Some(jwt_payload)
+            };
+
+            let jwt_payload = match authorized_jwt {
+                Some(p) => p,
+                None => {
+                    return QuickResponse::err(Error(
+                        ErrorCode::Unauthorized,
+                        "Access denied, this method required authorization",
+                    ));
+                }
+            };
+
+            match json_rpc_payload {
+                // Get epoch, it's alias of orand_getPublicEpoch() and orand_getPrivateEpoch()
+                JSONRPCMethod::OrandGetEpoch(network, address, epoch, count, verify) => {
+                    orand_get_epoch(network, address, epoch, count, verify, context).await
+                }
+                // Verify epoch, it's alias of orand_verifyPublicEpoch() and orand_verifyPrivateEpoch()
+                JSONRPCMethod::OrandVerifyEpoch(network, address, epoch) => {
+                    orand_verify_epoch(network, address, epoch, context).await
+                }
+                // Export the whole epoch chain of a receiver, the response is streamed
+                JSONRPCMethod::OrandExportChain(network, address) => {
+                    orand_export_chain(network, address, context).await
+                }
+                // Get epoch, it's alias of orand_newPublicEpoch() and orand_newPrivateEpoch()
+                JSONRPCMethod::OrandNewEpoch(network, address, request_id) => {
+                    // Only orand could able pair with ZERO_ADDRESS
+                    if address.is_zero() && !jwt_payload.user.eq(ORAND_KEYRING_NAME) {
+                        return QuickResponse::err(Error(
+                            ErrorCode::Forbidden,
+                            "Access denied, you do not have ability to create public epoch",
+                        ));
+                    }
+                    // Register unknown receiver according to node policy
+                    if let Err(e) = orand_auto_register(
+                        Arc::clone(&context),
+                        jwt_payload.user.clone(),
+                        network,
+                        address,
+                    )
+                    .await
+                    {
+                        return QuickResponse::err(e);
+                    }
+                    // Create new epoch
+                    orand_new_epoch(
+                        Arc::clone(&context),
+                        jwt_payload.user.clone(),
+                        network,
+                        address,
+                        request_id,
+                    )
+                    .await
+                }
+                JSONRPCMethod::OrandGetPublicKey(key_name) => {
+                    let keyring = context.postgres().table_keyring();
+                    match keyring.find_by_name(key_name).await {
+                        Ok(key_record) => QuickResponse::res_json(&key_record),
+                        Err(err) => QuickResponse::err(err.into()),
+                    }
+                }
+                JSONRPCMethod::OrandGetNetworks => match context.network_status().await {
+                    Ok(networks) => QuickResponse::res_json(&networks),
+                    Err(err) => {
+                        log::error!("Unable to query network status {}", err);
+                        QuickResponse::err(Error(
+                            ErrorCode::Internal,
+                            "Unable to query network status",
+                        ))
+                    }
+                },
+                JSONRPCMethod::OrandGetParams => {
+                    QuickResponse::res_json(&context.vrf().public_params())
+                }
+                JSONRPCMethod::OrandGetOperatorAddress(key_name) => {
+                    let keyring = context.postgres().table_keyring();
+                    match keyring.find_by_name(key_name).await {
+                        Ok(Some(key_record)) => match operator_address(&key_record.public_key) {
+                            Ok(result) => QuickResponse::res_json(&result),
+                            Err(e) => QuickResponse::err(e),
+                        },
+                        Ok(None) => {
+                            QuickResponse::err(Error(ErrorCode::NotFound, "Key was not found"))
+                        }
+                        Err(err) => QuickResponse::err(err.into()),
+                    }
+                }
+                JSONRPCMethod::OrandGetKeyHistory(key_name) => {
+                    let key_record = match keyring.find_by_name(key_name).await {
+                        Ok(Some(key_record)) => key_record,
+                        Ok(None) => {
+                            return QuickResponse::err(Error(
+                                ErrorCode::NotFound,
+                                "Key was not found",
+                            ))
+                        }
+                        Err(err) => return QuickResponse::err(err.into()),
+                    };
+                    match context
+                        .postgres()
+                        .table_key_rotation()
+                        .find_by_keyring(key_record.id)
+                        .await
+                    {
+                        Ok(rotations) => QuickResponse::res_json(&json!({
+                            "public_key": key_record.public_key,
+                            "rotations": rotations,
+                        })),
+                        Err(err) => QuickResponse::err(err.into()),
+                    }
+                }
+                JSONRPCMethod::AdminRotateOperatorKey => {
+                    // Only orand could able to rotate the operator key
+                    if !jwt_payload.user.eq(ORAND_KEYRING_NAME) {
+                        return QuickResponse::err(Error(
+                            ErrorCode::Forbidden,
+                            "Access denied, you do not have ability to rotate operator key",
+                        ));
+                    }
+                    match context.rotate_operator_key().await {
+                        Ok(rotation) => {
+                            log::info!(
+                                "Operator key was rotated after randomness record {}, new public key: {}",
+                                rotation.last_randomness_id,
+                                rotation.new_public_key
+                            );
+                            QuickResponse::res_json(&rotation)
+                        }
+                        Err(err) => {
+                            log::error!("Unable to rotate operator key {}", err);
+                            QuickResponse::err(Error(
+                                ErrorCode::Internal,
+                                "Unable to rotate operator key",
+                            ))
+                        }
+                    }
+                }
+                JSONRPCMethod::AdminAddUser(username) => {
+                    // Only orand could able pair with ZERO_ADDRESS
+                    if jwt_payload.user.eq(ORAND_KEYRING_NAME) {
+                        match keyring.find_by_name(username.clone()).await {
+                            Err(err) => return QuickResponse::err(err.into()),
+                            Ok(Some(_)) => {
+                                return QuickResponse::err(Error(
+                                    ErrorCode::Conflict,
+                                    "Unable to create user",
+                                ))
+                            }
+                            Ok(None) => {
+                                let insert_result =
+                                    match keyring.bootstrap_user(username, None).await {
+                                        Ok(record) => record,
+                                        Err(err) => return QuickResponse::err(err.into()),
+                                    };
+                                return QuickResponse::res_json(&UserResponse {
+                                    username: insert_result.username,
+                                    hmac_secret: insert_result.hmac_secret,
+                                    public_key: insert_result.public_key,
+                                    created_date: insert_result.created_date,
+                                });
+                            }
+                        }
+                    }
+                    QuickResponse::err(Error(
+                        ErrorCode::Forbidden,
+                        "Access denied, you do not have ability add new user",
+                    ))
+                }
+                JSONRPCMethod::AdminAddReceiver(username, receiver_address, network) => {
+                    // ZERO_ADDRESS is the receiver of public epochs, it can't be added
+                    if let Err(e) = check_private_receiver(&receiver_address) {
+                        return QuickResponse::err(e);
+                    }
+                    // Only orand could able pair with ZERO_ADDRESS
+                    if jwt_payload.user.eq(ORAND_KEYRING_NAME) {
+                        let model_keyring = match context
+                            .postgres()
+                            .table_keyring()
+                            .find_by_name(username.clone())
+                            .await
+                        {
+                            Ok(Some(keyring_record)) => keyring_record,
+                            Err(err) => return QuickResponse::err(err.into()),
+                            Ok(None) => {
+                                return QuickResponse::err(Error(
+                                    ErrorCode::Forbidden,
+                                    "User may not exist or database error",
+                                ));
+                            }
+                        };
+                        let receiver_check =
+                            match receiver.find_one(network, &receiver_address).await {
+                                Ok(receiver_check) => receiver_check,
+                                Err(err) => return QuickResponse::err(err.into()),
+                            };
+                        // Dummy patch to check if receiver existed
+                        if receiver_check.is_some() {
+                            return QuickResponse::res_json(&receiver_check);
+                        }
+
+                        log::info!(
+                            "Trying insert new receiver address: {} network: {}",
+                            receiver_address,
+                            network
+                        );
+                        match receiver
+                            .insert(json!({
+                                "keyring_id": model_keyring.id,
+                                "name": ReceiverTable::deterministic_name(
+                                    &username,
+                                    network,
+                                    &receiver_address,
+                                ),
+                                "address": receiver_address.to_string(),
+                                "network": network,
+                                "nonce": 0,
+                            }))
+                            .await
+                        {
+                            Ok(model_receiver) => return QuickResponse::res_json(&model_receiver),
+                            Err(err) => {
+                                log::error!("Unable to add new receiver {}", err);
+                                return QuickResponse::err(Error(
+                                    ErrorCode::Internal,
+                                    "Unable to add new receiver",
+                                ));
+                            }
+                        }
+                    }
+                    QuickResponse::err(Error(
+                        ErrorCode::Forbidden,
+                        "Access denied, you do not have ability to add new receiver",
+                    ))
+                }
+                JSONRPCMethod::AdminGetUser(username) => {
+                    if jwt_payload.user.eq(ORAND_KEYRING_NAME) {
+                        match keyring.find_by_name(username.clone()).await {
+                            Err(err) => return QuickResponse::err(err.into()),
+                            Ok(Some(record)) => {
+                                return QuickResponse::res_json(&json!({
+                                    "username": record.username,
+                                    "hmac_secret": record.hmac_secret,
+                                    "created_date": record.created_date
+                                }));
+                            }
+                            _ => {
+                                return QuickResponse::err(Error(
+                                    ErrorCode::Forbidden,
+                                    "User may not exist or database error",
+                                ))
+                            }
+                        }
+                    }
+                    QuickResponse::err(Error(
+                        ErrorCode::Forbidden,
+                        "Access denied, you do not have ability to add new receiver",
+                    ))
+                }
+                JSONRPCMethod::AdminGetReceiver(username) => {
+                    match receiver.find_by_username(username.clone()).await {
+                        Ok(receivers) => QuickResponse::res_json(&receivers),
+                        Err(err) => QuickResponse::err(err.into()),
+                    }
+                }
+                JSONRPCMethod::AdminGetReceiverStats(username) => {
+                    // Only orand could able to read receivers of another user
+                    if !jwt_payload.user.eq(ORAND_KEYRING_NAME) && !jwt_payload.user.eq(&username) {
+                        return QuickResponse::err(Error(
+                            ErrorCode::Forbidden,
+                            "Access denied, you do not have ability to read receivers of this user",
+                        ));
+                    }
+                    match receiver.stats(username).await {
+                        Ok(stats) => QuickResponse::res_json(&stats),
+                        Err(err) => {
+                            log::error!("Unable to query receiver stats {}", err);
+                            QuickResponse::err(Error(
+                                ErrorCode::Internal,
+                                "Unable to query receiver stats",
+                            ))
+                        }
+                    }
+                }
+                JSONRPCMethod::AdminResignEpoch(network, address, epoch, nonce) => {
+                    // Only orand could able to re-sign an epoch
+                    if !jwt_payload.user.eq(ORAND_KEYRING_NAME) {
+                        return QuickResponse::err(Error(
+                            ErrorCode::Forbidden,
+                            "Access denied, you do not have ability to re-sign epoch",
+                        ));
+                    }
+                    let randomness = context.postgres().table_randomness();
+                    match randomness
+                        .resign_epoch(Arc::clone(&context), network, &address, epoch, nonce)
+                        .await
+                    {
+                        Ok(record) => QuickResponse::res_json(&record),
+                        Err(DbErr::RecordNotFound(_)) => {
+                            QuickResponse::err(Error(ErrorCode::NotFound, "Epoch was not found"))
+                        }
+                        Err(err) => {
+                            log::error!("Unable to re-sign epoch {}", err);
+                            QuickResponse::err(Error(
+                                ErrorCode::Internal,
+                                "Unable to re-sign epoch",
+                            ))
+                        }
+                    }
+                }
+                JSONRPCMethod::AdminRemoveReceiver(username, receiver_id) => {
+                    let result = receiver.delete(username, receiver_id).await;
+                    match result {
+                        Ok(_) => QuickResponse::res_json(
+                            &json!({"success": true, "message": "Receiver has been removed"}),
+                        ),
+                        Err(_) => QuickResponse::err(Error(
+                            ErrorCode::Internal,
+                            "Unable to remove receiver",
+                        )),
+                    }
+                }
+                _ => QuickResponse::err(Error(
+                    ErrorCode::NotImplemented,
+                    "It is not working in this way",
+                )),
+            }
+        }
+        _ => QuickResponse::err(Error(
+            ErrorCode::NotImplemented,
+            "It is not working in this way",
+        )),
+    }
+}
@@ -1,15 +1,29 @@
-use std::time::SystemTime;
+use std::{
+    collections::HashMap,
+    sync::Mutex,
+    time::{Duration, Instant, SystemTime},
+};
 
-use crate::{rpc::check_name, Error};
+use crate::{rpc::check_name, Error, ErrorCode};
 use base64_url;
 use hex;
 use hmac::{Hmac, Mac};
+use libecvrf::secp256k1::{
+    sign_with_context, verify, Message, PublicKey, SecretKey, Signature, ECMULT_GEN_CONTEXT,
+};
 use serde::{Deserialize, Serialize};
-use sha2::Sha256;
+use serde_json::Value;
+use sha2::{Digest, Sha256};
 
 // Create alias for HMAC-SHA256
 type HmacSha256 = Hmac<Sha256>;
 
+/// Header of a JWT signed with a shared HMAC secret
+const HS256_HEADER: &str = r#"{"alg":"HS256","typ":"JWT"}"#;
+
+/// Header of a JWT signed with a secp256k1 key
+const ES256K_HEADER: &str = r#"{"alg":"ES256K","typ":"JWT"}"#;
+
 /// JWT Payload
 #[derive(Clone, Debug, Deserialize, Serialize, Default)]
 pub struct JWTPayload {
@@ -47,43 +61,375 @@ impl JWT {
         if split_jwt.len() == 3 {
             let decoded_payload = match base64_url::decode(&split_jwt[1]) {
                 Ok(payload) => payload,
-                Err(_) => return Err(Error("INVALID_PAYLOAD", "Unable to decode payload")),
+                Err(_) => return Err(Error(ErrorCode::BadRequest, "Unable to decode payload")),
             };
             let jwt_payload: JWTPayload = match serde_json::from_slice(&decoded_payload) {
                 Ok(payload) => payload,
-                Err(_) => return Err(Error("INVALID_PAYLOAD", "Unable to deserialize payload")),
+                Err(_) => {
+                    return Err(Error(
+                        ErrorCode::BadRequest,
+                        "Unable to deserialize payload",
+                    ))
+                }
             };
             // Check if JWT is expired, iat < current_time < exp
             if current_time > jwt_payload.exp
                 || current_time < jwt_payload.iat
                 || jwt_payload.iat > jwt_payload.exp
             {
-                return Err(Error("EXPIRED_JWT", "JWT is expired"));
+                return Err(Error(ErrorCode::Unauthorized, "JWT is expired"));
             }
             if check_name(jwt_payload.user.clone()) {
                 return Ok(jwt_payload);
             } else {
-                return Err(Error("INVALID_USERNAME", "Invalid username"));
+                return Err(Error(ErrorCode::BadRequest, "Invalid username"));
             }
         }
-        Err(Error("INVALID_JWT", "Invalid JWT format"))
+        Err(Error(ErrorCode::Unauthorized, "Invalid JWT format"))
     }
 
-    /// Encode payload to JWT
+    /// Sign payload to JWT, the signature is HMAC-SHA256 of the signing input
+    /// `header.payload`, the same message that [JWT::verify] checks
+    pub fn sign(&self, payload: &JWTPayload) -> String {
+        let payload = serde_json::to_vec(payload).expect("Unable to serialize payload");
+        let signing_input = format!(
+            "{}.{}",
+            base64_url::encode(HS256_HEADER),
+            base64_url::encode(&payload)
+        );
+        let mut mac =
+            HmacSha256::new_from_slice(&self.secret_key).expect("HMAC can take key of any size");
+        mac.update(signing_input.as_bytes());
+        format!(
+            "{}.{}",
+            signing_input,
+            base64_url::encode(&mac.finalize().into_bytes())
+        )
+    }
+
+    /// Verify JWT with the HMAC secret, a malformed token is an ordinary failure
     pub fn verify(&self, json_web_token: &str) -> bool {
         let split_jwt: Vec<&str> = json_web_token.trim().split('.').collect();
-        if split_jwt.len() == 3 {
-            let payload = split_jwt[1];
-            let signature = split_jwt[2];
-            let mut mac = HmacSha256::new_from_slice(&self.secret_key)
-                .expect("HMAC can take key of any size");
-            mac.update(&base64_url::decode(&payload).expect("Unable to decode base64 payload"));
-            return mac
-                .verify_slice(
-                    &base64_url::decode(&signature).expect("Unable to decode base64 signature"),
-                )
-                .is_ok();
+        if split_jwt.len() != 3 {
+            return false;
+        }
+        let signature = match base64_url::decode(split_jwt[2]) {
+            Ok(signature) => signature,
+            Err(_) => return false,
+        };
+        let signing_input = format!("{}.{}", split_jwt[0], split_jwt[1]);
+        let mut mac =
+            HmacSha256::new_from_slice(&self.secret_key).expect("HMAC can take key of any size");
+        mac.update(signing_input.as_bytes());
+        mac.verify_slice(&signature).is_ok()
+    }
+}
+
+/// JWT signed with the secp256k1 key of the operator, ES256K, anyone who knows the
+/// public key is able to verify it without a shared secret
+pub struct JWTES256K {
+    secret_key: Option<SecretKey>,
+    public_key: PublicKey,
+}
+
+impl JWTES256K {
+    /// Create new instance of ES256K JWT that is able to sign and verify
+    pub fn new(secret_key: SecretKey) -> Self {
+        let public_key = PublicKey::from_secret_key(&secret_key);
+        JWTES256K {
+            secret_key: Some(secret_key),
+            public_key,
+        }
+    }
+
+    /// Create new instance of ES256K JWT that is only able to verify
+    pub fn new_verifier(public_key: PublicKey) -> Self {
+        JWTES256K {
+            secret_key: None,
+            public_key,
+        }
+    }
+
+    /// SHA-256 of the signing input, `header.payload`
+    fn message(signing_input: &str) -> Message {
+        let mut digest = [0u8; 32];
+        digest.copy_from_slice(&Sha256::digest(signing_input.as_bytes()));
+        Message::parse(&digest)
+    }
+
+    /// Sign payload to JWT, the signature is r || s of the signing input
+    pub fn sign(&self, payload: &JWTPayload) -> Result<String, Error> {
+        let secret_key = self.secret_key.as_ref().ok_or(Error(
+            ErrorCode::Internal,
+            "Secret key is required to sign JWT",
+        ))?;
+        let payload = serde_json::to_vec(payload)
+            .map_err(|_| Error(ErrorCode::Internal, "Unable to serialize payload"))?;
+        let signing_input = format!(
+            "{}.{}",
+            base64_url::encode(ES256K_HEADER),
+            base64_url::encode(&payload)
+        );
+        let (signature, _) = sign_with_context(
+            &Self::message(&signing_input),
+            secret_key,
+            &ECMULT_GEN_CONTEXT,
+        );
+        Ok(format!(
+            "{}.{}",
+            signing_input,
+            base64_url::encode(&signature.serialize())
+        ))
+    }
+
+    /// Verify JWT with the public key, the header must be ES256K
+    pub fn verify(&self, json_web_token: &str) -> bool {
+        let split_jwt: Vec<&str> = json_web_token.trim().split('.').collect();
+        if split_jwt.len() != 3 {
+            return false;
+        }
+        // Reject tokens of other algorithms, e.g. HS256 signed with the public key
+        let header = match base64_url::decode(split_jwt[0])
+            .ok()
+            .and_then(|header| serde_json::from_slice::<Value>(&header).ok())
+        {
+            Some(header) => header,
+            None => return false,
+        };
+        if header["alg"] != "ES256K" {
+            return false;
+        }
+        let signature = match base64_url::decode(split_jwt[2])
+            .ok()
+            .and_then(|signature| Signature::parse_standard_slice(&signature).ok())
+        {
+            Some(signature) => signature,
+            None => return false,
+        };
+        let signing_input = format!("{}.{}", split_jwt[0], split_jwt[1]);
+        verify(&Self::message(&signing_input), &signature, &self.public_key)
+    }
+}
+
+/// Consecutive failed verifications before a user is locked out
+pub const MAX_FAILED_VERIFICATIONS: u32 = 5;
+
+/// Window that failures are counted in, it's also the lockout cooldown
+pub const LOCKOUT_WINDOW: Duration = Duration::from_secs(60);
+
+/// Failed verifications of a user
+#[derive(Debug, Clone, Copy)]
+struct FailedVerification {
+    failures: u32,
+    window_start: Instant,
+    locked_until: Option<Instant>,
+}
+
+/// Guard JWT verification against online guessing of a HMAC secret, a user is locked
+/// out for [LOCKOUT_WINDOW] after [MAX_FAILED_VERIFICATIONS] consecutive failures
+#[derive(Debug, Default)]
+pub struct JWTGuard {
+    failures: Mutex<HashMap<String, FailedVerification>>,
+}
+
+impl JWTGuard {
+    /// Create new instance of JWT guard
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Check if the user is allowed to verify at the given time
+    pub fn check(&self, user: &str, now: Instant) -> Result<(), Error> {
+        let mut failures = self.failures.lock().expect("JWT guard lock is poisoned");
+        match failures.get(user).and_then(|failure| failure.locked_until) {
+            Some(locked_until) if now < locked_until => Err(Error(
+                ErrorCode::Forbidden,
+                "Too many failed attempts, please retry later",
+            )),
+            Some(_) => {
+                // Cooldown is over
+                failures.remove(user);
+                Ok(())
+            }
+            None => Ok(()),
+        }
+    }
+
+    /// Record a failed verification of the user
+    pub fn record_failure(&self, user: &str, now: Instant) {
+        let mut failures = self.failures.lock().expect("JWT guard lock is poisoned");
+        let failure = failures
+            .entry(user.to_string())
+            .or_insert(FailedVerification {
+                failures: 0,
+                window_start: now,
+                locked_until: None,
+            });
+        // Failures out of the window are forgotten
+        if now.duration_since(failure.window_start) > LOCKOUT_WINDOW {
+            failure.failures = 0;
+            failure.window_start = now;
+        }
+        failure.failures += 1;
+        if failure.failures >= MAX_FAILED_VERIFICATIONS {
+            failure.locked_until = Some(now + LOCKOUT_WINDOW);
+        }
+    }
+
+    /// Record a successful verification, the failure counter is reset
+    pub fn record_success(&self, user: &str) {
+        self.failures
+            .lock()
+            .expect("JWT guard lock is poisoned")
+            .remove(user);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{JWTGuard, JWTPayload, JWT, JWTES256K, LOCKOUT_WINDOW, MAX_FAILED_VERIFICATIONS};
+    use crate::ErrorCode;
+    use libecvrf::KeyPair;
+    use std::time::{Duration, Instant};
+
+    #[test]
+    fn signed_jwt_should_verify() {
+        let jwt = JWT::new(&hex::encode([1u8; 32]));
+        let payload = JWTPayload {
+            user: "chiro".to_string(),
+            nonce: 1,
+            iat: 1_700_000_000,
+            exp: 1_700_003_600,
+        };
+        let token = jwt.sign(&payload);
+        assert_eq!(token.split('.').count(), 3);
+        assert!(jwt.verify(&token));
+
+        // Another secret
+        assert!(!JWT::new(&hex::encode([2u8; 32])).verify(&token));
+
+        // Tampered payload, the signature is kept
+        let parts = token.split('.').collect::<Vec<&str>>();
+        let tampered = JWTPayload {
+            user: "orand".to_string(),
+            ..payload
+        };
+        let tampered_payload =
+            base64_url::encode(&serde_json::to_vec(&tampered).expect("Unable to serialize"));
+        let tampered_token = format!("{}.{}.{}", parts[0], tampered_payload, parts[2]);
+        assert!(!jwt.verify(&tampered_token));
+
+        // Malformed base64 is a failed verification, not a panic
+        assert!(!jwt.verify(&format!("{}.{}.!!!", parts[0], parts[1])));
+        assert!(!jwt.verify(&format!("{}.!!!.{}", parts[0], parts[2])));
+        assert!(!jwt.verify("a.b"));
+    }
+
+    #[test]
+    fn hs256_jwt_should_match_known_vector() {
+        // Example token of RFC 7519 tools, HMAC is computed over `header.payload`
+        let jwt = JWT::new(&hex::encode("your-256-bit-secret"));
+        let token = "eyJhbGciOiJIUzI1NiIsInR5cCI6IkpXVCJ9.\
+                     eyJzdWIiOiIxMjM0NTY3ODkwIiwibmFtZSI6IkpvaG4gRG9lIiwiaWF0IjoxNTE2MjM5MDIyfQ.\
+                     SflKxwRJSMeKKF2QT4fwpMeJf36POk6yJV_adQssw5c";
+        assert!(jwt.verify(token));
+        assert!(!JWT::new(&hex::encode("another-secret")).verify(token));
+
+        // Signed token is a standard HS256 JWT of the serialized payload
+        let payload = JWTPayload {
+            user: "chiro".to_string(),
+            nonce: 1,
+            iat: 1_700_000_000,
+            exp: 1_700_003_600,
+        };
+        assert_eq!(
+            JWT::new(&hex::encode([1u8; 32])).sign(&payload),
+            "eyJhbGciOiJIUzI1NiIsInR5cCI6IkpXVCJ9.\
+             eyJ1c2VyIjoiY2hpcm8iLCJub25jZSI6MSwiaWF0IjoxNzAwMDAwMDAwLCJleHAiOjE3MDAwMDM2MDB9.\
+             XqQluDAUTM2s_5-S47Md8k2AjZECaPgTouHSRIJf_vY"
+        );
+    }
+
+    #[test]
+    fn es256k_jwt_should_verify_with_public_key() {
+        let keypair = KeyPair::new();
+        let payload = JWTPayload {
+            user: "orand".to_string(),
+            nonce: 1,
+            iat: 1_700_000_000,
+            exp: 1_700_003_600,
+        };
+        let token = JWTES256K::new(keypair.secret_key)
+            .sign(&payload)
+            .expect("Unable to sign JWT");
+        // Third party only knows the public key
+        let verifier = JWTES256K::new_verifier(keypair.public_key);
+        assert!(verifier.verify(&token));
+        assert!(verifier.sign(&payload).is_err());
+
+        // Signed with another key
+        let other_token = JWTES256K::new(KeyPair::new().secret_key)
+            .sign(&payload)
+            .expect("Unable to sign JWT");
+        assert!(!verifier.verify(&other_token));
+
+        // Tampered payload, the signature is kept
+        let parts = token.split('.').collect::<Vec<&str>>();
+        let tampered = JWTPayload {
+            user: "chiro".to_string(),
+            ..payload.clone()
+        };
+        let tampered_payload =
+            base64_url::encode(&serde_json::to_vec(&tampered).expect("Unable to serialize"));
+        assert!(!verifier.verify(&format!("{}.{}.{}", parts[0], tampered_payload, parts[2])));
+
+        // HS256 token and garbage are rejected
+        let hs256_token = JWT::new(&hex::encode([1u8; 32])).sign(&payload);
+        assert!(!verifier.verify(&hs256_token));
+        assert!(!verifier.verify("a.b.c"));
+        assert!(!verifier.verify(""));
+    }
+
+    #[test]
+    fn guard_should_lock_out_after_consecutive_failures() {
+        let guard = JWTGuard::new();
+        let now = Instant::now();
+        for i in 0..MAX_FAILED_VERIFICATIONS {
+            assert!(guard.check("chiro", now).is_ok());
+            guard.record_failure("chiro", now + Duration::from_secs(i as u64));
+        }
+        let locked = now + Duration::from_secs(MAX_FAILED_VERIFICATIONS as u64);
+        assert_eq!(
+            guard.check("chiro", locked).map_err(|e| e.code()),
+            Err(ErrorCode::Forbidden)
+        );
+        // Other users are not affected
+        assert!(guard.check("orand", locked).is_ok());
+
+        // Lockout clears after the window
+        assert!(guard.check("chiro", locked + LOCKOUT_WINDOW).is_ok());
+        guard.record_failure("chiro", locked + LOCKOUT_WINDOW);
+        assert!(guard.check("chiro", locked + LOCKOUT_WINDOW).is_ok());
+    }
+
+    #[test]
+    fn guard_should_reset_on_success() {
+        let guard = JWTGuard::new();
+        let now = Instant::now();
+        for _ in 1..MAX_FAILED_VERIFICATIONS {
+            guard.record_failure("chiro", now);
+        }
+        guard.record_success("chiro");
+        guard.record_failure("chiro", now);
+        assert!(guard.check("chiro", now).is_ok());
+
+        // Failures spread over more than the window are not consecutive
+        let guard = JWTGuard::new();
+        for i in 0..MAX_FAILED_VERIFICATIONS {
+            guard.record_failure("chiro", now + LOCKOUT_WINDOW * (i + 1) * 2);
         }
-        false
+        assert!(guard
+            .check("chiro", now + LOCKOUT_WINDOW * MAX_FAILED_VERIFICATIONS * 2)
+            .is_ok());
     }
 }
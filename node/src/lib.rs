@@ -24,7 +24,7 @@ pub mod rpc;
 
 /// Error handling
 mod error;
-pub use error::Error;
+pub use error::{Error, ErrorCode};
 
 mod quick_response;
 pub use quick_response::*;
@@ -33,3 +33,16 @@ mod node_context;
 pub use node_context::*;
 
 pub mod evm;
+
+/// Epoch ciphersuite and verification
+pub mod epoch;
+
+/// Keccak Merkle tree of epoch results
+pub mod merkle;
+
+/// Startup configuration of the node
+mod config;
+pub use config::*;
+
+/// HTTP handler of the JSON RPC
+pub mod handler;
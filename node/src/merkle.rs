@@ -0,0 +1,164 @@
+use tiny_keccak::{Hasher, Keccak};
+
+/// Keccak256 of the concatenated inputs
+fn keccak256(inputs: &[&[u8]]) -> [u8; 32] {
+    let mut hasher = Keccak::v256();
+    let mut output = [0u8; 32];
+    for input in inputs {
+        hasher.update(input);
+    }
+    hasher.finalize(&mut output);
+    output
+}
+
+/// Leaf of an epoch result, leaves are hashed so a leaf can't be mistaken for a node
+pub fn leaf_hash(y: &[u8; 32]) -> [u8; 32] {
+    keccak256(&[y])
+}
+
+/// Parent of two nodes, keccak256(left || right)
+pub fn node_hash(left: &[u8; 32], right: &[u8; 32]) -> [u8; 32] {
+    keccak256(&[left, right])
+}
+
+/// Sibling of the last node of an odd level, no leaf or node is known to hash to it so
+/// a result can't be proven at a position past the end of the range
+pub const EMPTY_NODE: [u8; 32] = [0u8; 32];
+
+/// Next level of the tree, the last node of an odd level is paired with [EMPTY_NODE]
+fn next_level(level: &[[u8; 32]]) -> Vec<[u8; 32]> {
+    level
+        .chunks(2)
+        .map(|pair| node_hash(&pair[0], pair.get(1).unwrap_or(&EMPTY_NODE)))
+        .collect()
+}
+
+/// Merkle root of the results of consecutive epochs in order, there is no root of an
+/// empty range
+pub fn merkle_root(ys: &[[u8; 32]]) -> Option<[u8; 32]> {
+    if ys.is_empty() {
+        return None;
+    }
+    let mut level = ys.iter().map(leaf_hash).collect::<Vec<[u8; 32]>>();
+    while level.len() > 1 {
+        level = next_level(&level);
+    }
+    Some(level[0])
+}
+
+/// Merkle path of the result at `index`, the siblings from the leaf up to the root
+pub fn merkle_path(ys: &[[u8; 32]], index: usize) -> Option<Vec<[u8; 32]>> {
+    if index >= ys.len() {
+        return None;
+    }
+    let mut level = ys.iter().map(leaf_hash).collect::<Vec<[u8; 32]>>();
+    let mut position = index;
+    let mut path = Vec::new();
+    while level.len() > 1 {
+        let sibling = position ^ 1;
+        path.push(*level.get(sibling).unwrap_or(&EMPTY_NODE));
+        level = next_level(&level);
+        position /= 2;
+    }
+    Some(path)
+}
+
+/// Verify that `y` is the result at `index` of the range committed to by `root`, the
+/// bits of the index pick the side of each sibling the same way a contract would
+pub fn verify_membership(root: &[u8; 32], y: &[u8; 32], index: usize, path: &[[u8; 32]]) -> bool {
+    // An index beyond the depth of the path can't be in the tree
+    if path.len() < usize::BITS as usize && index >> path.len() != 0 {
+        return false;
+    }
+    let mut position = index;
+    let computed = path.iter().fold(leaf_hash(y), |node, sibling| {
+        let parent = match position & 1 {
+            0 => node_hash(&node, sibling),
+            _ => node_hash(sibling, &node),
+        };
+        position >>= 1;
+        parent
+    });
+    computed == *root
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{leaf_hash, merkle_path, merkle_root, node_hash, verify_membership, EMPTY_NODE};
+
+    fn results(count: u8) -> Vec<[u8; 32]> {
+        (0..count).map(|i| [i; 32]).collect()
+    }
+
+    #[test]
+    fn merkle_root_should_hash_leaves_in_order() {
+        assert_eq!(merkle_root(&[]), None);
+        let ys = results(3);
+        assert_eq!(merkle_root(&ys[..1]), Some(leaf_hash(&ys[0])));
+        assert_eq!(
+            merkle_root(&ys[..2]),
+            Some(node_hash(&leaf_hash(&ys[0]), &leaf_hash(&ys[1])))
+        );
+        // The last node of an odd level is paired with the empty node
+        let left = node_hash(&leaf_hash(&ys[0]), &leaf_hash(&ys[1]));
+        let right = node_hash(&leaf_hash(&ys[2]), &EMPTY_NODE);
+        assert_eq!(merkle_root(&ys), Some(node_hash(&left, &right)));
+    }
+
+    #[test]
+    fn merkle_root_should_change_with_any_result() {
+        let ys = results(7);
+        let root = merkle_root(&ys);
+        assert_eq!(merkle_root(&ys), root);
+        for i in 0..ys.len() {
+            let mut changed = ys.clone();
+            changed[i][31] ^= 1;
+            assert_ne!(merkle_root(&changed), root);
+        }
+        // Order matters
+        let mut swapped = ys.clone();
+        swapped.swap(0, 1);
+        assert_ne!(merkle_root(&swapped), root);
+    }
+
+    #[test]
+    fn merkle_path_should_verify_every_result() {
+        for count in 1..=9 {
+            let ys = results(count);
+            let root = merkle_root(&ys).expect("Range is not empty");
+            for (index, y) in ys.iter().enumerate() {
+                let path = merkle_path(&ys, index).expect("Index is in range");
+                assert!(verify_membership(&root, y, index, &path));
+                // Wrong result or wrong position
+                assert!(!verify_membership(&root, &[0xff; 32], index, &path));
+                if (index ^ 1) < ys.len() {
+                    assert!(!verify_membership(&root, y, index ^ 1, &path));
+                }
+            }
+            assert_eq!(merkle_path(&ys, ys.len()), None);
+        }
+    }
+
+    #[test]
+    fn verify_membership_should_reject_index_beyond_path() {
+        let ys = results(2);
+        let root = merkle_root(&ys).expect("Range is not empty");
+        let path = merkle_path(&ys, 0).expect("Index is in range");
+        // Index 2 has the same low bit as index 0
+        assert!(!verify_membership(&root, &ys[0], 2, &path));
+        assert!(!verify_membership(&root, &ys[0], usize::MAX, &path));
+    }
+
+    #[test]
+    fn verify_membership_should_reject_index_past_range() {
+        for count in 1..=9 {
+            let ys = results(count);
+            let root = merkle_root(&ys).expect("Range is not empty");
+            let last = ys.len() - 1;
+            let path = merkle_path(&ys, last).expect("Index is in range");
+            assert!(verify_membership(&root, &ys[last], last, &path));
+            // The position right after the range is within the depth of the tree
+            assert!(!verify_membership(&root, &ys[last], last + 1, &path));
+        }
+    }
+}
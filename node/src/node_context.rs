@@ -1,16 +1,161 @@
-use libecvrf::{KeyPair, ECVRF};
-use std::sync::Arc;
+use libecvrf::{
+    extends::ScalarExtend, secp256k1::curve::Scalar, ECVRFContractProof, KeyPair, ECVRF,
+};
+use sea_orm::DbErr;
+use serde::{Deserialize, Serialize};
+use std::{
+    future::Future,
+    sync::{Arc, RwLock, RwLockReadGuard},
+};
 use tokio::sync::Mutex;
 
-use crate::postgres_sql::Postgres;
+use crate::{
+    epoch::ProofFormat,
+    error::{Error, ErrorCode},
+    ethereum::EthAddress,
+    jwt::JWTGuard,
+    key_rotation,
+    postgres_sql::Postgres,
+    table::KeyHistory,
+    Config,
+};
+
+/// Network served by the node
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Serialize, Deserialize)]
+pub struct NetworkStatus {
+    /// Network chain Id
+    pub network: u64,
+    /// Public epoch is served, public epochs are enabled and a ZERO_ADDRESS receiver
+    /// exists on this network
+    pub public_enabled: bool,
+}
+
+/// Policy to register an unknown receiver when a new epoch is requested
+#[derive(Debug, Clone, Copy, Default, Eq, PartialEq)]
+pub enum AutoRegister {
+    /// Unknown receiver is rejected
+    Never,
+    /// Unknown receiver is registered only on testnet
+    #[default]
+    OnTestnet,
+    /// Unknown receiver is always registered
+    Always,
+}
+
+impl AutoRegister {
+    /// Check if an unknown receiver should be registered
+    pub fn allows(&self, is_testnet: bool) -> bool {
+        match self {
+            AutoRegister::Never => false,
+            AutoRegister::OnTestnet => is_testnet,
+            AutoRegister::Always => true,
+        }
+    }
+}
+
+impl TryFrom<&str> for AutoRegister {
+    type Error = Error;
+
+    fn try_from(value: &str) -> Result<Self, Self::Error> {
+        match value.trim().to_lowercase().as_str() {
+            "never" => Ok(AutoRegister::Never),
+            "on_testnet" => Ok(AutoRegister::OnTestnet),
+            "always" => Ok(AutoRegister::Always),
+            _ => Err(Error(
+                ErrorCode::BadRequest,
+                "Auto register must be never, on_testnet or always",
+            )),
+        }
+    }
+}
+
+/// Behavior of a node that is chosen by its operator, see [Config]
+#[derive(Debug, Clone, Default, Eq, PartialEq)]
+pub struct NodeOptions {
+    /// Node is running on testnet
+    pub is_testnet: bool,
+    /// Registration of unknown receivers
+    pub auto_register: AutoRegister,
+    /// Chain ids of the networks served by this node
+    pub networks: Vec<u64>,
+    /// Format of newly stored proofs
+    pub proof_format: ProofFormat,
+    /// Private-only deployment, public epochs are not served
+    pub disable_public: bool,
+}
+
+impl From<&Config> for NodeOptions {
+    fn from(config: &Config) -> Self {
+        NodeOptions {
+            is_testnet: config.is_testnet,
+            auto_register: config.auto_register,
+            networks: config.networks.clone(),
+            proof_format: config.proof_format,
+            disable_public: config.disable_public,
+        }
+    }
+}
+
+/// Prove and verify a random alpha with the given keypair, it makes sure the loaded
+/// key works with the VRF code before the node starts serving
+pub fn vrf_self_test(keypair: &KeyPair) -> Result<(), Error> {
+    let ecvrf = ECVRF::new(keypair.secret_key);
+    let alpha = Scalar::randomize();
+    let proof = ecvrf
+        .prove(&alpha)
+        .map_err(|_| Error(ErrorCode::Internal, "VRF self-test unable to prove"))?;
+    if proof.pk != keypair.public_key {
+        return Err(Error(
+            ErrorCode::Internal,
+            "VRF self-test public key does not match secret key",
+        ));
+    }
+    // Verify with the stored public key only, the same way any third party would
+    let verifier = ECVRF::new_verifier(keypair.public_key);
+    if !verifier.verify(&alpha, &proof) {
+        return Err(Error(ErrorCode::Internal, "VRF self-test unable to verify"));
+    }
+    let contract_proof = ecvrf
+        .prove_contract(&alpha)
+        .map_err(|_| Error(ErrorCode::Internal, "VRF self-test unable to prove"))?;
+    if !verifier.verify_contract(&contract_proof) {
+        return Err(Error(
+            ErrorCode::Internal,
+            "VRF self-test unable to verify contract proof",
+        ));
+    }
+    Ok(())
+}
+
+/// Prove a contract proof on the blocking thread pool, the EC math is CPU bound and
+/// would otherwise stall every other request served by the same executor thread
+pub fn spawn_prove(
+    vrf: Arc<ECVRF<'static>>,
+    alpha: Scalar,
+) -> impl Future<Output = Result<ECVRFContractProof, Error>> {
+    let task = tokio::task::spawn_blocking(move || vrf.prove_contract(&alpha));
+    async move {
+        match task.await {
+            Ok(Ok(contract_proof)) => Ok(contract_proof),
+            Ok(Err(_)) => Err(Error(ErrorCode::Internal, "Unable to prove contract")),
+            Err(_) => Err(Error(ErrorCode::Internal, "Proving task was aborted")),
+        }
+    }
+}
 
 /// Node context
 pub struct NodeContext<'a> {
-    ecvrf: ECVRF<'a>,
+    // Operator key is swapped on rotation, guards must not be held across an await
+    ecvrf: RwLock<Arc<ECVRF<'a>>>,
     is_testnet: bool,
+    auto_register: AutoRegister,
+    networks: Vec<u64>,
+    proof_format: ProofFormat,
+    disable_public: bool,
     postgres: Postgres,
     key_id: i64,
-    keypair: KeyPair,
+    keypair: RwLock<KeyPair>,
+    jwt_guard: JWTGuard,
     // Single lock will be the botle neck when we have more user
     // I'm prefer to use [HashMap] to mapping from receiver_id -> lock
     pub sync: Mutex<bool>,
@@ -18,14 +163,31 @@ pub struct NodeContext<'a> {
 
 impl<'a> NodeContext<'a> {
     /// Create a new instance of node context
-    pub fn new(key_id: i64, keypair: KeyPair, is_testnet: bool, postgres: Postgres) -> Arc<Self> {
+    pub fn new(
+        key_id: i64,
+        keypair: KeyPair,
+        options: NodeOptions,
+        postgres: Postgres,
+    ) -> Arc<Self> {
+        let NodeOptions {
+            is_testnet,
+            auto_register,
+            networks,
+            proof_format,
+            disable_public,
+        } = options;
         let ecvrf = ECVRF::new(keypair.secret_key);
         Arc::new(Self {
             key_id,
-            ecvrf,
+            ecvrf: RwLock::new(Arc::new(ecvrf)),
             is_testnet,
+            auto_register,
+            networks,
+            proof_format,
+            disable_public,
             postgres,
-            keypair,
+            keypair: RwLock::new(keypair),
+            jwt_guard: JWTGuard::new(),
             sync: Mutex::new(false),
         })
     }
@@ -36,13 +198,46 @@ impl<'a> NodeContext<'a> {
     }
 
     /// Get keypair
-    pub fn keypair(&self) -> &KeyPair {
-        &self.keypair
+    pub fn keypair(&self) -> RwLockReadGuard<'_, KeyPair> {
+        self.keypair
+            .read()
+            .expect("Operator keypair lock is poisoned")
+    }
+
+    /// Get the ECVRF instance of the operator key, it's built once and shared by every
+    /// request, a rotation replaces it without affecting instances already handed out
+    pub fn vrf(&self) -> Arc<ECVRF<'a>> {
+        Arc::clone(&self.ecvrf.read().expect("Operator ECVRF lock is poisoned"))
+    }
+
+    /// Replace the operator key with a new one, every epoch up to the returned rotation
+    /// epoch was proven with the old key and the next one is proven with the new key
+    pub async fn rotate_operator_key(&self) -> Result<key_rotation::Model, DbErr> {
+        // No epoch is created while the key is being replaced
+        let _lock = self.sync.lock().await;
+        let keypair = KeyPair::new();
+        vrf_self_test(&keypair).map_err(|e| DbErr::Custom(e.reason().to_string()))?;
+        let rotation = self
+            .postgres
+            .table_key_rotation()
+            .rotate(self.key_id, &keypair)
+            .await?;
+        *self.ecvrf.write().expect("Operator ECVRF lock is poisoned") =
+            Arc::new(ECVRF::new(keypair.secret_key));
+        *self
+            .keypair
+            .write()
+            .expect("Operator keypair lock is poisoned") = keypair;
+        Ok(rotation)
     }
 
-    /// Get ECVRF instance
-    pub fn ecvrf(&self) -> &ECVRF<'_> {
-        &self.ecvrf
+    /// Get public keys of the operator over time
+    pub async fn key_history(&self) -> Result<KeyHistory, DbErr> {
+        let current = self.keypair().public_key;
+        self.postgres
+            .table_key_rotation()
+            .history(self.key_id, current)
+            .await
     }
 
     /// Check if node is running on testnet
@@ -50,8 +245,271 @@ impl<'a> NodeContext<'a> {
         self.is_testnet
     }
 
+    /// Get auto register policy
+    pub fn auto_register(&self) -> AutoRegister {
+        self.auto_register
+    }
+
+    /// Check if an unknown receiver should be registered on this node
+    pub fn should_auto_register(&self) -> bool {
+        self.auto_register.allows(self.is_testnet)
+    }
+
+    /// Get the format that new proofs are stored in
+    pub fn proof_format(&self) -> ProofFormat {
+        self.proof_format
+    }
+
+    /// Check if public epochs are disabled, the node only serves private receivers
+    pub fn is_public_disabled(&self) -> bool {
+        self.disable_public
+    }
+
+    /// Reject methods on public epochs when they are disabled, whoever the caller is
+    pub fn check_public(&self, is_public: bool) -> Result<(), Error> {
+        if is_public && self.disable_public {
+            return Err(Error(
+                ErrorCode::NotImplemented,
+                "Public epochs are disabled on this node",
+            ));
+        }
+        Ok(())
+    }
+
+    /// Get configured networks
+    pub fn networks(&self) -> &[u64] {
+        &self.networks
+    }
+
+    /// Get configured networks and whether public epoch is served on each of them
+    pub async fn network_status(&self) -> Result<Vec<NetworkStatus>, DbErr> {
+        let public_receivers = self
+            .postgres
+            .table_receiver()
+            .find_by_address(&EthAddress::ZERO, &self.networks)
+            .await?;
+        Ok(self
+            .networks
+            .iter()
+            .map(|network| NetworkStatus {
+                network: *network,
+                public_enabled: !self.disable_public
+                    && public_receivers
+                        .iter()
+                        .any(|receiver| receiver.network as u64 == *network),
+            })
+            .collect())
+    }
+
+    /// Get JWT guard, it tracks failed verifications of each user
+    pub fn jwt_guard(&self) -> &JWTGuard {
+        &self.jwt_guard
+    }
+
     /// Get Postgres database
     pub fn postgres(&self) -> &Postgres {
         &self.postgres
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::{spawn_prove, vrf_self_test, AutoRegister, NetworkStatus, NodeOptions};
+    use crate::{
+        postgres_sql::Postgres,
+        receiver,
+        rpc::{JSONRPCMethod, ZERO_ADDRESS},
+        ErrorCode, NodeContext,
+    };
+    use libecvrf::{extends::ScalarExtend, secp256k1::curve::Scalar, KeyPair, ECVRF};
+    use sea_orm::{prelude::DateTime, DatabaseBackend, MockDatabase};
+    use std::sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc,
+    };
+
+    #[tokio::test]
+    async fn network_status_should_report_public_receiver() {
+        // Only network 1 has a ZERO_ADDRESS receiver
+        let connection = MockDatabase::new(DatabaseBackend::Postgres)
+            .append_query_results([vec![receiver::Model {
+                id: 1,
+                keyring_id: 1,
+                name: "public".to_string(),
+                address: ZERO_ADDRESS.to_string(),
+                network: 1,
+                nonce: 0,
+                created_date: DateTime::default(),
+            }]])
+            .into_connection();
+        let context = NodeContext::new(
+            1,
+            KeyPair::new(),
+            NodeOptions {
+                auto_register: AutoRegister::Never,
+                networks: vec![1, 56],
+                ..Default::default()
+            },
+            Postgres::from_connection(connection),
+        );
+
+        assert_eq!(context.networks(), &[1, 56]);
+        assert_eq!(
+            context
+                .network_status()
+                .await
+                .expect("Unable to query network status"),
+            vec![
+                NetworkStatus {
+                    network: 1,
+                    public_enabled: true
+                },
+                NetworkStatus {
+                    network: 56,
+                    public_enabled: false
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn public_methods_should_be_rejected_when_disabled() {
+        let connection = MockDatabase::new(DatabaseBackend::Postgres).into_connection();
+        let context = NodeContext::new(
+            1,
+            KeyPair::new(),
+            NodeOptions {
+                auto_register: AutoRegister::Never,
+                networks: vec![1],
+                disable_public: true,
+                ..Default::default()
+            },
+            Postgres::from_connection(connection),
+        );
+        assert!(context.is_public_disabled());
+
+        let public = [
+            r#"{"method":"orand_newPublicEpoch","params":["1"]}"#,
+            r#"{"method":"orand_getPublicEpoch","params":["1","7"]}"#,
+            r#"{"method":"orand_verifyPublicEpoch","params":["1","7"]}"#,
+        ];
+        for json in public {
+            let method = JSONRPCMethod::from_json_string(json).expect("Unable to decode method");
+            let err = context
+                .check_public(method.is_public())
+                .expect_err("Public method must be rejected");
+            assert_eq!(err.code(), ErrorCode::NotImplemented);
+        }
+
+        let private = [
+            r#"{"method":"orand_newPrivateEpoch","params":["1","0x1111111111111111111111111111111111111111"]}"#,
+            r#"{"method":"orand_getPrivateEpoch","params":["1","0x1111111111111111111111111111111111111111","7"]}"#,
+            r#"{"method":"orand_getNetworks","params":[]}"#,
+        ];
+        for json in private {
+            let method = JSONRPCMethod::from_json_string(json).expect("Unable to decode method");
+            assert!(!method.is_public());
+            assert_eq!(context.check_public(method.is_public()), Ok(()));
+        }
+    }
+
+    #[test]
+    fn auto_register_policy_should_decide_unknown_receiver() {
+        let cases = [
+            (AutoRegister::Never, false, false),
+            (AutoRegister::Never, true, false),
+            (AutoRegister::OnTestnet, false, false),
+            (AutoRegister::OnTestnet, true, true),
+            (AutoRegister::Always, false, true),
+            (AutoRegister::Always, true, true),
+        ];
+        for (policy, is_testnet, expected) in cases {
+            assert_eq!(policy.allows(is_testnet), expected);
+        }
+        assert_eq!(AutoRegister::default(), AutoRegister::OnTestnet);
+    }
+
+    #[test]
+    fn auto_register_policy_should_parse_from_string() {
+        assert_eq!(AutoRegister::try_from("never"), Ok(AutoRegister::Never));
+        assert_eq!(
+            AutoRegister::try_from(" On_Testnet "),
+            Ok(AutoRegister::OnTestnet)
+        );
+        assert_eq!(AutoRegister::try_from("always"), Ok(AutoRegister::Always));
+        assert!(AutoRegister::try_from("sometimes").is_err());
+    }
+
+    #[test]
+    fn vrf_self_test_should_pass_with_valid_key() {
+        assert!(vrf_self_test(&KeyPair::new()).is_ok());
+    }
+
+    #[test]
+    fn vrf_self_test_should_fail_with_corrupted_key() {
+        let keypair = KeyPair::new();
+        let corrupted = KeyPair {
+            public_key: KeyPair::new().public_key,
+            secret_key: keypair.secret_key,
+        };
+        assert!(vrf_self_test(&corrupted).is_err());
+    }
+
+    #[test]
+    fn context_vrf_should_prove_and_verify() {
+        fn shareable<T: Send + Sync>(_: &T) {}
+        let keypair = KeyPair::new();
+        let public_key = keypair.public_key;
+        let connection = MockDatabase::new(DatabaseBackend::Postgres).into_connection();
+        let context = NodeContext::new(
+            1,
+            keypair,
+            NodeOptions {
+                auto_register: AutoRegister::Never,
+                networks: vec![1],
+                ..Default::default()
+            },
+            Postgres::from_connection(connection),
+        );
+
+        // The instance is built once, every call shares it
+        let vrf = context.vrf();
+        shareable(&vrf);
+        assert!(Arc::ptr_eq(&vrf, &context.vrf()));
+
+        let alpha = Scalar::randomize();
+        let proof = vrf.prove(&alpha).expect("Unable to prove");
+        assert_eq!(proof.pk, public_key);
+        assert!(vrf.verify(&alpha, &proof));
+        assert!(ECVRF::new_verifier(public_key).verify(&alpha, &proof));
+        let contract_proof = vrf.prove_contract(&alpha).expect("Unable to prove");
+        assert!(ECVRF::new_verifier(public_key).verify_contract(&contract_proof));
+    }
+
+    #[tokio::test]
+    async fn spawn_prove_should_let_other_tasks_progress() {
+        let keypair = KeyPair::new();
+        let vrf = Arc::new(ECVRF::new(keypair.secret_key));
+        let verifier = ECVRF::new_verifier(keypair.public_key);
+
+        // Single threaded runtime, the ticker only runs while proving yields
+        let ticks = Arc::new(AtomicUsize::new(0));
+        let ticker = {
+            let ticks = Arc::clone(&ticks);
+            tokio::spawn(async move {
+                loop {
+                    ticks.fetch_add(1, Ordering::Relaxed);
+                    tokio::task::yield_now().await;
+                }
+            })
+        };
+        for _ in 0..4 {
+            let contract_proof = spawn_prove(Arc::clone(&vrf), Scalar::randomize())
+                .await
+                .expect("Unable to prove");
+            assert!(verifier.verify_contract(&contract_proof));
+        }
+        ticker.abort();
+        assert!(ticks.load(Ordering::Relaxed) > 0);
+    }
+}
@@ -1,5 +1,6 @@
 //! `SeaORM` Entity. Generated by sea-orm-codegen 0.12.11
 
+pub mod key_rotation;
 pub mod keyring;
 
 /// PostgresSQL
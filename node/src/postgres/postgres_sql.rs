@@ -1,6 +1,10 @@
-use sea_orm::{Database, DatabaseConnection};
+use migration::{Migrator, MigratorTrait};
+use sea_orm::{ConnectOptions, ConnectionTrait, Database, DatabaseConnection, DbErr};
 
-use super::table::{KeyringTable, RandomnessTable, ReceiverTable};
+use super::table::{KeyRotationTable, KeyringTable, RandomnessTable, ReceiverTable};
+
+/// Database urls that open an ephemeral in-memory SQLite database
+const IN_MEMORY_URLS: [&str; 2] = [":memory:", "sqlite::memory:"];
 
 /// SQL Lite Database
 pub struct Postgres {
@@ -8,8 +12,14 @@ pub struct Postgres {
 }
 
 impl Postgres {
-    /// Create a new instance of SQLite database
+    /// Create a new instance of SQLite database, `:memory:` or `sqlite::memory:`
+    /// opens an in-memory SQLite database, see [Postgres::new_in_memory]
     pub async fn new(database_url: String) -> Self {
+        if IN_MEMORY_URLS.contains(&database_url.as_str()) {
+            return Self::new_in_memory()
+                .await
+                .expect("Can not create in-memory database");
+        }
         Self {
             connection: Database::connect(database_url)
                 .await
@@ -17,6 +27,36 @@ impl Postgres {
         }
     }
 
+    /// Create an ephemeral in-memory SQLite database with all migrations applied,
+    /// it's meant for tests that need the full node stack without a database server
+    pub async fn new_in_memory() -> Result<Self, DbErr> {
+        // Each SQLite connection to `:memory:` opens its own empty database,
+        // a single connection keeps the same database for the lifetime of the pool
+        let mut options = ConnectOptions::new(IN_MEMORY_URLS[1]);
+        options.max_connections(1).min_connections(1);
+        let connection = Database::connect(options).await?;
+        Migrator::up(&connection, None).await?;
+        Ok(Self { connection })
+    }
+
+    /// Create a new instance from an established connection
+    pub fn from_connection(connection: DatabaseConnection) -> Self {
+        Self { connection }
+    }
+
+    /// Check the database connection with a cheap `SELECT 1`
+    pub async fn ping(&self) -> Result<(), DbErr> {
+        self.connection.execute_unprepared("SELECT 1").await?;
+        Ok(())
+    }
+
+    /// Check that every known migration has been applied to the database
+    pub async fn migrations_current(&self) -> Result<bool, DbErr> {
+        Ok(Migrator::get_pending_migrations(&self.connection)
+            .await?
+            .is_empty())
+    }
+
     /// Get table receiver
     pub fn table_receiver(&self) -> ReceiverTable<'_> {
         ReceiverTable::new(&self.connection)
@@ -31,4 +71,9 @@ impl Postgres {
     pub fn table_keyring(&self) -> KeyringTable<'_> {
         KeyringTable::new(&self.connection)
     }
+
+    /// Get table key rotation
+    pub fn table_key_rotation(&self) -> KeyRotationTable<'_> {
+        KeyRotationTable::new(&self.connection)
+    }
 }
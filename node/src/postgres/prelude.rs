@@ -1,5 +1,6 @@
 //! `SeaORM` Entity. Generated by sea-orm-codegen 0.12.11
 
+pub use super::key_rotation::Entity as KeyRotation;
 pub use super::keyring::Entity as Keyring;
 pub use super::randomness::Entity as Randomness;
 pub use super::receiver::Entity as Receiver;
@@ -42,6 +42,14 @@ pub struct Model {
     /// Signature proof
     #[sea_orm(unique)]
     pub signature_proof: String,
+    /// Client supplied request id, unique per receiver
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub request_id: Option<String>,
+    /// ECVRF ciphersuite that produced the proof
+    pub ciphersuite: String,
+    /// Compact proof, the proof columns are left empty when it's set
+    #[serde(skip)]
+    pub proof: Option<Vec<u8>>,
     /// Created date
     #[serde(skip_deserializing)]
     pub created_date: DateTime,
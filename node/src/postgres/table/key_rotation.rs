@@ -0,0 +1,111 @@
+use crate::key_rotation::{ActiveModel, Column, Entity, Model};
+use crate::{keyring, randomness};
+use libecvrf::{secp256k1::PublicKey, KeyPair, RawKeyPair, Zeroable};
+use sea_orm::{
+    ActiveModelTrait, ActiveValue, ColumnTrait, DatabaseConnection, DbErr, EntityTrait,
+    QueryFilter, QueryOrder, TransactionTrait,
+};
+use serde_json::json;
+use zeroize::Zeroizing;
+
+/// Public keys that an operator used over time
+#[derive(Debug, Clone)]
+pub struct KeyHistory {
+    current: PublicKey,
+    // (last randomness Id, retired key) in the order of rotations
+    retired: Vec<(i64, PublicKey)>,
+}
+
+impl KeyHistory {
+    /// Public key that is active now
+    pub fn current(&self) -> PublicKey {
+        self.current
+    }
+
+    /// Public key that proved the given randomness record, the earliest rotation that
+    /// happened after the record retired its key
+    pub fn public_key_for(&self, randomness_id: i64) -> PublicKey {
+        self.retired
+            .iter()
+            .find(|(last_randomness_id, _)| randomness_id <= *last_randomness_id)
+            .map(|(_, public_key)| *public_key)
+            .unwrap_or(self.current)
+    }
+}
+
+/// Key rotation table
+pub struct KeyRotationTable<'a> {
+    connection: &'a DatabaseConnection,
+}
+
+impl<'a> KeyRotationTable<'a> {
+    /// Create new instance of key rotation table
+    pub fn new(connection: &'a DatabaseConnection) -> Self {
+        Self { connection }
+    }
+
+    /// Find rotations of a key in the order they happened
+    pub async fn find_by_keyring(&self, keyring_id: i64) -> Result<Vec<Model>, DbErr> {
+        Entity::find()
+            .filter(Column::KeyringId.eq(keyring_id))
+            .order_by_asc(Column::Id)
+            .all(self.connection)
+            .await
+    }
+
+    /// Replace the key of a keyring record with the given keypair and record the
+    /// rotation, the raw keypair is wiped before the records are written
+    pub async fn rotate(&self, keyring_id: i64, keypair: &KeyPair) -> Result<Model, DbErr> {
+        let txn = self.connection.begin().await?;
+        let keyring_record = match keyring::Entity::find_by_id(keyring_id).one(&txn).await? {
+            Some(record) => record,
+            None => return Err(DbErr::RecordNotFound("Keyring was not found".to_string())),
+        };
+        // Every randomness record up to this one was proven with the old key
+        let last_randomness_id = randomness::Entity::find()
+            .filter(randomness::Column::KeyringId.eq(keyring_id))
+            .order_by_desc(randomness::Column::Id)
+            .one(&txn)
+            .await?
+            .map(|record| record.id)
+            .unwrap_or(0);
+
+        let mut raw_keypair = RawKeyPair::from(keypair);
+        let new_public_key = hex::encode(raw_keypair.public_key);
+        let new_secret_key = Zeroizing::new(hex::encode(raw_keypair.secret_key));
+        // Wipe raw keypair from memory before anything is able to fail
+        raw_keypair.zeroize();
+        let rotation = Entity::insert(ActiveModel::from_json(json!({
+            "keyring_id": keyring_id,
+            "old_public_key": keyring_record.public_key,
+            "new_public_key": new_public_key,
+            "last_randomness_id": last_randomness_id,
+        }))?)
+        .exec_with_returning(&txn)
+        .await?;
+
+        let mut keyring_active_model = keyring::ActiveModel::from(keyring_record);
+        keyring_active_model.public_key = ActiveValue::Set(new_public_key);
+        keyring_active_model.secret_key = ActiveValue::Set(new_secret_key.to_string());
+        keyring_active_model.update(&txn).await?;
+        txn.commit().await?;
+        Ok(rotation)
+    }
+
+    /// Key history of a keyring record, `current` is the key that is active now
+    pub async fn history(&self, keyring_id: i64, current: PublicKey) -> Result<KeyHistory, DbErr> {
+        let retired = self
+            .find_by_keyring(keyring_id)
+            .await?
+            .into_iter()
+            .map(|rotation| {
+                let public_key = hex::decode(&rotation.old_public_key)
+                    .ok()
+                    .and_then(|bytes| PublicKey::parse_slice(&bytes, None).ok())
+                    .ok_or(DbErr::Custom("Stored public key is malformed".to_string()))?;
+                Ok((rotation.last_randomness_id, public_key))
+            })
+            .collect::<Result<Vec<(i64, PublicKey)>, DbErr>>()?;
+        Ok(KeyHistory { current, retired })
+    }
+}
@@ -1,5 +1,10 @@
 use crate::keyring::{ActiveModel, Column, Entity, Model};
+use libecvrf::{helper::random_bytes, secp256k1::SecretKey, KeyPair, RawKeyPair, Zeroable};
 use sea_orm::{ActiveModelTrait, ColumnTrait, DatabaseConnection, DbErr, EntityTrait, QueryFilter};
+use serde_json::json;
+
+/// Size of the HMAC secret that is used to sign JWT of a user
+const HMAC_SECRET_SIZE: usize = 32;
 
 /// Keyring table
 pub struct KeyringTable<'a> {
@@ -38,4 +43,30 @@ impl<'a> KeyringTable<'a> {
             .exec_with_returning(self.connection)
             .await
     }
+
+    /// Create a new user with a random HMAC secret, the keypair is derived from the
+    /// given secret key or generated, the raw keypair is wiped once it's inserted
+    pub async fn bootstrap_user(
+        &self,
+        username: String,
+        secret_key: Option<SecretKey>,
+    ) -> Result<Model, DbErr> {
+        let mut hmac_secret = [0u8; HMAC_SECRET_SIZE];
+        random_bytes(&mut hmac_secret);
+        let keypair = match secret_key {
+            Some(secret_key) => KeyPair::from(secret_key),
+            None => KeyPair::new(),
+        };
+        let mut raw_keypair = RawKeyPair::from(&keypair);
+        let result = self
+            .insert(json!({
+                "username": username,
+                "hmac_secret": hex::encode(hmac_secret),
+                "public_key": hex::encode(raw_keypair.public_key),
+                "secret_key": hex::encode(raw_keypair.secret_key)}))
+            .await;
+        // Wipe raw keypair from memory
+        raw_keypair.zeroize();
+        result
+    }
 }
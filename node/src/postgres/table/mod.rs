@@ -1,6 +1,11 @@
+mod key_rotation;
 mod keyring;
 mod randomness;
 mod receiver;
+pub use key_rotation::{KeyHistory, KeyRotationTable};
 pub use keyring::KeyringTable;
-pub use randomness::RandomnessTable;
-pub use receiver::ReceiverTable;
+pub use randomness::{
+    is_unique_violation, RandomnessTable, CHAIN_PAGE_SIZE, DEFAULT_RECENT_EPOCHS, MAX_RANGE_EPOCHS,
+    MAX_RECENT_EPOCHS,
+};
+pub use receiver::{ReceiverStats, ReceiverTable};
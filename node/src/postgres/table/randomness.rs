@@ -1,23 +1,41 @@
 use std::sync::Arc;
 
 use crate::{
-    ethereum::{compose_operator_proof, ecvrf_proof_digest, sign_ethereum_message},
+    epoch::{
+        decode_contract_proof, expand_proof, genesis_alpha, ChainEpoch, Ciphersuite, ProofFormat,
+    },
+    ethereum::{compose_operator_proof, ecvrf_proof_digest, sign_ethereum_message, EthAddress},
     evm::evm_verify,
     keyring,
+    merkle::{merkle_path, merkle_root},
     randomness::{ActiveModel, Column, Entity, Model},
-    receiver, NodeContext,
+    receiver, spawn_prove, NodeContext,
 };
+use bytes::Bytes;
 use libecvrf::{
     extends::{AffineExtend, ScalarExtend},
     secp256k1::curve::Scalar,
 };
 use sea_orm::{
     sea_query::Query, ActiveModelTrait, ActiveValue, ColumnTrait, Condition, DatabaseConnection,
-    DbErr, EntityTrait, Order, QueryFilter, QueryOrder, QuerySelect, TransactionTrait,
+    DbErr, EntityTrait, Order, QueryFilter, QueryOrder, QuerySelect, SqlErr, TransactionTrait,
 };
 use serde_json::json;
+use tokio::sync::mpsc::{self, Receiver};
 
-use super::ReceiverTable;
+use super::{receiver::network_to_db, KeyHistory, ReceiverTable};
+
+/// Number of recent epochs returned when the client doesn't ask for a count
+pub const DEFAULT_RECENT_EPOCHS: u64 = 20;
+
+/// Largest number of recent epochs that could be returned at once
+pub const MAX_RECENT_EPOCHS: u64 = 50;
+
+/// Number of epochs that are read at once when a chain is exported
+pub const CHAIN_PAGE_SIZE: u64 = 100;
+
+/// Largest number of epochs that could be committed to at once
+pub const MAX_RANGE_EPOCHS: i64 = 1024;
 
 /// Randomness table
 pub struct RandomnessTable<'a> {
@@ -32,43 +50,52 @@ impl<'a> RandomnessTable<'a> {
         Self { connection }
     }
 
-    /// Find randomness record by its network and address
+    /// Find the most recent randomness records by its network and address,
+    /// count is clamped to the range 1..=[MAX_RECENT_EPOCHS]
     pub async fn find_recent_epoch(
         &self,
-        network: i64,
-        address: &str,
+        network: u64,
+        address: &EthAddress,
+        count: u64,
     ) -> Result<Vec<Model>, DbErr> {
         let receiver = ReceiverTable::new(self.connection)
             .find_one(network, address)
-            .await
-            .expect("Unable to query receiver from database");
+            .await?;
         match receiver {
-            Some(receiver_record) => {
-                Entity::find()
-                    .filter(Condition::all().add(Column::ReceiverId.eq(receiver_record.id)))
-                    // 20 is the limit of number of records
-                    .limit(20)
-                    .order_by(Column::Epoch, Order::Desc)
-                    .all(self.connection)
-                    .await
-            }
+            Some(receiver_record) => Entity::find()
+                .filter(Condition::all().add(Column::ReceiverId.eq(receiver_record.id)))
+                .limit(count.clamp(1, MAX_RECENT_EPOCHS))
+                .order_by(Column::Epoch, Order::Desc)
+                .all(self.connection)
+                .await?
+                .into_iter()
+                .map(expand)
+                .collect(),
             None => Ok(vec![]),
         }
     }
 
-    /// Find randomness record by its network and address
+    /// Find up to 20 randomness records ending at the given epoch (inclusive)
+    /// The given epoch must exist, a negative epoch or an epoch beyond the latest
+    /// one is rejected instead of falling back to the nearest records
     pub async fn find_closure_epoch(
         &self,
-        network: i64,
-        address: &str,
+        network: u64,
+        address: &EthAddress,
         epoch: i64,
     ) -> Result<Vec<Model>, DbErr> {
         let receiver = ReceiverTable::new(self.connection)
             .find_one(network, address)
-            .await
-            .expect("Unable to query receiver from database");
+            .await?;
         match receiver {
             Some(receiver_record) => {
+                let latest_epoch = Entity::find()
+                    .filter(Column::ReceiverId.eq(receiver_record.id))
+                    .order_by(Column::Epoch, Order::Desc)
+                    .one(self.connection)
+                    .await?
+                    .map(|record| record.epoch);
+                check_closure_epoch(epoch, latest_epoch)?;
                 Entity::find()
                     .filter(
                         Condition::all()
@@ -79,7 +106,10 @@ impl<'a> RandomnessTable<'a> {
                     .limit(20)
                     .order_by(Column::Epoch, Order::Desc)
                     .all(self.connection)
-                    .await
+                    .await?
+                    .into_iter()
+                    .map(expand)
+                    .collect()
             }
             None => Ok(vec![]),
         }
@@ -88,21 +118,20 @@ impl<'a> RandomnessTable<'a> {
     /// Find randomness record by its network and address
     pub async fn find_latest_epoch(
         &self,
-        network: i64,
-        address: &str,
+        network: u64,
+        address: &EthAddress,
     ) -> Result<Option<Model>, DbErr> {
         let receiver = ReceiverTable::new(self.connection)
             .find_one(network, address)
-            .await
-            .expect("Unable to query receiver from database");
+            .await?;
         match receiver {
-            Some(receiver_record) => {
-                Entity::find()
-                    .filter(Column::ReceiverId.eq(receiver_record.id))
-                    .order_by(Column::Epoch, Order::Desc)
-                    .one(self.connection)
-                    .await
-            }
+            Some(receiver_record) => Entity::find()
+                .filter(Column::ReceiverId.eq(receiver_record.id))
+                .order_by(Column::Epoch, Order::Desc)
+                .one(self.connection)
+                .await?
+                .map(expand)
+                .transpose(),
             None => Ok(None),
         }
     }
@@ -110,48 +139,203 @@ impl<'a> RandomnessTable<'a> {
     /// Find randomness record by its network, address and epoch_id
     pub async fn find_given_epoch(
         &self,
-        network: i64,
-        address: &str,
+        network: u64,
+        address: &EthAddress,
         epoch_id: i64,
     ) -> Result<Option<Model>, DbErr> {
         let receiver = ReceiverTable::new(self.connection)
             .find_one(network, address)
-            .await
-            .expect("Unable to query receiver from database");
+            .await?;
         match receiver {
-            Some(receiver_record) => {
-                Entity::find()
+            Some(receiver_record) => Entity::find()
+                .filter(
+                    Condition::all()
+                        .add(Column::ReceiverId.eq(receiver_record.id))
+                        .add(Column::Epoch.eq(epoch_id)),
+                )
+                .order_by(Column::Epoch, Order::Desc)
+                .one(self.connection)
+                .await?
+                .map(expand)
+                .transpose(),
+            None => Ok(None),
+        }
+    }
+
+    /// Results of the epochs `from..=to` of a receiver in order, every epoch of the
+    /// range must exist
+    async fn find_range_results(
+        &self,
+        network: u64,
+        address: &EthAddress,
+        from: i64,
+        to: i64,
+    ) -> Result<Vec<[u8; 32]>, DbErr> {
+        if from < 0 || to < from {
+            return Err(DbErr::Custom("Invalid epoch range".to_string()));
+        }
+        if to - from >= MAX_RANGE_EPOCHS {
+            return Err(DbErr::Custom("Epoch range is too large".to_string()));
+        }
+        let receiver_record = match ReceiverTable::new(self.connection)
+            .find_one(network, address)
+            .await?
+        {
+            Some(receiver_record) => receiver_record,
+            None => return Err(DbErr::RecordNotFound("Receiver was not found".to_string())),
+        };
+        let records = Entity::find()
+            .filter(
+                Condition::all()
+                    .add(Column::ReceiverId.eq(receiver_record.id))
+                    .add(Column::Epoch.between(from, to)),
+            )
+            .order_by(Column::Epoch, Order::Asc)
+            .all(self.connection)
+            .await?;
+        if records.len() as i64 != to - from + 1 {
+            return Err(DbErr::RecordNotFound(
+                "Epoch range is beyond the latest epoch".to_string(),
+            ));
+        }
+        records
+            .iter()
+            .map(|record| {
+                let mut y = [0u8; 32];
+                hex::decode_to_slice(&record.y, &mut y)
+                    .map_err(|_| DbErr::Custom("Stored result is malformed".to_string()))?;
+                Ok(y)
+            })
+            .collect()
+    }
+
+    /// Commitment to the results of the epochs `from..=to` of a receiver, it's the
+    /// keccak256 Merkle root of their y in order, see [merkle_root]
+    pub async fn range_commitment(
+        &self,
+        network: u64,
+        address: &EthAddress,
+        from: i64,
+        to: i64,
+    ) -> Result<[u8; 32], DbErr> {
+        let ys = self.find_range_results(network, address, from, to).await?;
+        merkle_root(&ys).ok_or(DbErr::Custom("Invalid epoch range".to_string()))
+    }
+
+    /// Result y of `epoch` and its Merkle path against the commitment to the epochs
+    /// `from..=to`, see [RandomnessTable::range_commitment] and
+    /// [verify_membership](crate::merkle::verify_membership)
+    pub async fn range_membership_proof(
+        &self,
+        network: u64,
+        address: &EthAddress,
+        from: i64,
+        to: i64,
+        epoch: i64,
+    ) -> Result<([u8; 32], Vec<[u8; 32]>), DbErr> {
+        if epoch < from || epoch > to {
+            return Err(DbErr::Custom("Epoch is out of range".to_string()));
+        }
+        let ys = self.find_range_results(network, address, from, to).await?;
+        let index = (epoch - from) as usize;
+        let path =
+            merkle_path(&ys, index).ok_or(DbErr::Custom("Epoch is out of range".to_string()))?;
+        Ok((ys[index], path))
+    }
+
+    /// Export the whole epoch chain of a receiver as JSON chunks of a [ChainBundle],
+    /// epochs are read page by page so the chain is never buffered as a whole.
+    /// Epochs proven with a retired key carry that key, see [KeyHistory::public_key_for].
+    /// The stream ends early if the database fails, the JSON is left unterminated
+    pub fn export_chain(&self, history: KeyHistory, receiver_id: i64) -> Receiver<Bytes> {
+        let (sender, receiver) = mpsc::channel(4);
+        let connection = self.connection.clone();
+        let header = format!(
+            "{{\"public_key\":\"{}\",\"epochs\":[",
+            hex::encode(history.current().serialize())
+        );
+        tokio::spawn(async move {
+            if sender.send(Bytes::from(header)).await.is_err() {
+                return;
+            }
+            let mut next_epoch = 0;
+            let mut separator = "";
+            loop {
+                let page = match Entity::find()
                     .filter(
                         Condition::all()
-                            .add(Column::ReceiverId.eq(receiver_record.id))
-                            .add(Column::Epoch.eq(epoch_id)),
+                            .add(Column::ReceiverId.eq(receiver_id))
+                            .add(Column::Epoch.gte(next_epoch)),
                     )
-                    .order_by(Column::Epoch, Order::Desc)
-                    .one(self.connection)
+                    .order_by(Column::Epoch, Order::Asc)
+                    .limit(CHAIN_PAGE_SIZE)
+                    .all(&connection)
                     .await
+                    .and_then(|records| {
+                        records
+                            .into_iter()
+                            .map(expand)
+                            .collect::<Result<Vec<_>, _>>()
+                    }) {
+                    Ok(page) => page,
+                    Err(err) => {
+                        log::error!("Unable to export epoch chain {}", err);
+                        return;
+                    }
+                };
+                let last = match page.last() {
+                    Some(last) => last.epoch,
+                    None => break,
+                };
+                let mut chunk = String::from(separator);
+                for record in page.iter() {
+                    if chunk.len() > separator.len() {
+                        chunk.push(',');
+                    }
+                    let mut chain_epoch = ChainEpoch::from(record);
+                    let public_key = history.public_key_for(record.id);
+                    if public_key != history.current() {
+                        chain_epoch.public_key = Some(hex::encode(public_key.serialize()));
+                    }
+                    chunk.push_str(
+                        &serde_json::to_string(&chain_epoch)
+                            .expect("Chain epoch must be serializable"),
+                    );
+                }
+                if sender.send(Bytes::from(chunk)).await.is_err() {
+                    // Client is gone
+                    return;
+                }
+                if (page.len() as u64) < CHAIN_PAGE_SIZE {
+                    break;
+                }
+                next_epoch = last + 1;
+                separator = ",";
             }
-            None => Ok(None),
-        }
+            let _ = sender.send(Bytes::from_static(b"]}")).await;
+        });
+        receiver
     }
 
-    /// Find randomness record by its network and address
+    /// Create a new epoch for the given receiver, if `request_id` was already used
+    /// for this receiver the previously produced epoch is returned instead
     pub async fn safe_insert(
         &self,
-        context: Arc<NodeContext<'_>>,
+        context: Arc<NodeContext<'static>>,
         username: String,
-        network: i64,
-        address: String,
+        network: u64,
+        address: EthAddress,
+        request_id: Option<String>,
     ) -> Result<Model, DbErr> {
         let _lock = context.sync.lock().await;
-        let ecvrf = context.ecvrf();
         let txn = self.connection.begin().await?;
 
         // Lookup the receiver record by address and network from database
         let receiver_record = match receiver::Entity::find()
             .filter(
                 Condition::all()
-                    .add(receiver::Column::Address.eq(address.to_owned()))
-                    .add(receiver::Column::Network.eq(network))
+                    .add(receiver::Column::Address.eq(address.to_string()))
+                    .add(receiver::Column::Network.eq(network_to_db(network)?))
                     .add(
                         receiver::Column::KeyringId.in_subquery(
                             Query::select()
@@ -176,6 +360,24 @@ impl<'a> RandomnessTable<'a> {
             },
             Err(e) => return Err(e),
         };
+        // Retried request, return the epoch that was produced for it
+        if let Some(request_id) = request_id.as_ref() {
+            if let Some(previous_epoch) = Entity::find()
+                .filter(
+                    Condition::all()
+                        .add(Column::ReceiverId.eq(receiver_record.id))
+                        .add(Column::RequestId.eq(request_id.to_owned())),
+                )
+                .one(&txn)
+                .await?
+            {
+                txn.commit().await?;
+                return expand(previous_epoch);
+            }
+        }
+
+        // Epoch of this request is the receiver nonce, fail before proving if there is no next one
+        let next_nonce = next_epoch(receiver_record.nonce)?;
 
         // Read alpha from latest epoch
         let alpha = match Entity::find()
@@ -186,12 +388,18 @@ impl<'a> RandomnessTable<'a> {
         {
             Ok(randomness_exec_result) => match randomness_exec_result {
                 Some(latest_epoch) => {
+                    if latest_epoch.epoch < 0 {
+                        log::error!("Latest epoch of the receiver is negative");
+                        return Err(DbErr::Custom("Epoch must not be negative".to_string()));
+                    }
                     let mut buf = [0u8; 32];
                     hex::decode_to_slice(latest_epoch.y, &mut buf)
                         .expect("Unable to decode previous result");
 
                     Scalar::from_bytes(&buf)
                 }
+                // Genesis of the public chain is derived from the operator key
+                None if address.is_zero() => genesis_alpha(&context.keypair().public_key, network),
                 None => Scalar::randomize(),
             },
             Err(e) => {
@@ -200,7 +408,7 @@ impl<'a> RandomnessTable<'a> {
             }
         };
 
-        let contract_proof = match ecvrf.prove_contract(&alpha) {
+        let contract_proof = match spawn_prove(context.vrf(), alpha).await {
             Ok(r) => r,
             Err(_) => {
                 log::error!("ECVRF can not generate proof");
@@ -218,36 +426,34 @@ impl<'a> RandomnessTable<'a> {
         }
 
         let receiver_nonce = receiver_record.nonce;
-        let mut bytes_address = [0u8; 20];
-        hex::decode_to_slice(
-            address.replace("0x", "").replace("0X", ""),
-            &mut bytes_address,
-        )
-        .expect("Unable to decode address");
-
+        // Proof columns are left empty in compact format, the blob carries them
+        let compact = context.proof_format() == ProofFormat::Compact;
+        let proof_column = |value: String| if compact { String::new() } else { value };
         let raw_proof = compose_operator_proof(
             receiver_nonce,
-            &bytes_address,
+            &address,
             &ecvrf_proof_digest(&contract_proof),
         );
         let ecdsa_proof = sign_ethereum_message(&context.keypair().secret_key, &raw_proof);
 
         // Construct active model from JSON
-        let new_randomness_record = match ActiveModel::from_json(json!({
+        let mut new_randomness_record = match ActiveModel::from_json(json!({
             "keyring_id": context.key_id(),
             "receiver_id": receiver_record.id,
             "epoch": receiver_record.nonce,
             "alpha":hex::encode(alpha
                 .b32()),
-            "gamma": contract_proof.gamma.to_hex_string(),
-            "c":hex::encode(contract_proof.c.b32()),
-            "s":hex::encode(contract_proof.s.b32()),
+            "gamma": proof_column(contract_proof.gamma.to_hex_string()),
+            "c": proof_column(hex::encode(contract_proof.c.b32())),
+            "s": proof_column(hex::encode(contract_proof.s.b32())),
             "y":hex::encode(contract_proof.y.b32()),
-            "witness_address": hex::encode(contract_proof.witness_address.b32())[0..40],
-            "witness_gamma": contract_proof.witness_gamma.to_hex_string(),
-            "witness_hash": contract_proof.witness_hash.to_hex_string(),
-            "inverse_z": hex::encode(contract_proof.inverse_z.b32()),
+            "witness_address": proof_column(hex::encode(contract_proof.witness_address.to_eth_address())),
+            "witness_gamma": proof_column(contract_proof.witness_gamma.to_hex_string()),
+            "witness_hash": proof_column(contract_proof.witness_hash.to_hex_string()),
+            "inverse_z": proof_column(hex::encode(contract_proof.inverse_z.b32())),
             "signature_proof": hex::encode(&ecdsa_proof),
+            "request_id": request_id,
+            "ciphersuite": Ciphersuite::default().as_str(),
         })) {
             Ok(rr) => rr,
             Err(e) => {
@@ -255,9 +461,12 @@ impl<'a> RandomnessTable<'a> {
                 return Err(e);
             }
         };
+        if compact {
+            new_randomness_record.proof = ActiveValue::Set(Some(contract_proof.to_bytes()));
+        }
 
         let mut receiver_active_model = receiver::ActiveModel::from(receiver_record);
-        receiver_active_model.nonce = ActiveValue::Set(receiver_nonce + 1);
+        receiver_active_model.nonce = ActiveValue::Set(next_nonce);
         // Update database receiver record
         receiver_active_model.save(&txn).await?;
 
@@ -266,7 +475,7 @@ impl<'a> RandomnessTable<'a> {
             .await;
 
         match txn.commit().await {
-            Ok(_) => result,
+            Ok(_) => result.and_then(expand),
             Err(e) => {
                 log::error!("Can not finalize transaction");
                 Err(e)
@@ -274,8 +483,274 @@ impl<'a> RandomnessTable<'a> {
         }
     }
 
+    /// Re-sign the operator proof of an epoch with a corrected receiver nonce,
+    /// the ECVRF proof is kept as is, only the signature proof is replaced
+    pub async fn resign_epoch(
+        &self,
+        context: Arc<NodeContext<'_>>,
+        network: u64,
+        address: &EthAddress,
+        epoch: i64,
+        nonce: i64,
+    ) -> Result<Model, DbErr> {
+        if nonce < 0 {
+            return Err(DbErr::Custom("Nonce must not be negative".to_string()));
+        }
+        let _lock = context.sync.lock().await;
+        let record = match self.find_given_epoch(network, address, epoch).await? {
+            Some(record) => record,
+            None => return Err(DbErr::RecordNotFound("Epoch was not found".to_string())),
+        };
+        if record.keyring_id != context.key_id() {
+            return Err(DbErr::Custom(
+                "Epoch was not produced by the operator key".to_string(),
+            ));
+        }
+        // Rotation keeps the keyring, an epoch proven with a retired key can't be re-signed
+        let history = context.key_history().await?;
+        if history.public_key_for(record.id) != history.current() {
+            return Err(DbErr::Custom(
+                "Epoch was proven with a retired operator key".to_string(),
+            ));
+        }
+
+        let contract_proof = decode_contract_proof(&history.current(), &record)
+            .map_err(|e| DbErr::Custom(e.reason().to_string()))?;
+        let raw_proof =
+            compose_operator_proof(nonce, address, &ecvrf_proof_digest(&contract_proof));
+        let ecdsa_proof = sign_ethereum_message(&context.keypair().secret_key, &raw_proof);
+
+        let mut active_model = ActiveModel::from(record);
+        active_model.signature_proof = ActiveValue::Set(hex::encode(&ecdsa_proof));
+        active_model.update(self.connection).await
+    }
+
+    /// Insert data to randomness table, a duplicated epoch of the same receiver
+    /// is rejected, see [is_unique_violation]
+    pub async fn insert(&self, json_record: serde_json::Value) -> Result<Model, DbErr> {
+        let new_record = ActiveModel::from_json(json_record)?;
+        Entity::insert(new_record)
+            .exec_with_returning(self.connection)
+            .await
+    }
+
     /// Find randomness record by its network and address
     pub async fn update(&self, active_model: ActiveModel) -> Result<Model, DbErr> {
         active_model.update(self.connection).await
     }
 }
+
+/// Decode compactly stored proofs, callers always see epochs in full format
+fn expand(record: Model) -> Result<Model, DbErr> {
+    expand_proof(record).map_err(|e| DbErr::Custom(e.reason().to_string()))
+}
+
+/// Check if the error is a unique constraint violation, e.g. the epoch of
+/// the receiver or the request id was already inserted
+pub fn is_unique_violation(err: &DbErr) -> bool {
+    matches!(err.sql_err(), Some(SqlErr::UniqueConstraintViolation(_)))
+}
+
+/// Epoch that follows `epoch`, a negative epoch or one that would overflow is
+/// rejected instead of wrapping around
+fn next_epoch(epoch: i64) -> Result<i64, DbErr> {
+    if epoch < 0 {
+        return Err(DbErr::Custom("Epoch must not be negative".to_string()));
+    }
+    epoch
+        .checked_add(1)
+        .ok_or(DbErr::Custom("Epoch is out of range".to_string()))
+}
+
+/// Make sure the requested epoch lies within `[0, latest_epoch]`
+fn check_closure_epoch(epoch: i64, latest_epoch: Option<i64>) -> Result<(), DbErr> {
+    if epoch < 0 {
+        return Err(DbErr::Custom("Epoch must not be negative".to_string()));
+    }
+    match latest_epoch {
+        Some(latest) if epoch <= latest => Ok(()),
+        _ => Err(DbErr::RecordNotFound(
+            "Requested epoch is beyond the latest epoch".to_string(),
+        )),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{check_closure_epoch, next_epoch};
+    use crate::{
+        postgres_sql::Postgres, randomness, receiver, AutoRegister, NodeContext, NodeOptions,
+    };
+    use libecvrf::KeyPair;
+    use sea_orm::{prelude::DateTime, DatabaseBackend, DbErr, MockDatabase};
+    use std::sync::Arc;
+
+    const RECEIVER_ADDRESS: &str = "0x1111111111111111111111111111111111111111";
+
+    fn receiver_record(nonce: i64) -> receiver::Model {
+        receiver::Model {
+            id: 1,
+            keyring_id: 1,
+            name: "receiver".to_string(),
+            address: RECEIVER_ADDRESS.to_string(),
+            network: 1,
+            nonce,
+            created_date: DateTime::default(),
+        }
+    }
+
+    fn randomness_record(request_id: &str) -> randomness::Model {
+        randomness::Model {
+            id: 1,
+            keyring_id: 1,
+            receiver_id: 1,
+            epoch: 0,
+            alpha: "a".repeat(64),
+            gamma: "b".repeat(128),
+            c: "c".repeat(64),
+            s: "d".repeat(64),
+            y: "e".repeat(64),
+            witness_address: "f".repeat(40),
+            witness_gamma: "1".repeat(128),
+            witness_hash: "2".repeat(128),
+            inverse_z: "3".repeat(64),
+            signature_proof: "4".repeat(130),
+            request_id: Some(request_id.to_string()),
+            ciphersuite: "ecvrf-keccak256".to_string(),
+            proof: None,
+            created_date: DateTime::default(),
+        }
+    }
+
+    #[tokio::test]
+    async fn safe_insert_should_be_idempotent_on_request_id() {
+        let request_id = "retry-request-1";
+        // Every query must be answered by the mock in order, an unexpected
+        // query (e.g. a second insert) would exhaust the buffer and fail
+        let connection = MockDatabase::new(DatabaseBackend::Postgres)
+            // First request: receiver lookup
+            .append_query_results([vec![receiver_record(0)]])
+            // First request: request id was never used
+            .append_query_results([Vec::<randomness::Model>::new()])
+            // First request: there is no previous epoch
+            .append_query_results([Vec::<randomness::Model>::new()])
+            // First request: receiver nonce is increased
+            .append_query_results([vec![receiver_record(1)]])
+            // First request: new epoch is inserted
+            .append_query_results([vec![randomness_record(request_id)]])
+            // Second request: receiver lookup
+            .append_query_results([vec![receiver_record(1)]])
+            // Second request: request id was used, previous epoch is returned
+            .append_query_results([vec![randomness_record(request_id)]])
+            .into_connection();
+        let context = NodeContext::new(
+            1,
+            KeyPair::new(),
+            NodeOptions {
+                auto_register: AutoRegister::Never,
+                networks: vec![1],
+                ..Default::default()
+            },
+            Postgres::from_connection(connection),
+        );
+        let randomness = context.postgres().table_randomness();
+
+        let first = randomness
+            .safe_insert(
+                Arc::clone(&context),
+                "orand".to_string(),
+                1,
+                RECEIVER_ADDRESS.parse().expect("Invalid address"),
+                Some(request_id.to_string()),
+            )
+            .await
+            .expect("First request must create a new epoch");
+        let second = randomness
+            .safe_insert(
+                Arc::clone(&context),
+                "orand".to_string(),
+                1,
+                RECEIVER_ADDRESS.parse().expect("Invalid address"),
+                Some(request_id.to_string()),
+            )
+            .await
+            .expect("Retried request must return the previous epoch");
+
+        assert_eq!(first, second);
+        assert_eq!(second.request_id.as_deref(), Some(request_id));
+    }
+
+    #[tokio::test]
+    async fn safe_insert_retry_should_not_need_next_epoch() {
+        let request_id = "retry-request-2";
+        // The receiver used up its epochs after the first request was served
+        let connection = MockDatabase::new(DatabaseBackend::Postgres)
+            .append_query_results([vec![receiver_record(i64::MAX)]])
+            .append_query_results([vec![randomness_record(request_id)]])
+            .into_connection();
+        let context = NodeContext::new(
+            1,
+            KeyPair::new(),
+            NodeOptions {
+                auto_register: AutoRegister::Never,
+                networks: vec![1],
+                ..Default::default()
+            },
+            Postgres::from_connection(connection),
+        );
+
+        let retried = context
+            .postgres()
+            .table_randomness()
+            .safe_insert(
+                Arc::clone(&context),
+                "orand".to_string(),
+                1,
+                RECEIVER_ADDRESS.parse().expect("Invalid address"),
+                Some(request_id.to_string()),
+            )
+            .await
+            .expect("Retried request must return the previous epoch");
+        assert_eq!(retried.request_id.as_deref(), Some(request_id));
+    }
+
+    #[test]
+    fn closure_epoch_should_accept_existing_epochs() {
+        assert!(check_closure_epoch(0, Some(0)).is_ok());
+        assert!(check_closure_epoch(0, Some(10)).is_ok());
+        assert!(check_closure_epoch(5, Some(10)).is_ok());
+        assert!(check_closure_epoch(10, Some(10)).is_ok());
+    }
+
+    #[test]
+    fn closure_epoch_should_reject_epoch_beyond_latest() {
+        assert!(matches!(
+            check_closure_epoch(11, Some(10)),
+            Err(DbErr::RecordNotFound(_))
+        ));
+        assert!(matches!(
+            check_closure_epoch(0, None),
+            Err(DbErr::RecordNotFound(_))
+        ));
+    }
+
+    #[test]
+    fn closure_epoch_should_reject_negative_epoch() {
+        assert!(matches!(
+            check_closure_epoch(-1, Some(10)),
+            Err(DbErr::Custom(_))
+        ));
+        assert!(matches!(
+            check_closure_epoch(i64::MIN, None),
+            Err(DbErr::Custom(_))
+        ));
+    }
+
+    #[test]
+    fn next_epoch_should_not_overflow() {
+        assert_eq!(next_epoch(0).ok(), Some(1));
+        assert_eq!(next_epoch(i64::MAX - 1).ok(), Some(i64::MAX));
+        assert!(matches!(next_epoch(i64::MAX), Err(DbErr::Custom(_))));
+        assert!(matches!(next_epoch(-1), Err(DbErr::Custom(_))));
+    }
+}
@@ -1,10 +1,35 @@
-use crate::keyring;
+use crate::ethereum::EthAddress;
 use crate::receiver::{ActiveModel, Column, Entity, Model};
+use crate::{keyring, randomness};
 use sea_orm::sea_query::Query;
 use sea_orm::{
     ActiveModelTrait, ColumnTrait, Condition, DatabaseConnection, DbErr, DeleteResult, EntityTrait,
-    QueryFilter,
+    FromQueryResult, QueryFilter, QueryOrder, QuerySelect,
 };
+use serde::{Deserialize, Serialize};
+use tiny_keccak::{Hasher, Keccak};
+
+/// Networks are u64 chain ids but they're stored in a BIGINT column
+pub(crate) fn network_to_db(network: u64) -> Result<i64, DbErr> {
+    i64::try_from(network).map_err(|_| DbErr::Custom("Network is out of range".to_string()))
+}
+
+/// Receiver with the statistic of its epochs
+#[derive(Clone, Debug, PartialEq, Eq, FromQueryResult, Serialize, Deserialize)]
+pub struct ReceiverStats {
+    /// Receiver Id
+    pub id: i64,
+    /// Receiver address
+    pub address: String,
+    /// Network chain Id
+    pub network: i64,
+    /// Receiver nonce
+    pub nonce: i64,
+    /// Number of epochs of this receiver
+    pub epoch_count: i64,
+    /// Latest epoch, none if there is no epoch
+    pub latest_epoch: Option<i64>,
+}
 
 /// Receiver table
 pub struct ReceiverTable<'a> {
@@ -17,6 +42,18 @@ impl<'a> ReceiverTable<'a> {
         Self { connection }
     }
 
+    /// Name of a receiver derived from its owner, network and address, importing the same
+    /// receiver again always yields the same name so the unique name index catches it
+    pub fn deterministic_name(username: &str, network: u64, address: &EthAddress) -> String {
+        let mut hasher = Keccak::v256();
+        let mut output = [0u8; 32];
+        hasher.update(username.as_bytes());
+        hasher.update(&network.to_be_bytes());
+        hasher.update(address.as_bytes());
+        hasher.finalize(&mut output);
+        format!("r_{}", hex::encode(&output[..16]))
+    }
+
     /// Find receiver record by its network and address
     pub async fn find_by_id(&self, id: i64) -> Result<Option<Model>, DbErr> {
         Entity::find_by_id(id).one(self.connection).await
@@ -36,6 +73,26 @@ impl<'a> ReceiverTable<'a> {
             .await
     }
 
+    /// Find receivers of the given user with their epoch count and latest epoch
+    pub async fn stats(&self, username: String) -> Result<Vec<ReceiverStats>, DbErr> {
+        Entity::find()
+            .select_only()
+            .column(Column::Id)
+            .column(Column::Address)
+            .column(Column::Network)
+            .column(Column::Nonce)
+            .column_as(randomness::Column::Id.count(), "epoch_count")
+            .column_as(randomness::Column::Epoch.max(), "latest_epoch")
+            .left_join(keyring::Entity)
+            .left_join(randomness::Entity)
+            .filter(keyring::Column::Username.eq(username))
+            .group_by(Column::Id)
+            .order_by_asc(Column::Id)
+            .into_model::<ReceiverStats>()
+            .all(self.connection)
+            .await
+    }
+
     pub async fn delete(&self, username: String, receiver_id: i64) -> Result<DeleteResult, DbErr> {
         Entity::delete_many()
             .filter(
@@ -56,17 +113,42 @@ impl<'a> ReceiverTable<'a> {
     }
 
     /// Find receiver record by its network and address
-    pub async fn find_one(&self, network: i64, address: &str) -> Result<Option<Model>, DbErr> {
+    pub async fn find_one(
+        &self,
+        network: u64,
+        address: &EthAddress,
+    ) -> Result<Option<Model>, DbErr> {
+        let network = network_to_db(network)?;
         Entity::find()
             .filter(
                 Condition::all()
-                    .add(Column::Address.eq(address.to_owned()))
+                    .add(Column::Address.eq(address.to_string()))
                     .add(Column::Network.eq(network)),
             )
             .one(self.connection)
             .await
     }
 
+    /// Find receivers of the given address on any of the given networks
+    pub async fn find_by_address(
+        &self,
+        address: &EthAddress,
+        networks: &[u64],
+    ) -> Result<Vec<Model>, DbErr> {
+        let networks = networks
+            .iter()
+            .map(|network| network_to_db(*network))
+            .collect::<Result<Vec<i64>, DbErr>>()?;
+        Entity::find()
+            .filter(
+                Condition::all()
+                    .add(Column::Address.eq(address.to_string()))
+                    .add(Column::Network.is_in(networks)),
+            )
+            .all(self.connection)
+            .await
+    }
+
     /// Insert data to receiver table
     pub async fn insert(&self, json_record: serde_json::Value) -> Result<Model, DbErr> {
         let new_record = ActiveModel::from_json(json_record).expect("Unable to parse JSON");
@@ -75,3 +157,48 @@ impl<'a> ReceiverTable<'a> {
             .await
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::{network_to_db, ReceiverTable};
+    use crate::rpc::check_name;
+
+    #[test]
+    fn network_to_db_should_reject_out_of_range_network() {
+        assert_eq!(
+            network_to_db(i64::MAX as u64).expect("Network is in range"),
+            i64::MAX
+        );
+        assert!(network_to_db(i64::MAX as u64 + 1).is_err());
+    }
+
+    #[test]
+    fn deterministic_name_should_be_reproducible() {
+        let address = "0x1111111111111111111111111111111111111111"
+            .parse()
+            .expect("Invalid address");
+        let name = ReceiverTable::deterministic_name("orand", 1, &address);
+        assert_eq!(
+            ReceiverTable::deterministic_name("orand", 1, &address),
+            name
+        );
+        assert!(check_name(name.clone()));
+
+        // Any other owner, network or address has another name
+        let other_address = "0x2222222222222222222222222222222222222222"
+            .parse()
+            .expect("Invalid address");
+        assert_ne!(
+            ReceiverTable::deterministic_name("chiro", 1, &address),
+            name
+        );
+        assert_ne!(
+            ReceiverTable::deterministic_name("orand", 56, &address),
+            name
+        );
+        assert_ne!(
+            ReceiverTable::deterministic_name("orand", 1, &other_address),
+            name
+        );
+    }
+}
@@ -1,8 +1,20 @@
-use crate::Error;
+use crate::{Error, ErrorCode};
 use bytes::Bytes;
 use http_body_util::{combinators::BoxBody, BodyExt, Empty, Full};
-use hyper::{Response, StatusCode};
+use hyper::{
+    body::{Body, Frame},
+    Response, StatusCode,
+};
 use serde::Serialize;
+use std::{
+    env,
+    future::Future,
+    pin::Pin,
+    sync::OnceLock,
+    task::{Context, Poll},
+    time::Duration,
+};
+use tokio::sync::mpsc::Receiver;
 
 /// Empty response
 pub fn empty() -> BoxBody<Bytes, hyper::Error> {
@@ -18,13 +30,44 @@ pub fn full<T: Into<Bytes>>(chunk: T) -> BoxBody<Bytes, hyper::Error> {
         .boxed()
 }
 
-fn json_encode<T>(value: &T) -> Result<String, Error>
+/// Body that is streamed from a channel, it ends once every sender is dropped
+struct ChannelBody(Receiver<Bytes>);
+
+impl Body for ChannelBody {
+    type Data = Bytes;
+    type Error = hyper::Error;
+
+    fn poll_frame(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+    ) -> Poll<Option<Result<Frame<Self::Data>, Self::Error>>> {
+        self.0
+            .poll_recv(cx)
+            .map(|chunk| chunk.map(|data| Ok(Frame::data(data))))
+    }
+}
+
+/// Pretty print JSON responses only if `ORAND_JSON_PRETTY=true`, it's compact by default
+fn json_pretty() -> bool {
+    static JSON_PRETTY: OnceLock<bool> = OnceLock::new();
+    *JSON_PRETTY.get_or_init(|| match env::var("ORAND_JSON_PRETTY") {
+        Ok(s) => s.trim().to_lowercase().eq("true"),
+        _ => false,
+    })
+}
+
+fn json_encode<T>(value: &T, pretty: bool) -> Result<String, Error>
 where
     T: ?Sized + Serialize,
 {
-    match serde_json::to_string_pretty(value) {
+    let encoded = if pretty {
+        serde_json::to_string_pretty(value)
+    } else {
+        serde_json::to_string(value)
+    };
+    match encoded {
         Ok(s) => Ok(s),
-        Err(_) => Err(Error("SERIALIZE_ERROR", "Can not serialize data")),
+        Err(_) => Err(Error(ErrorCode::Internal, "Can not serialize data")),
     }
 }
 
@@ -43,12 +86,20 @@ impl QuickResponse {
             .expect("Unable to construct response"))
     }
 
-    /// Invoke quick response with status 400
+    /// Invoke quick response with the HTTP status of the error code
     pub fn err(err: Error) -> Result<Response<BoxBody<Bytes, hyper::Error>>, hyper::Error> {
+        Self::err_with_status(err, err.code().status_code())
+    }
+
+    /// Invoke quick response with the given error status
+    pub fn err_with_status(
+        err: Error,
+        status: StatusCode,
+    ) -> Result<Response<BoxBody<Bytes, hyper::Error>>, hyper::Error> {
         Ok(Response::builder()
             .header("Access-Control-Allow-Origin", "*")
             .header("Content-Type", "application/json")
-            .status(StatusCode::INTERNAL_SERVER_ERROR)
+            .status(status)
             .body(full(err.to_json_string()))
             .expect("Unable to construct response"))
     }
@@ -65,6 +116,18 @@ impl QuickResponse {
             .expect("Unable to construct response"))
     }
 
+    /// Invoke quick response with status 200, the JSON body is streamed from the channel
+    pub fn stream(
+        chunks: Receiver<Bytes>,
+    ) -> Result<Response<BoxBody<Bytes, hyper::Error>>, hyper::Error> {
+        Ok(Response::builder()
+            .header("Access-Control-Allow-Origin", "*")
+            .header("Content-Type", "application/json")
+            .status(StatusCode::OK)
+            .body(ChannelBody(chunks).boxed())
+            .expect("Unable to construct response"))
+    }
+
     /// Response based on result
     pub fn res<B: Into<Bytes>>(
         ret: Result<B, Error>,
@@ -79,6 +142,119 @@ impl QuickResponse {
     pub fn res_json<J: ?Sized + Serialize>(
         value: &J,
     ) -> Result<Response<BoxBody<Bytes, hyper::Error>>, hyper::Error> {
-        Self::res(json_encode(value))
+        Self::res(json_encode(value, json_pretty()))
+    }
+
+    /// Resolve the response of the given handler, or response with status 504 if it
+    /// does not finish in time. The handler is dropped on timeout so any pending
+    /// database transaction is rolled back
+    pub async fn with_timeout<F>(
+        duration: Duration,
+        handler: F,
+    ) -> Result<Response<BoxBody<Bytes, hyper::Error>>, hyper::Error>
+    where
+        F: Future<Output = Result<Response<BoxBody<Bytes, hyper::Error>>, hyper::Error>>,
+    {
+        match tokio::time::timeout(duration, handler).await {
+            Ok(response) => response,
+            Err(_) => Self::err(Error(ErrorCode::GatewayTimeout, "Request timed out")),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{json_encode, QuickResponse};
+    use crate::{Error, ErrorCode};
+    use bytes::Bytes;
+    use http_body_util::BodyExt;
+    use hyper::StatusCode;
+    use serde_json::{json, Value};
+    use std::time::Duration;
+
+    #[test]
+    fn json_encode_should_respect_pretty_flag() {
+        let payload = json!({"success": true, "epochs": [1, 2, 3]});
+
+        let compact = json_encode(&payload, false).expect("Unable to encode compact JSON");
+        let pretty = json_encode(&payload, true).expect("Unable to encode pretty JSON");
+
+        assert!(!compact.contains('\n'));
+        assert!(pretty.contains('\n'));
+        assert_eq!(
+            serde_json::from_str::<Value>(&compact).unwrap(),
+            serde_json::from_str::<Value>(&pretty).unwrap()
+        );
+    }
+
+    #[tokio::test]
+    async fn error_response_should_carry_status_of_error_code() {
+        let not_found = QuickResponse::err(Error(ErrorCode::NotFound, "Epoch was not found"))
+            .expect("Unable to construct error response");
+        assert_eq!(not_found.status(), StatusCode::NOT_FOUND);
+        let body = not_found
+            .into_body()
+            .collect()
+            .await
+            .expect("Unable to collect response body")
+            .to_bytes();
+        assert_eq!(
+            serde_json::from_slice::<Value>(&body).expect("Invalid JSON body"),
+            json!({"success": false, "message": "Code [NOT_FOUND]: Epoch was not found"})
+        );
+
+        let internal = QuickResponse::res::<String>(Err(Error(ErrorCode::Internal, "Unknown")))
+            .expect("Unable to construct error response");
+        assert_eq!(internal.status(), StatusCode::INTERNAL_SERVER_ERROR);
+
+        let unavailable = QuickResponse::err(Error(
+            ErrorCode::ServiceUnavailable,
+            "Database is unreachable",
+        ))
+        .expect("Unable to construct error response");
+        assert_eq!(unavailable.status(), StatusCode::SERVICE_UNAVAILABLE);
+    }
+
+    #[tokio::test]
+    async fn streamed_response_should_concat_chunks() {
+        let (sender, receiver) = tokio::sync::mpsc::channel(1);
+        tokio::spawn(async move {
+            for chunk in ["[1", ",2", "]"] {
+                sender
+                    .send(Bytes::from(chunk))
+                    .await
+                    .expect("Unable to send chunk");
+            }
+        });
+        let response = QuickResponse::stream(receiver).expect("Unable to construct response");
+        assert_eq!(response.status(), StatusCode::OK);
+        let body = response
+            .into_body()
+            .collect()
+            .await
+            .expect("Unable to collect response body")
+            .to_bytes();
+        assert_eq!(
+            serde_json::from_slice::<Value>(&body).expect("Invalid JSON body"),
+            json!([1, 2])
+        );
+    }
+
+    #[tokio::test]
+    async fn slow_handler_should_time_out() {
+        let slow = QuickResponse::with_timeout(Duration::from_millis(10), async {
+            tokio::time::sleep(Duration::from_secs(5)).await;
+            QuickResponse::ok("too late")
+        })
+        .await
+        .expect("Unable to construct timeout response");
+        assert_eq!(slow.status(), StatusCode::GATEWAY_TIMEOUT);
+
+        let fast = QuickResponse::with_timeout(Duration::from_secs(5), async {
+            QuickResponse::ok("in time")
+        })
+        .await
+        .expect("Unable to construct response");
+        assert_eq!(fast.status(), StatusCode::OK);
     }
 }
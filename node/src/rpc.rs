@@ -1,5 +1,7 @@
-use crate::error::Error;
-use core::panic;
+use crate::{
+    error::{Error, ErrorCode},
+    ethereum::EthAddress,
+};
 use regex::Regex;
 use serde::{Deserialize, Serialize};
 
@@ -10,24 +12,53 @@ pub struct JSONRPCPayload {
     params: Vec<String>,
 }
 
+impl JSONRPCPayload {
+    /// Get a required parameter
+    fn param(&self, index: usize) -> Result<&str, Error> {
+        self.params
+            .get(index)
+            .map(|param| param.as_str())
+            .ok_or(Error(ErrorCode::BadRequest, "Missing parameter"))
+    }
+}
+
 /// JSON RPC Method
 pub enum JSONRPCMethod {
-    /// New epoch (network id, receiver address, epoch id)
-    OrandNewEpoch(i64, String),
-    /// Get epoch (network id, receiver address, epoch id)
-    OrandGetEpoch(i64, String, i64),
+    /// New epoch (network id, receiver address, optional request id)
+    OrandNewEpoch(u64, EthAddress, Option<String>),
+    /// Get epoch (network id, receiver address, epoch id, optional number of recent epochs,
+    /// verify epochs)
+    OrandGetEpoch(u64, EthAddress, i64, Option<u64>, bool),
+    /// Verify a stored epoch with its ciphersuite (network id, receiver address, epoch id)
+    OrandVerifyEpoch(u64, EthAddress, i64),
+    /// Export the epoch chain of a receiver (network id, receiver address)
+    OrandExportChain(u64, EthAddress),
     /// New epoch of given network (network id, receiver address)
-    OrandNewPrivateEpoch(i64, String),
+    OrandNewPrivateEpoch(u64, String),
     /// Get public key (username)
     OrandGetPublicKey(String),
+    /// Get networks served by the node and their public epoch status
+    OrandGetNetworks,
+    /// Get public key and ciphersuite of the operator VRF for a light client
+    OrandGetParams,
+    /// Get public key and its Ethereum address (username)
+    OrandGetOperatorAddress(String),
     // Get user (username)
     AdminGetUser(String),
     /// Create new user (username)
     AdminAddUser(String),
     /// Get receiver (username)
     AdminGetReceiver(String),
+    /// Get receivers with their epoch count and latest epoch (username)
+    AdminGetReceiverStats(String),
     /// Create new receiver (username, receiver address, network)
-    AdminAddReceiver(String, String, i64),
+    AdminAddReceiver(String, EthAddress, u64),
+    /// Re-sign operator proof of an epoch (network id, receiver address, epoch id, correct nonce)
+    AdminResignEpoch(u64, EthAddress, i64, i64),
+    /// Get current public key and rotations of an operator key (username)
+    OrandGetKeyHistory(String),
+    /// Replace the operator VRF key, epochs before the rotation keep their old key
+    AdminRotateOperatorKey,
     /// Admin delete receiver (username, receiver address)
     /// This method has been removed
     AdminRemoveReceiver(String, i64),
@@ -36,27 +67,85 @@ pub enum JSONRPCMethod {
 /// Zero address
 pub const ZERO_ADDRESS: &str = "0x0000000000000000000000000000000000000000";
 
-pub fn decode_i64(val: String) -> i64 {
-    let regex_i64 = Regex::new(r#"\d{1,10}"#).expect("Unable to init Regex");
-    match regex_i64.is_match(val.as_str().as_ref()) {
-        true => val.as_str().parse::<i64>().expect("Unable to parse i64"),
-        false => panic!("Invalid input i64 value"),
+/// Largest network id, networks are stored in a BIGINT column
+pub const MAX_NETWORK: u64 = i64::MAX as u64;
+
+/// Decode a network id (chain id), it must be a decimal number up to [MAX_NETWORK]
+pub fn decode_network(val: &str) -> Result<u64, Error> {
+    let regex_network = Regex::new(r#"^\d{1,20}$"#).expect("Unable to init Regex");
+    if !regex_network.is_match(val) {
+        return Err(Error(ErrorCode::BadRequest, "Invalid network"));
+    }
+    match val.parse::<u64>() {
+        Ok(network) if network <= MAX_NETWORK => Ok(network),
+        _ => Err(Error(ErrorCode::BadRequest, "Network is out of range")),
+    }
+}
+
+/// Decode a number of records, it must be a decimal number
+pub fn decode_count(val: &str) -> Result<u64, Error> {
+    let regex_count = Regex::new(r#"^\d{1,20}$"#).expect("Unable to init Regex");
+    if !regex_count.is_match(val) {
+        return Err(Error(ErrorCode::BadRequest, "Invalid count"));
+    }
+    val.parse::<u64>()
+        .map_err(|_| Error(ErrorCode::BadRequest, "Count is out of range"))
+}
+
+/// Decode a boolean flag, it must be `true` or `false`
+pub fn decode_bool(val: &str) -> Result<bool, Error> {
+    match val {
+        "true" => Ok(true),
+        "false" => Ok(false),
+        _ => Err(Error(ErrorCode::BadRequest, "Invalid boolean")),
     }
 }
 
-pub fn decode_address(val: String) -> String {
+/// Decode a non negative i64, e.g. epoch id or nonce
+pub fn decode_i64(val: &str) -> Result<i64, Error> {
+    let regex_i64 = Regex::new(r#"^\d{1,19}$"#).expect("Unable to init Regex");
+    if !regex_i64.is_match(val) {
+        return Err(Error(ErrorCode::BadRequest, "Invalid number"));
+    }
+    val.parse::<i64>()
+        .map_err(|_| Error(ErrorCode::BadRequest, "Number is out of range"))
+}
+
+/// Decode a hex address, it's returned in lower case
+pub fn decode_address(val: &str) -> Result<String, Error> {
     let regex_address = Regex::new(r#"^0x[a-fA-F0-9]{40}$"#).expect("Unable to init Regex");
-    match regex_address.is_match(val.as_str().as_ref()) {
-        true => val.clone().to_lowercase(),
-        false => panic!("Invalid input address value"),
+    match regex_address.is_match(val) {
+        true => Ok(val.to_lowercase()),
+        false => Err(Error(ErrorCode::BadRequest, "Invalid address")),
+    }
+}
+
+/// Decode a username or receiver name, see [check_name]
+pub fn decode_name(val: &str) -> Result<String, Error> {
+    match check_name(val.to_string()) {
+        true => Ok(val.to_string()),
+        false => Err(Error(ErrorCode::BadRequest, "Invalid name")),
+    }
+}
+
+/// Decode a request id, up to 64 alphanumeric characters, dashes and underscores
+pub fn decode_request_id(val: &str) -> Result<String, Error> {
+    let regex_request_id = Regex::new(r#"^[a-zA-Z0-9\-\_]{1,64}$"#).expect("Unable to init Regex");
+    match regex_request_id.is_match(val) {
+        true => Ok(val.to_string()),
+        false => Err(Error(ErrorCode::BadRequest, "Invalid request id")),
     }
 }
 
-pub fn decode_name(val: String) -> String {
-    match check_name(val.clone()) {
-        true => val.clone(),
-        false => panic!("Invalid input name value"),
+/// Make sure a private receiver is not ZERO_ADDRESS, it's reserved for public epochs
+pub fn check_private_receiver(address: &EthAddress) -> Result<(), Error> {
+    if address.is_zero() {
+        return Err(Error(
+            ErrorCode::BadRequest,
+            "ZERO_ADDRESS is reserved for public epochs",
+        ));
     }
+    Ok(())
 }
 
 pub fn check_name(val: String) -> bool {
@@ -69,40 +158,186 @@ impl JSONRPCMethod {
     pub fn from_json_string(json_string: &str) -> Result<Self, Error> {
         let json_rpc: JSONRPCPayload = match serde_json::from_str(json_string) {
             Ok(json_rpc) => json_rpc,
-            Err(_) => return Err(Error("INVALID_JSON", "Invalid JSON")),
+            Err(_) => return Err(Error(ErrorCode::BadRequest, "Invalid JSON")),
         };
         let result = match json_rpc.method.as_str() {
             "orand_getPublicEpoch" => Self::OrandGetEpoch(
-                decode_i64(json_rpc.params[0].clone()),
-                ZERO_ADDRESS.to_string(),
-                decode_i64(json_rpc.params[1].clone()),
+                decode_network(json_rpc.param(0)?)?,
+                EthAddress::ZERO,
+                decode_i64(json_rpc.param(1)?)?,
+                json_rpc
+                    .params
+                    .get(2)
+                    .map(|c| decode_count(c))
+                    .transpose()?,
+                json_rpc
+                    .params
+                    .get(3)
+                    .map(|v| decode_bool(v))
+                    .transpose()?
+                    .unwrap_or(false),
             ),
             "orand_getPrivateEpoch" => Self::OrandGetEpoch(
-                decode_i64(json_rpc.params[0].clone()),
-                decode_address(json_rpc.params[1].clone()),
-                decode_i64(json_rpc.params[2].clone()),
+                decode_network(json_rpc.param(0)?)?,
+                json_rpc.param(1)?.parse::<EthAddress>()?,
+                decode_i64(json_rpc.param(2)?)?,
+                json_rpc
+                    .params
+                    .get(3)
+                    .map(|c| decode_count(c))
+                    .transpose()?,
+                json_rpc
+                    .params
+                    .get(4)
+                    .map(|v| decode_bool(v))
+                    .transpose()?
+                    .unwrap_or(false),
+            ),
+            "orand_verifyPublicEpoch" => Self::OrandVerifyEpoch(
+                decode_network(json_rpc.param(0)?)?,
+                EthAddress::ZERO,
+                decode_i64(json_rpc.param(1)?)?,
+            ),
+            "orand_verifyPrivateEpoch" => Self::OrandVerifyEpoch(
+                decode_network(json_rpc.param(0)?)?,
+                json_rpc.param(1)?.parse::<EthAddress>()?,
+                decode_i64(json_rpc.param(2)?)?,
+            ),
+            "orand_exportChain" => Self::OrandExportChain(
+                decode_network(json_rpc.param(0)?)?,
+                json_rpc.param(1)?.parse::<EthAddress>()?,
             ),
             "orand_newPublicEpoch" => Self::OrandNewEpoch(
-                decode_i64(json_rpc.params[0].clone()),
-                ZERO_ADDRESS.to_string(),
+                decode_network(json_rpc.param(0)?)?,
+                EthAddress::ZERO,
+                json_rpc
+                    .params
+                    .get(1)
+                    .map(|r| decode_request_id(r))
+                    .transpose()?,
             ),
             "orand_newPrivateEpoch" => Self::OrandNewEpoch(
-                decode_i64(json_rpc.params[0].clone()),
-                decode_address(json_rpc.params[1].clone()),
+                decode_network(json_rpc.param(0)?)?,
+                json_rpc.param(1)?.parse::<EthAddress>()?,
+                json_rpc
+                    .params
+                    .get(2)
+                    .map(|r| decode_request_id(r))
+                    .transpose()?,
             ),
-            "orand_getPublicKey" => {
-                Self::OrandGetPublicKey(decode_name(json_rpc.params[0].clone()))
+            "orand_getPublicKey" => Self::OrandGetPublicKey(decode_name(json_rpc.param(0)?)?),
+            "orand_getNetworks" => Self::OrandGetNetworks,
+            "orand_getParams" => Self::OrandGetParams,
+            "orand_getOperatorAddress" => {
+                Self::OrandGetOperatorAddress(decode_name(json_rpc.param(0)?)?)
+            }
+            "orand_getKeyHistory" => Self::OrandGetKeyHistory(decode_name(json_rpc.param(0)?)?),
+            "admin_rotateOperatorKey" => Self::AdminRotateOperatorKey,
+            "admin_getUser" => Self::AdminGetUser(decode_name(json_rpc.param(0)?)?),
+            "admin_addUser" => Self::AdminAddUser(decode_name(json_rpc.param(0)?)?),
+            "admin_getReceiver" => Self::AdminGetReceiver(decode_name(json_rpc.param(0)?)?),
+            "admin_getReceiverStats" => {
+                Self::AdminGetReceiverStats(decode_name(json_rpc.param(0)?)?)
             }
-            "admin_getUser" => Self::AdminGetUser(decode_name(json_rpc.params[0].clone())),
-            "admin_addUser" => Self::AdminAddUser(decode_name(json_rpc.params[0].clone())),
-            "admin_getReceiver" => Self::AdminGetReceiver(decode_name(json_rpc.params[0].clone())),
             "admin_addReceiver" => Self::AdminAddReceiver(
-                decode_name(json_rpc.params[0].clone()),
-                decode_address(json_rpc.params[1].clone()),
-                decode_i64(json_rpc.params[2].clone()),
+                decode_name(json_rpc.param(0)?)?,
+                json_rpc.param(1)?.parse::<EthAddress>()?,
+                decode_network(json_rpc.param(2)?)?,
+            ),
+            "admin_resignEpoch" => Self::AdminResignEpoch(
+                decode_network(json_rpc.param(0)?)?,
+                json_rpc.param(1)?.parse::<EthAddress>()?,
+                decode_i64(json_rpc.param(2)?)?,
+                decode_i64(json_rpc.param(3)?)?,
             ),
-            _ => return Err(Error("INVALID_METHOD", "Unsupported method")),
+            _ => return Err(Error(ErrorCode::BadRequest, "Unsupported method")),
         };
         Ok(result)
     }
+
+    /// Check if the method targets public epochs, they belong to ZERO_ADDRESS
+    pub fn is_public(&self) -> bool {
+        match self {
+            Self::OrandNewEpoch(_, address, _)
+            | Self::OrandGetEpoch(_, address, _, _, _)
+            | Self::OrandVerifyEpoch(_, address, _)
+            | Self::OrandExportChain(_, address) => address.is_zero(),
+            _ => false,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        check_private_receiver, decode_bool, decode_count, decode_network, JSONRPCMethod,
+        MAX_NETWORK, ZERO_ADDRESS,
+    };
+    use crate::{error::ErrorCode, ethereum::EthAddress};
+
+    #[test]
+    fn large_network_should_round_trip() {
+        // Larger than u32::MAX, it used to be truncated
+        let network: u64 = 4_503_599_627_370_476;
+        assert_eq!(decode_network(&network.to_string()), Ok(network));
+        assert_eq!(decode_network(&MAX_NETWORK.to_string()), Ok(MAX_NETWORK));
+
+        let payload = format!(
+            "{{\"method\":\"orand_getPublicEpoch\",\"params\":[\"{}\",\"7\"]}}",
+            network
+        );
+        match JSONRPCMethod::from_json_string(&payload) {
+            Ok(JSONRPCMethod::OrandGetEpoch(decoded, address, epoch, count, verify)) => {
+                assert_eq!(decoded, network);
+                assert_eq!(address.to_string(), ZERO_ADDRESS);
+                assert_eq!(epoch, 7);
+                assert_eq!(count, None);
+                assert!(!verify);
+            }
+            _ => panic!("Unable to decode orand_getPublicEpoch"),
+        }
+    }
+
+    #[test]
+    fn invalid_network_should_be_rejected() {
+        for network in ["", "-1", "0x01", "1 ", "18446744073709551616"] {
+            assert_eq!(
+                decode_network(network).map_err(|e| e.code()),
+                Err(ErrorCode::BadRequest)
+            );
+        }
+        assert!(decode_network(&(MAX_NETWORK + 1).to_string()).is_err());
+    }
+
+    #[test]
+    fn optional_epoch_count_should_be_decoded() {
+        let payload = "{\"method\":\"orand_getPrivateEpoch\",\"params\":[\"1\",\"0x0000000000000000000000000000000000000001\",\"9223372036854775807\",\"5\",\"true\"]}";
+        match JSONRPCMethod::from_json_string(payload) {
+            Ok(JSONRPCMethod::OrandGetEpoch(_, _, epoch, count, verify)) => {
+                assert_eq!(epoch, i64::MAX);
+                assert_eq!(count, Some(5));
+                assert!(verify);
+            }
+            _ => panic!("Unable to decode orand_getPrivateEpoch"),
+        }
+        assert!(decode_bool("yes").is_err());
+        for count in ["", "-1", "five", "18446744073709551616"] {
+            assert_eq!(
+                decode_count(count).map_err(|e| e.code()),
+                Err(ErrorCode::BadRequest)
+            );
+        }
+    }
+
+    #[test]
+    fn zero_address_should_not_be_private_receiver() {
+        assert_eq!(
+            check_private_receiver(&EthAddress::ZERO).map_err(|e| e.code()),
+            Err(ErrorCode::BadRequest)
+        );
+        let address = "0x0000000000000000000000000000000000000001"
+            .parse::<EthAddress>()
+            .expect("Invalid address");
+        assert!(check_private_receiver(&address).is_ok());
+    }
 }
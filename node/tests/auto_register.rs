@@ -0,0 +1,86 @@
+mod common;
+
+use common::{call, jwt, NETWORK};
+use hyper::StatusCode;
+use libecvrf::KeyPair;
+use node::{
+    handler::ORAND_KEYRING_NAME, postgres_sql::Postgres, AutoRegister, NodeContext, NodeOptions,
+};
+use serde_json::json;
+
+const RECEIVER_ADDRESS: &str = "0x7777777777777777777777777777777777777777";
+
+/// Request a new private epoch of an unknown receiver on a node with the given policy,
+/// return the status and whether the receiver exists afterward
+async fn new_epoch_of_unknown_receiver(
+    auto_register: AutoRegister,
+    is_testnet: bool,
+) -> (StatusCode, bool) {
+    let postgres = Postgres::new_in_memory()
+        .await
+        .expect("Unable to create in-memory database");
+    let orand_record = postgres
+        .table_keyring()
+        .bootstrap_user(ORAND_KEYRING_NAME.to_string(), None)
+        .await
+        .expect("Unable to bootstrap orand");
+    let context = NodeContext::new(
+        orand_record.id,
+        KeyPair::from(orand_record.secret_key.clone()),
+        NodeOptions {
+            is_testnet,
+            auto_register,
+            networks: vec![NETWORK],
+            ..Default::default()
+        },
+        postgres,
+    );
+
+    let (status, _) = call(
+        &context,
+        Some(&jwt(ORAND_KEYRING_NAME, &orand_record.hmac_secret)),
+        "orand_newPrivateEpoch",
+        json!([NETWORK.to_string(), RECEIVER_ADDRESS]),
+    )
+    .await;
+    let registered = context
+        .postgres()
+        .table_receiver()
+        .find_one(NETWORK, &RECEIVER_ADDRESS.parse().expect("Invalid address"))
+        .await
+        .expect("Unable to query receiver")
+        .is_some();
+    (status, registered)
+}
+
+#[tokio::test]
+async fn never_should_reject_unknown_receiver() {
+    for is_testnet in [true, false] {
+        assert_eq!(
+            new_epoch_of_unknown_receiver(AutoRegister::Never, is_testnet).await,
+            (StatusCode::NOT_FOUND, false)
+        );
+    }
+}
+
+#[tokio::test]
+async fn on_testnet_should_register_unknown_receiver_on_testnet_only() {
+    assert_eq!(
+        new_epoch_of_unknown_receiver(AutoRegister::OnTestnet, true).await,
+        (StatusCode::OK, true)
+    );
+    assert_eq!(
+        new_epoch_of_unknown_receiver(AutoRegister::OnTestnet, false).await,
+        (StatusCode::NOT_FOUND, false)
+    );
+}
+
+#[tokio::test]
+async fn always_should_register_unknown_receiver() {
+    for is_testnet in [true, false] {
+        assert_eq!(
+            new_epoch_of_unknown_receiver(AutoRegister::Always, is_testnet).await,
+            (StatusCode::OK, true)
+        );
+    }
+}
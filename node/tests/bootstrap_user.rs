@@ -0,0 +1,72 @@
+use hmac::{Hmac, Mac};
+use libecvrf::KeyPair;
+use node::{jwt::JWT, postgres_sql::Postgres};
+use sha2::Sha256;
+
+/// Sign a JWT with the given HMAC secret the same way clients do
+fn sign_jwt(hmac_secret: &str, username: &str) -> String {
+    let now = std::time::SystemTime::now()
+        .duration_since(std::time::SystemTime::UNIX_EPOCH)
+        .expect("Unable to get current time")
+        .as_secs();
+    let header = base64_url::encode(r#"{"alg":"HS256","typ":"JWT"}"#);
+    let payload = format!(
+        r#"{{"user":"{}","nonce":0,"iat":{},"exp":{}}}"#,
+        username,
+        now - 10,
+        now + 60
+    );
+    let mut mac = Hmac::<Sha256>::new_from_slice(
+        &hex::decode(hmac_secret).expect("HMAC secret must be hex encoded"),
+    )
+    .expect("HMAC can take key of any size");
+    mac.update(payload.as_bytes());
+    format!(
+        "{}.{}.{}",
+        header,
+        base64_url::encode(&payload),
+        base64_url::encode(&mac.finalize().into_bytes())
+    )
+}
+
+#[tokio::test]
+async fn bootstrapped_user_should_be_findable_and_able_to_sign_jwt() {
+    let postgres = Postgres::new_in_memory()
+        .await
+        .expect("Unable to create in-memory database");
+    let keyring = postgres.table_keyring();
+
+    let record = keyring
+        .bootstrap_user("chiro_hiro".to_string(), None)
+        .await
+        .expect("Unable to bootstrap user");
+    let found = keyring
+        .find_by_name("chiro_hiro".to_string())
+        .await
+        .expect("Unable to query keyring")
+        .expect("User must exist");
+    assert_eq!(found, record);
+
+    // Stored keypair is consistent
+    let keypair = KeyPair::from(record.secret_key.clone());
+    assert_eq!(
+        hex::encode(keypair.public_key.serialize()),
+        record.public_key
+    );
+
+    // HMAC secret is able to sign and verify JWT
+    let token = sign_jwt(&record.hmac_secret, &record.username);
+    let payload = JWT::decode_payload(&token).expect("Unable to decode JWT");
+    assert_eq!(payload.user, record.username);
+    assert!(JWT::new(&record.hmac_secret).verify(&token));
+    assert!(!JWT::new(&hex::encode([0u8; 32])).verify(&token));
+
+    // Given secret key is kept
+    let given = KeyPair::new();
+    let orand = keyring
+        .bootstrap_user("orand".to_string(), Some(given.secret_key))
+        .await
+        .expect("Unable to bootstrap user");
+    assert_eq!(orand.secret_key, hex::encode(given.secret_key.serialize()));
+    assert_ne!(orand.hmac_secret, record.hmac_secret);
+}
@@ -0,0 +1,31 @@
+mod common;
+
+use common::fixture;
+use node::epoch::{verify_epoch, Ciphersuite, ProofFormat};
+use std::sync::Arc;
+
+const RECEIVER_ADDRESS: &str = "0x1111111111111111111111111111111111111111";
+
+#[tokio::test]
+async fn new_epoch_should_record_and_verify_with_its_ciphersuite() {
+    let context = fixture(RECEIVER_ADDRESS, ProofFormat::Full).await.context;
+    let public_key = context.keypair().public_key;
+    let randomness = context.postgres().table_randomness();
+    let record = randomness
+        .safe_insert(
+            Arc::clone(&context),
+            "orand".to_string(),
+            1,
+            RECEIVER_ADDRESS.parse().expect("Invalid address"),
+            None,
+        )
+        .await
+        .expect("Unable to create new epoch");
+    assert_eq!(record.ciphersuite, Ciphersuite::default().as_str());
+    assert_eq!(verify_epoch(&public_key, &record), Ok(true));
+
+    // A proof stored with an unknown ciphersuite must not be verified
+    let mut unknown = record.clone();
+    unknown.ciphersuite = "ecvrf-poseidon".to_string();
+    assert!(verify_epoch(&public_key, &unknown).is_err());
+}
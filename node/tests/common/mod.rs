@@ -0,0 +1,191 @@
+//! Fixtures shared by the integration tests, each test crate uses a subset of them
+#![allow(dead_code)]
+
+use bytes::Bytes;
+use http_body_util::{BodyExt, Full};
+use hyper::{Method, Request, StatusCode};
+use libecvrf::KeyPair;
+use node::{
+    epoch::ProofFormat,
+    handler::orand,
+    jwt::{JWTPayload, JWT},
+    keyring,
+    postgres_sql::Postgres,
+    randomness, receiver, AutoRegister, NodeContext, NodeOptions,
+};
+use serde_json::{json, Value};
+use std::{sync::Arc, time::SystemTime};
+
+/// Network of the receivers that are created by [fixture]
+pub const NETWORK: u64 = 1;
+
+/// Node with an operator key and one receiver in an in-memory database
+pub struct Fixture {
+    /// Context of the node, it proves with the operator key
+    pub context: Arc<NodeContext<'static>>,
+    /// Receiver of the operator on [NETWORK]
+    pub receiver: receiver::Model,
+}
+
+/// Insert the keyring record of the operator `orand` with the given keypair
+pub async fn insert_operator(postgres: &Postgres, keypair: &KeyPair) -> keyring::Model {
+    postgres
+        .table_keyring()
+        .insert(json!({
+            "username": "orand",
+            "hmac_secret": hex::encode([1u8; 32]),
+            "public_key": hex::encode(keypair.public_key.serialize()),
+            "secret_key": hex::encode(keypair.secret_key.serialize())}))
+        .await
+        .expect("Unable to insert new key to keyring table")
+}
+
+/// Insert a receiver of the given keyring on [NETWORK], it's named after its address
+pub async fn insert_receiver(
+    postgres: &Postgres,
+    keyring_id: i64,
+    address: &str,
+    nonce: i64,
+) -> receiver::Model {
+    postgres
+        .table_receiver()
+        .insert(json!({
+            "keyring_id": keyring_id,
+            "name": format!("receiver_{}", address),
+            "address": address,
+            "network": NETWORK,
+            "nonce": nonce,
+        }))
+        .await
+        .expect("Unable to insert new receiver")
+}
+
+/// Context of a mainnet node that serves [NETWORK] and never registers receivers
+pub fn node_context(
+    keyring_id: i64,
+    keypair: KeyPair,
+    proof_format: ProofFormat,
+    postgres: Postgres,
+) -> Arc<NodeContext<'static>> {
+    NodeContext::new(
+        keyring_id,
+        keypair,
+        NodeOptions {
+            auto_register: AutoRegister::Never,
+            networks: vec![NETWORK],
+            proof_format,
+            ..Default::default()
+        },
+        postgres,
+    )
+}
+
+/// Node with a fresh operator key and a receiver at `address` without any epoch
+pub async fn fixture(address: &str, proof_format: ProofFormat) -> Fixture {
+    let postgres = Postgres::new_in_memory()
+        .await
+        .expect("Unable to create in-memory database");
+    let keypair = KeyPair::new();
+    let keyring = insert_operator(&postgres, &keypair).await;
+    let receiver = insert_receiver(&postgres, keyring.id, address, 0).await;
+    Fixture {
+        context: node_context(keyring.id, keypair, proof_format, postgres),
+        receiver,
+    }
+}
+
+/// Create `count` epochs of the receiver at `address` in the order they're created
+pub async fn new_epochs(
+    context: &Arc<NodeContext<'static>>,
+    address: &str,
+    count: usize,
+) -> Vec<randomness::Model> {
+    let mut epochs = Vec::with_capacity(count);
+    for _ in 0..count {
+        let epoch = context
+            .postgres()
+            .table_randomness()
+            .safe_insert(
+                Arc::clone(context),
+                "orand".to_string(),
+                NETWORK,
+                address.parse().expect("Invalid address"),
+                None,
+            )
+            .await
+            .expect("Unable to create new epoch");
+        epochs.push(epoch);
+    }
+    epochs
+}
+
+/// Randomness record with placeholder proof columns, `seed` must differ between the
+/// records of a database since alpha, y and the signature proof are unique
+pub fn randomness_record(
+    keyring_id: i64,
+    receiver_id: i64,
+    epoch: i64,
+    seed: u8,
+) -> serde_json::Value {
+    json!({
+        "keyring_id": keyring_id,
+        "receiver_id": receiver_id,
+        "epoch": epoch,
+        "alpha": hex::encode([seed; 32]),
+        "gamma": hex::encode([seed; 64]),
+        "c": hex::encode([seed; 32]),
+        "s": hex::encode([seed; 32]),
+        "y": hex::encode([seed.wrapping_add(1); 32]),
+        "witness_address": hex::encode([seed; 20]),
+        "witness_gamma": hex::encode([seed; 64]),
+        "witness_hash": hex::encode([seed; 64]),
+        "inverse_z": hex::encode([seed; 32]),
+        "signature_proof": hex::encode([seed; 65]),
+    })
+}
+
+/// Build a JWT of the user signed with the HMAC secret of the user
+pub fn jwt(user: &str, hmac_secret: &str) -> String {
+    let now = SystemTime::now()
+        .duration_since(SystemTime::UNIX_EPOCH)
+        .expect("Unable to get current time")
+        .as_secs();
+    JWT::new(hmac_secret).sign(&JWTPayload {
+        user: user.to_string(),
+        nonce: 0,
+        iat: now,
+        exp: now + 3600,
+    })
+}
+
+/// Send a JSON RPC request to the handler, return the status and the JSON body
+pub async fn call(
+    context: &Arc<NodeContext<'static>>,
+    authorization: Option<&str>,
+    method: &str,
+    params: Value,
+) -> (StatusCode, Value) {
+    let mut request = Request::builder().method(Method::POST).uri("/");
+    if let Some(token) = authorization {
+        request = request.header("authorization", token);
+    }
+    let request = request
+        .body(Full::new(Bytes::from(
+            json!({"method": method, "params": params}).to_string(),
+        )))
+        .expect("Unable to build request");
+    let response = orand(request, Arc::clone(context))
+        .await
+        .expect("Unable to handle request");
+    let status = response.status();
+    let body = response
+        .into_body()
+        .collect()
+        .await
+        .expect("Unable to collect response body")
+        .to_bytes();
+    (
+        status,
+        serde_json::from_slice(&body).expect("Response is not JSON"),
+    )
+}
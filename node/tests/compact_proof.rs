@@ -0,0 +1,60 @@
+mod common;
+
+use common::fixture;
+use libecvrf::ECVRF;
+use node::{
+    epoch::{decode_contract_proof, verify_epoch, ProofFormat},
+    randomness,
+};
+use sea_orm::EntityTrait;
+use std::sync::Arc;
+
+const RECEIVER_ADDRESS: &str = "0x1111111111111111111111111111111111111111";
+
+#[tokio::test]
+async fn compact_epoch_should_decode_to_full_format() {
+    let context = fixture(RECEIVER_ADDRESS, ProofFormat::Compact)
+        .await
+        .context;
+    let public_key = context.keypair().public_key;
+    let receiver_address = RECEIVER_ADDRESS.parse().expect("Invalid address");
+    let randomness = context.postgres().table_randomness();
+    let inserted = randomness
+        .safe_insert(
+            Arc::clone(&context),
+            "orand".to_string(),
+            1,
+            receiver_address,
+            None,
+        )
+        .await
+        .expect("Unable to create new epoch");
+
+    // Proof columns are empty in the database, the blob carries them
+    let raw = randomness::Entity::find_by_id(inserted.id)
+        .one(randomness.connection)
+        .await
+        .expect("Unable to query randomness table")
+        .expect("Epoch must exist");
+    assert!(raw.gamma.is_empty());
+    assert!(raw.witness_hash.is_empty());
+    assert_eq!(raw.y, inserted.y);
+    assert!(raw.proof.is_some());
+
+    // Get endpoints see the same structured epoch as the full format
+    let found = randomness
+        .find_given_epoch(1, &receiver_address, inserted.epoch)
+        .await
+        .expect("Unable to query randomness table")
+        .expect("Epoch must exist");
+    assert_eq!(found, inserted);
+    assert_eq!(
+        serde_json::to_value(&found).expect("Unable to serialize epoch"),
+        serde_json::to_value(&inserted).expect("Unable to serialize epoch")
+    );
+    assert!(!found.gamma.is_empty());
+    assert_eq!(verify_epoch(&public_key, &found), Ok(true));
+    let contract_proof =
+        decode_contract_proof(&public_key, &found).expect("Unable to decode stored proof");
+    assert!(ECVRF::new_verifier(public_key).verify_contract(&contract_proof));
+}
@@ -0,0 +1,64 @@
+mod common;
+
+use common::fixture;
+use node::epoch::ProofFormat;
+use std::sync::Arc;
+
+const RECEIVER_ADDRESS: &str = "0x2222222222222222222222222222222222222222";
+
+const CONCURRENT_EPOCHS: usize = 16;
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+async fn concurrent_new_epochs_should_be_contiguous() {
+    let context = fixture(RECEIVER_ADDRESS, ProofFormat::Full).await.context;
+
+    // Every task races for the same receiver nonce
+    let tasks = (0..CONCURRENT_EPOCHS)
+        .map(|_| {
+            let context = Arc::clone(&context);
+            tokio::spawn(async move {
+                context
+                    .postgres()
+                    .table_randomness()
+                    .safe_insert(
+                        Arc::clone(&context),
+                        "orand".to_string(),
+                        1,
+                        RECEIVER_ADDRESS.parse().expect("Invalid address"),
+                        None,
+                    )
+                    .await
+            })
+        })
+        .collect::<Vec<_>>();
+    let mut created = Vec::with_capacity(CONCURRENT_EPOCHS);
+    for task in tasks {
+        let epoch = task
+            .await
+            .expect("Task panicked")
+            .expect("Unable to create new epoch");
+        created.push(epoch.epoch);
+    }
+    created.sort();
+    assert_eq!(created, (0..CONCURRENT_EPOCHS as i64).collect::<Vec<i64>>());
+
+    // Stored epochs are 0..M-1 exactly once and each one is seeded by the previous result
+    let mut epochs = context
+        .postgres()
+        .table_randomness()
+        .find_recent_epoch(
+            1,
+            &RECEIVER_ADDRESS.parse().expect("Invalid address"),
+            CONCURRENT_EPOCHS as u64,
+        )
+        .await
+        .expect("Unable to query epochs");
+    epochs.reverse();
+    assert_eq!(epochs.len(), CONCURRENT_EPOCHS);
+    for (i, epoch) in epochs.iter().enumerate() {
+        assert_eq!(epoch.epoch, i as i64);
+        if i > 0 {
+            assert_eq!(epoch.alpha, epochs[i - 1].y);
+        }
+    }
+}
@@ -0,0 +1,52 @@
+mod common;
+
+use common::{insert_operator, insert_receiver, node_context, randomness_record};
+use libecvrf::KeyPair;
+use node::{epoch::ProofFormat, postgres_sql::Postgres};
+use sea_orm::DbErr;
+use std::sync::Arc;
+
+const RECEIVER_ADDRESS: &str = "0x5555555555555555555555555555555555555555";
+
+#[tokio::test]
+async fn new_epoch_near_max_should_fail_without_wrapping() {
+    let postgres = Postgres::new_in_memory()
+        .await
+        .expect("Unable to create in-memory database");
+    let keypair = KeyPair::new();
+    let keyring = insert_operator(&postgres, &keypair).await;
+    // A bad manual edit left the receiver at the largest epoch
+    let receiver = insert_receiver(&postgres, keyring.id, RECEIVER_ADDRESS, i64::MAX).await;
+    postgres
+        .table_randomness()
+        .insert(randomness_record(keyring.id, receiver.id, i64::MAX - 1, 1))
+        .await
+        .expect("Unable to insert latest epoch");
+
+    let context = node_context(keyring.id, keypair, ProofFormat::Full, postgres);
+    let address = RECEIVER_ADDRESS.parse().expect("Invalid address");
+    let result = context
+        .postgres()
+        .table_randomness()
+        .safe_insert(Arc::clone(&context), "orand".to_string(), 1, address, None)
+        .await;
+    assert!(matches!(result, Err(DbErr::Custom(_))));
+
+    // Nothing was written, there is no negative epoch
+    let latest = context
+        .postgres()
+        .table_randomness()
+        .find_latest_epoch(1, &address)
+        .await
+        .expect("Unable to query latest epoch")
+        .expect("Latest epoch was not found");
+    assert_eq!(latest.epoch, i64::MAX - 1);
+    let receiver = context
+        .postgres()
+        .table_receiver()
+        .find_one(1, &address)
+        .await
+        .expect("Unable to query receiver")
+        .expect("Receiver was not found");
+    assert_eq!(receiver.nonce, i64::MAX);
+}
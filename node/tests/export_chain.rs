@@ -0,0 +1,64 @@
+mod common;
+
+use common::{fixture, new_epochs, Fixture};
+use libecvrf::KeyPair;
+use node::{
+    epoch::{verify_chain, ChainBundle, ProofFormat},
+    table::CHAIN_PAGE_SIZE,
+};
+
+const RECEIVER_ADDRESS: &str = "0x1111111111111111111111111111111111111111";
+
+#[tokio::test]
+async fn exported_chain_should_verify_offline() {
+    let Fixture { context, receiver } = fixture(RECEIVER_ADDRESS, ProofFormat::Compact).await;
+    let randomness = context.postgres().table_randomness();
+    // The chain spans two pages, the second one is partial
+    let count = CHAIN_PAGE_SIZE as usize + 5;
+    new_epochs(&context, RECEIVER_ADDRESS, count).await;
+
+    let history = context
+        .key_history()
+        .await
+        .expect("Unable to query key history");
+    let mut chunks = randomness.export_chain(history, receiver.id);
+    let mut body = Vec::new();
+    let mut chunk_count = 0;
+    while let Some(chunk) = chunks.recv().await {
+        body.extend_from_slice(&chunk);
+        chunk_count += 1;
+    }
+    // Header, one chunk per page and footer
+    assert_eq!(chunk_count, 4);
+    let bundle: ChainBundle = serde_json::from_slice(&body).expect("Invalid chain bundle");
+    assert_eq!(bundle.epochs.len(), count);
+    assert!(bundle
+        .epochs
+        .iter()
+        .enumerate()
+        .all(|(i, epoch)| epoch.epoch == i as i64));
+    assert_eq!(
+        bundle.public_key,
+        hex::encode(context.keypair().public_key.serialize())
+    );
+    assert_eq!(verify_chain(&bundle), Ok(true));
+
+    // A tampered result breaks the chain
+    let mut tampered = bundle.clone();
+    tampered.epochs[5].y = tampered.epochs[6].y.clone();
+    assert_eq!(verify_chain(&tampered), Ok(false));
+
+    // A missing epoch breaks the chain even if every proof is valid, the first epoch
+    // of the second page links to the last epoch of the first page
+    let mut missing = bundle.clone();
+    missing.epochs.remove(5);
+    assert_eq!(verify_chain(&missing), Ok(false));
+    let mut missing = bundle.clone();
+    missing.epochs.remove(CHAIN_PAGE_SIZE as usize - 1);
+    assert_eq!(verify_chain(&missing), Ok(false));
+
+    // Proofs must belong to the exported public key
+    let mut other_key = bundle;
+    other_key.public_key = hex::encode(KeyPair::new().public_key.serialize());
+    assert_eq!(verify_chain(&other_key), Ok(false));
+}
@@ -0,0 +1,83 @@
+use libecvrf::{extends::ScalarExtend, KeyPair};
+use node::{
+    epoch::genesis_alpha, postgres_sql::Postgres, randomness::Model, rpc::ZERO_ADDRESS,
+    AutoRegister, NodeContext, NodeOptions,
+};
+use serde_json::json;
+use std::sync::Arc;
+
+const OPERATOR_SECRET_KEY: &str =
+    "0x0101010101010101010101010101010101010101010101010101010101010101";
+
+/// Start a fresh node with the given operator key and create its first public epoch
+async fn first_public_epoch(secret_key: &str, network: u64) -> Model {
+    let postgres = Postgres::new_in_memory()
+        .await
+        .expect("Unable to create in-memory database");
+    let keypair = KeyPair::try_from(secret_key).expect("Invalid secret key");
+    let keyring = postgres
+        .table_keyring()
+        .bootstrap_user("orand".to_string(), Some(keypair.secret_key))
+        .await
+        .expect("Unable to bootstrap orand");
+    postgres
+        .table_receiver()
+        .insert(json!({
+            "keyring_id": keyring.id,
+            "name": "orand",
+            "address": ZERO_ADDRESS,
+            "network": network,
+            "nonce": 0,
+        }))
+        .await
+        .expect("Unable to insert public receiver");
+
+    let context = NodeContext::new(
+        keyring.id,
+        keypair,
+        NodeOptions {
+            auto_register: AutoRegister::Never,
+            networks: vec![network],
+            ..Default::default()
+        },
+        postgres,
+    );
+    context
+        .postgres()
+        .table_randomness()
+        .safe_insert(
+            Arc::clone(&context),
+            "orand".to_string(),
+            network,
+            ZERO_ADDRESS.parse().expect("Invalid address"),
+            None,
+        )
+        .await
+        .expect("Unable to create public epoch")
+}
+
+#[tokio::test]
+async fn public_genesis_alpha_should_be_derived_from_operator_key() {
+    let first = first_public_epoch(OPERATOR_SECRET_KEY, 1).await;
+    let second = first_public_epoch(OPERATOR_SECRET_KEY, 1).await;
+    assert_eq!(first.epoch, 0);
+    assert_eq!(second.epoch, 0);
+    assert_eq!(first.alpha, second.alpha);
+    assert_eq!(first.y, second.y);
+
+    let keypair = KeyPair::try_from(OPERATOR_SECRET_KEY).expect("Invalid secret key");
+    assert_eq!(
+        first.alpha,
+        hex::encode(genesis_alpha(&keypair.public_key, 1).b32())
+    );
+
+    // Every network has its own public chain, so does every operator key
+    assert_ne!(
+        genesis_alpha(&keypair.public_key, 1).b32(),
+        genesis_alpha(&keypair.public_key, 56).b32()
+    );
+    assert_ne!(
+        first.alpha,
+        hex::encode(genesis_alpha(&KeyPair::new().public_key, 1).b32())
+    );
+}
@@ -0,0 +1,268 @@
+mod common;
+
+use common::{call, jwt};
+use hyper::StatusCode;
+use libecvrf::KeyPair;
+use node::{
+    epoch::{verify_epoch, Ciphersuite},
+    handler::ORAND_KEYRING_NAME,
+    jwt::MAX_FAILED_VERIFICATIONS,
+    postgres_sql::Postgres,
+    AutoRegister, NodeContext, NodeOptions,
+};
+use serde_json::json;
+use std::sync::Arc;
+
+const RECEIVER_ADDRESS: &str = "0x6666666666666666666666666666666666666666";
+
+async fn new_context() -> (Arc<NodeContext<'static>>, String) {
+    let postgres = Postgres::new_in_memory()
+        .await
+        .expect("Unable to create in-memory database");
+    let orand_record = postgres
+        .table_keyring()
+        .bootstrap_user(ORAND_KEYRING_NAME.to_string(), None)
+        .await
+        .expect("Unable to bootstrap orand");
+    let context = NodeContext::new(
+        orand_record.id,
+        KeyPair::from(orand_record.secret_key.clone()),
+        NodeOptions {
+            auto_register: AutoRegister::Never,
+            networks: vec![1],
+            ..Default::default()
+        },
+        postgres,
+    );
+    (context, orand_record.hmac_secret)
+}
+
+#[tokio::test]
+async fn authorized_user_should_create_new_private_epoch() {
+    let (context, orand_secret) = new_context().await;
+    let admin_jwt = jwt(ORAND_KEYRING_NAME, &orand_secret);
+
+    let (status, user) = call(
+        &context,
+        Some(&admin_jwt),
+        "admin_addUser",
+        json!(["alice"]),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    let user_secret = user["hmac_secret"]
+        .as_str()
+        .expect("HMAC secret is missing")
+        .to_string();
+
+    let (status, _) = call(
+        &context,
+        Some(&admin_jwt),
+        "admin_addReceiver",
+        json!(["alice", RECEIVER_ADDRESS, "1"]),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+
+    let user_jwt = jwt("alice", &user_secret);
+    let (status, epoch) = call(
+        &context,
+        Some(&user_jwt),
+        "orand_newPrivateEpoch",
+        json!(["1", RECEIVER_ADDRESS]),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(epoch["epoch"], json!(0));
+
+    // The returned epoch is stored and proven with the operator key
+    let record = context
+        .postgres()
+        .table_randomness()
+        .find_given_epoch(1, &RECEIVER_ADDRESS.parse().expect("Invalid address"), 0)
+        .await
+        .expect("Unable to query epoch")
+        .expect("Epoch was not found");
+    assert_eq!(epoch["y"], json!(record.y));
+    assert!(verify_epoch(&context.keypair().public_key, &record).expect("Unable to verify epoch"));
+}
+
+#[tokio::test]
+async fn missing_or_invalid_jwt_should_be_rejected() {
+    let (context, orand_secret) = new_context().await;
+    let admin_jwt = jwt(ORAND_KEYRING_NAME, &orand_secret);
+    let (status, user) = call(
+        &context,
+        Some(&admin_jwt),
+        "admin_addUser",
+        json!(["alice"]),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    let (status, _) = call(
+        &context,
+        Some(&admin_jwt),
+        "admin_addReceiver",
+        json!(["alice", RECEIVER_ADDRESS, "1"]),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    let params = json!(["1", RECEIVER_ADDRESS]);
+
+    // No authorization header
+    let (status, _) = call(&context, None, "orand_newPrivateEpoch", params.clone()).await;
+    assert_eq!(status, StatusCode::UNAUTHORIZED);
+
+    // Malformed token
+    let (status, _) = call(
+        &context,
+        Some("not-a-jwt"),
+        "orand_newPrivateEpoch",
+        params.clone(),
+    )
+    .await;
+    assert_eq!(status, StatusCode::UNAUTHORIZED);
+
+    // Signed with another secret
+    let forged_jwt = jwt("alice", &hex::encode([7u8; 32]));
+    let (status, _) = call(
+        &context,
+        Some(&forged_jwt),
+        "orand_newPrivateEpoch",
+        params.clone(),
+    )
+    .await;
+    assert_eq!(status, StatusCode::FORBIDDEN);
+
+    // A valid user can't impersonate the admin
+    let user_secret = user["hmac_secret"]
+        .as_str()
+        .expect("HMAC secret is missing");
+    let impersonated_jwt = jwt(ORAND_KEYRING_NAME, user_secret);
+    let (status, _) = call(
+        &context,
+        Some(&impersonated_jwt),
+        "admin_addUser",
+        json!(["mallory"]),
+    )
+    .await;
+    assert_eq!(status, StatusCode::FORBIDDEN);
+
+    // Nothing was created
+    let latest = context
+        .postgres()
+        .table_randomness()
+        .find_latest_epoch(1, &RECEIVER_ADDRESS.parse().expect("Invalid address"))
+        .await
+        .expect("Unable to query latest epoch");
+    assert!(latest.is_none());
+}
+
+#[tokio::test]
+async fn operator_params_should_be_returned() {
+    let (context, orand_secret) = new_context().await;
+    let admin_jwt = jwt(ORAND_KEYRING_NAME, &orand_secret);
+    let (status, params) = call(&context, Some(&admin_jwt), "orand_getParams", json!([])).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(
+        params["public_key"],
+        json!(hex::encode(
+            context.keypair().public_key.serialize_compressed()
+        ))
+    );
+    assert_eq!(
+        params["ciphersuite"],
+        json!(Ciphersuite::default().as_str())
+    );
+
+    // It requires authorization like every other method
+    let (status, _) = call(&context, None, "orand_getParams", json!([])).await;
+    assert_eq!(status, StatusCode::UNAUTHORIZED);
+}
+
+#[tokio::test]
+async fn receiver_stats_should_be_read_by_owner_or_orand_only() {
+    let (context, orand_secret) = new_context().await;
+    let admin_jwt = jwt(ORAND_KEYRING_NAME, &orand_secret);
+    let mut secrets = Vec::new();
+    for username in ["alice", "bob"] {
+        let (status, user) = call(
+            &context,
+            Some(&admin_jwt),
+            "admin_addUser",
+            json!([username]),
+        )
+        .await;
+        assert_eq!(status, StatusCode::OK);
+        secrets.push(
+            user["hmac_secret"]
+                .as_str()
+                .expect("HMAC secret is missing")
+                .to_string(),
+        );
+    }
+    let (status, _) = call(
+        &context,
+        Some(&admin_jwt),
+        "admin_addReceiver",
+        json!(["alice", RECEIVER_ADDRESS, "1"]),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+
+    // Another user is rejected
+    let bob_jwt = jwt("bob", &secrets[1]);
+    let (status, _) = call(
+        &context,
+        Some(&bob_jwt),
+        "admin_getReceiverStats",
+        json!(["alice"]),
+    )
+    .await;
+    assert_eq!(status, StatusCode::FORBIDDEN);
+
+    // Owner and orand are able to read them
+    let alice_jwt = jwt("alice", &secrets[0]);
+    for token in [&alice_jwt, &admin_jwt] {
+        let (status, stats) = call(
+            &context,
+            Some(token),
+            "admin_getReceiverStats",
+            json!(["alice"]),
+        )
+        .await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(stats[0]["address"], json!(RECEIVER_ADDRESS));
+        assert_eq!(stats[0]["epoch_count"], json!(0));
+    }
+}
+
+#[tokio::test]
+async fn malformed_signature_should_count_toward_lockout() {
+    let (context, orand_secret) = new_context().await;
+    let admin_jwt = jwt(ORAND_KEYRING_NAME, &orand_secret);
+    let (status, _) = call(&context, Some(&admin_jwt), "orand_getNetworks", json!([])).await;
+    assert_eq!(status, StatusCode::OK);
+
+    // Payload is valid, the signature is not base64
+    let parts = admin_jwt.split('.').collect::<Vec<&str>>();
+    let malformed_jwt = format!("{}.{}.!!!", parts[0], parts[1]);
+    for _ in 0..MAX_FAILED_VERIFICATIONS {
+        let (status, _) = call(
+            &context,
+            Some(&malformed_jwt),
+            "orand_getNetworks",
+            json!([]),
+        )
+        .await;
+        assert_eq!(status, StatusCode::FORBIDDEN);
+    }
+
+    // Every malformed token was recorded, orand is locked out even with a valid token
+    let (status, body) = call(&context, Some(&admin_jwt), "orand_getNetworks", json!([])).await;
+    assert_eq!(status, StatusCode::FORBIDDEN);
+    assert!(body["message"]
+        .as_str()
+        .expect("Message is missing")
+        .contains("Too many failed attempts"));
+}
@@ -0,0 +1,151 @@
+mod common;
+
+use common::{fixture, new_epochs, Fixture};
+use libecvrf::helper::get_address;
+use node::{
+    epoch::{verified_epochs_by, verify_chain, verify_epoch, ChainBundle, ProofFormat},
+    ethereum::recover_ethereum_message,
+};
+use serde_json::json;
+use std::sync::Arc;
+
+const RECEIVER_ADDRESS: &str = "0x3333333333333333333333333333333333333333";
+
+#[tokio::test]
+async fn rotated_key_should_keep_old_epochs_verifiable() {
+    let context = fixture(RECEIVER_ADDRESS, ProofFormat::Full).await.context;
+    let old_public_key = context.keypair().public_key;
+
+    new_epochs(&context, RECEIVER_ADDRESS, 3).await;
+    let rotation = context
+        .rotate_operator_key()
+        .await
+        .expect("Unable to rotate operator key");
+    let new_public_key = context.keypair().public_key;
+    assert_ne!(new_public_key, old_public_key);
+    assert_eq!(
+        rotation.old_public_key,
+        hex::encode(old_public_key.serialize())
+    );
+    assert_eq!(
+        rotation.new_public_key,
+        hex::encode(new_public_key.serialize())
+    );
+    new_epochs(&context, RECEIVER_ADDRESS, 2).await;
+
+    // Keyring holds the new key, the node loads it on restart
+    let stored = context
+        .postgres()
+        .table_keyring()
+        .find_by_name("orand".to_string())
+        .await
+        .expect("Unable to query keyring")
+        .expect("Keyring was not found");
+    assert_eq!(stored.public_key, rotation.new_public_key);
+
+    let mut epochs = context
+        .postgres()
+        .table_randomness()
+        .find_recent_epoch(1, &RECEIVER_ADDRESS.parse().expect("Invalid address"), 5)
+        .await
+        .expect("Unable to query epochs");
+    epochs.reverse();
+    assert_eq!(epochs.len(), 5);
+    assert_eq!(rotation.last_randomness_id, epochs[2].id);
+
+    let history = context
+        .key_history()
+        .await
+        .expect("Unable to query key history");
+    assert_eq!(history.current(), new_public_key);
+    for (i, epoch) in epochs.iter().enumerate() {
+        let (proven_with, other) = if i < 3 {
+            (old_public_key, new_public_key)
+        } else {
+            (new_public_key, old_public_key)
+        };
+        assert_eq!(history.public_key_for(epoch.id), proven_with);
+        assert!(verify_epoch(&proven_with, epoch).expect("Unable to verify epoch"));
+        assert!(!verify_epoch(&other, epoch).unwrap_or(false));
+        // The chain continues across the rotation
+        if i > 0 {
+            assert_eq!(epoch.alpha, epochs[i - 1].y);
+        }
+    }
+
+    let verified = verified_epochs_by(&epochs, |record| history.public_key_for(record.id))
+        .expect("Unable to verify epochs");
+    assert!(verified
+        .iter()
+        .all(|epoch| epoch["verified"] == json!(true)));
+}
+
+#[tokio::test]
+async fn rotated_key_should_export_and_resign_with_the_key_of_each_epoch() {
+    let Fixture { context, receiver } = fixture(RECEIVER_ADDRESS, ProofFormat::Full).await;
+    let old_public_key = context.keypair().public_key;
+
+    new_epochs(&context, RECEIVER_ADDRESS, 3).await;
+    context
+        .rotate_operator_key()
+        .await
+        .expect("Unable to rotate operator key");
+    let new_public_key = context.keypair().public_key;
+    new_epochs(&context, RECEIVER_ADDRESS, 2).await;
+
+    // Epochs before the rotation carry the retired key, the chain verifies as a whole
+    let randomness = context.postgres().table_randomness();
+    let history = context
+        .key_history()
+        .await
+        .expect("Unable to query key history");
+    let mut chunks = randomness.export_chain(history, receiver.id);
+    let mut body = Vec::new();
+    while let Some(chunk) = chunks.recv().await {
+        body.extend_from_slice(&chunk);
+    }
+    let bundle: ChainBundle = serde_json::from_slice(&body).expect("Invalid chain bundle");
+    assert_eq!(bundle.public_key, hex::encode(new_public_key.serialize()));
+    assert_eq!(bundle.epochs.len(), 5);
+    for (i, epoch) in bundle.epochs.iter().enumerate() {
+        let expected = (i < 3).then(|| hex::encode(old_public_key.serialize()));
+        assert_eq!(epoch.public_key, expected);
+    }
+    assert_eq!(verify_chain(&bundle), Ok(true));
+
+    // Without the retired key the epochs before the rotation don't verify
+    let mut current_key_only = bundle.clone();
+    current_key_only
+        .epochs
+        .iter_mut()
+        .for_each(|epoch| epoch.public_key = None);
+    assert_eq!(verify_chain(&current_key_only), Ok(false));
+
+    // Only epochs proven with the current key are able to be re-signed
+    let receiver_address = RECEIVER_ADDRESS.parse().expect("Invalid address");
+    assert!(randomness
+        .resign_epoch(
+            Arc::clone(&context),
+            1,
+            &receiver_address,
+            bundle.epochs[2].epoch,
+            5
+        )
+        .await
+        .is_err());
+    let resigned = randomness
+        .resign_epoch(
+            Arc::clone(&context),
+            1,
+            &receiver_address,
+            bundle.epochs[3].epoch,
+            5,
+        )
+        .await
+        .expect("Unable to re-sign epoch");
+    let signature_proof =
+        hex::decode(&resigned.signature_proof).expect("Unable to decode signature proof");
+    let (address, _) =
+        recover_ethereum_message(&signature_proof).expect("Unable to recover signer");
+    assert_eq!(address.as_bytes(), &get_address(&new_public_key));
+}
@@ -0,0 +1,144 @@
+mod common;
+
+use common::{fixture, new_epochs};
+use node::{
+    epoch::ProofFormat,
+    merkle::{merkle_root, verify_membership},
+    randomness, NodeContext,
+};
+use sea_orm::ActiveValue;
+use std::sync::Arc;
+
+const RECEIVER_ADDRESS: &str = "0x4444444444444444444444444444444444444444";
+
+async fn context_with_epochs(count: usize) -> Arc<NodeContext<'static>> {
+    let context = fixture(RECEIVER_ADDRESS, ProofFormat::Full).await.context;
+    new_epochs(&context, RECEIVER_ADDRESS, count).await;
+    context
+}
+
+#[tokio::test]
+async fn range_commitment_should_be_deterministic_and_bound_to_every_result() {
+    let context = context_with_epochs(6).await;
+    let randomness = context.postgres().table_randomness();
+    let address = RECEIVER_ADDRESS.parse().expect("Invalid address");
+
+    let commitment = randomness
+        .range_commitment(1, &address, 1, 4)
+        .await
+        .expect("Unable to commit to epoch range");
+    assert_eq!(
+        randomness
+            .range_commitment(1, &address, 1, 4)
+            .await
+            .expect("Unable to commit to epoch range"),
+        commitment
+    );
+
+    // It's the Merkle root of y of the epochs in order
+    let mut ys = Vec::new();
+    for epoch in 1..=4 {
+        let record = randomness
+            .find_given_epoch(1, &address, epoch)
+            .await
+            .expect("Unable to query epoch")
+            .expect("Epoch was not found");
+        let mut y = [0u8; 32];
+        hex::decode_to_slice(&record.y, &mut y).expect("Invalid y");
+        ys.push(y);
+    }
+    assert_eq!(merkle_root(&ys), Some(commitment));
+
+    // Any other range has another commitment
+    assert_ne!(
+        randomness
+            .range_commitment(1, &address, 0, 4)
+            .await
+            .expect("Unable to commit to epoch range"),
+        commitment
+    );
+
+    // Changing a single y changes the commitment
+    let record = randomness
+        .find_given_epoch(1, &address, 3)
+        .await
+        .expect("Unable to query epoch")
+        .expect("Epoch was not found");
+    let mut active_model = randomness::ActiveModel::from(record);
+    active_model.y = ActiveValue::Set("00".repeat(32));
+    randomness
+        .update(active_model)
+        .await
+        .expect("Unable to update epoch");
+    assert_ne!(
+        randomness
+            .range_commitment(1, &address, 1, 4)
+            .await
+            .expect("Unable to commit to epoch range"),
+        commitment
+    );
+}
+
+#[tokio::test]
+async fn range_commitment_should_reject_invalid_range() {
+    let context = context_with_epochs(3).await;
+    let randomness = context.postgres().table_randomness();
+    let address = RECEIVER_ADDRESS.parse().expect("Invalid address");
+
+    assert!(randomness
+        .range_commitment(1, &address, 2, 1)
+        .await
+        .is_err());
+    assert!(randomness
+        .range_commitment(1, &address, -1, 1)
+        .await
+        .is_err());
+    // Epoch 3 doesn't exist yet
+    assert!(randomness
+        .range_commitment(1, &address, 0, 3)
+        .await
+        .is_err());
+    assert!(randomness
+        .range_commitment(56, &address, 0, 2)
+        .await
+        .is_err());
+}
+
+#[tokio::test]
+async fn membership_proof_should_verify_against_range_commitment() {
+    let context = context_with_epochs(7).await;
+    let randomness = context.postgres().table_randomness();
+    let address = RECEIVER_ADDRESS.parse().expect("Invalid address");
+    let root = randomness
+        .range_commitment(1, &address, 1, 5)
+        .await
+        .expect("Unable to commit to epoch range");
+
+    for epoch in 1..=5 {
+        let (y, path) = randomness
+            .range_membership_proof(1, &address, 1, 5, epoch)
+            .await
+            .expect("Unable to prove membership");
+        let record = randomness
+            .find_given_epoch(1, &address, epoch)
+            .await
+            .expect("Unable to query epoch")
+            .expect("Epoch was not found");
+        assert_eq!(hex::encode(y), record.y);
+        let index = (epoch - 1) as usize;
+        assert!(verify_membership(&root, &y, index, &path));
+
+        // Wrong y
+        let mut wrong_y = y;
+        wrong_y[0] ^= 1;
+        assert!(!verify_membership(&root, &wrong_y, index, &path));
+    }
+
+    // Epochs outside of the committed range
+    for epoch in [0, 6] {
+        assert!(randomness
+            .range_membership_proof(1, &address, 1, 5, epoch)
+            .await
+            .is_err());
+    }
+}
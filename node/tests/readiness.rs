@@ -0,0 +1,62 @@
+mod common;
+
+use bytes::Bytes;
+use common::node_context;
+use http_body_util::Empty;
+use hyper::{Method, Request, StatusCode};
+use libecvrf::KeyPair;
+use migration::{Migrator, MigratorTrait};
+use node::{epoch::ProofFormat, handler::orand, postgres_sql::Postgres};
+use sea_orm::{ConnectOptions, Database};
+
+/// In-memory database with the first `steps` migrations applied, `None` applies all
+async fn migrated_database(steps: Option<u32>) -> Postgres {
+    // A single connection keeps the same in-memory database for the whole test
+    let mut options = ConnectOptions::new("sqlite::memory:");
+    options.max_connections(1).min_connections(1);
+    let connection = Database::connect(options)
+        .await
+        .expect("Unable to create in-memory database");
+    Migrator::up(&connection, steps)
+        .await
+        .expect("Unable to run migrations");
+    Postgres::from_connection(connection)
+}
+
+/// Status of the readiness probe of a node on top of the database
+async fn ready_status(postgres: Postgres) -> StatusCode {
+    let context = node_context(1, KeyPair::new(), ProofFormat::Full, postgres);
+    let request = Request::builder()
+        .method(Method::GET)
+        .uri("/ready")
+        .body(Empty::<Bytes>::new())
+        .expect("Unable to build request");
+    orand(request, context)
+        .await
+        .expect("Unable to handle request")
+        .status()
+}
+
+#[tokio::test]
+async fn migrated_database_should_be_ready() {
+    let postgres = migrated_database(None).await;
+    assert!(postgres
+        .migrations_current()
+        .await
+        .expect("Unable to read migrations"));
+    assert_eq!(ready_status(postgres).await, StatusCode::OK);
+}
+
+#[tokio::test]
+async fn pending_migration_should_not_be_ready() {
+    let n = Migrator::migrations().len() as u32;
+    let postgres = migrated_database(Some(n - 1)).await;
+    assert!(!postgres
+        .migrations_current()
+        .await
+        .expect("Unable to read migrations"));
+    assert_eq!(
+        ready_status(postgres).await,
+        StatusCode::SERVICE_UNAVAILABLE
+    );
+}
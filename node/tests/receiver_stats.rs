@@ -0,0 +1,86 @@
+mod common;
+
+use common::{insert_operator, insert_receiver, randomness_record};
+use libecvrf::KeyPair;
+use node::{postgres_sql::Postgres, table::ReceiverStats};
+
+const FIRST_RECEIVER_ADDRESS: &str = "0x1111111111111111111111111111111111111111";
+const SECOND_RECEIVER_ADDRESS: &str = "0x2222222222222222222222222222222222222222";
+const IDLE_RECEIVER_ADDRESS: &str = "0x3333333333333333333333333333333333333333";
+
+#[tokio::test]
+async fn stats_should_count_epochs_and_find_latest_epoch_of_each_receiver() {
+    let postgres = Postgres::new_in_memory()
+        .await
+        .expect("Unable to create in-memory database");
+    let keyring = insert_operator(&postgres, &KeyPair::new()).await;
+    let first = insert_receiver(&postgres, keyring.id, FIRST_RECEIVER_ADDRESS, 3).await;
+    let second = insert_receiver(&postgres, keyring.id, SECOND_RECEIVER_ADDRESS, 5).await;
+    let idle = insert_receiver(&postgres, keyring.id, IDLE_RECEIVER_ADDRESS, 0).await;
+
+    // First receiver has epoch 0, 1 and 2, second one has epoch 3 and 4 only
+    let randomness = postgres.table_randomness();
+    for (seed, (receiver_id, epoch)) in [
+        (first.id, 0),
+        (first.id, 1),
+        (second.id, 3),
+        (first.id, 2),
+        (second.id, 4),
+    ]
+    .into_iter()
+    .enumerate()
+    {
+        randomness
+            .insert(randomness_record(
+                keyring.id,
+                receiver_id,
+                epoch,
+                seed as u8 * 2 + 1,
+            ))
+            .await
+            .expect("Unable to insert epoch");
+    }
+
+    let stats = postgres
+        .table_receiver()
+        .stats("orand".to_string())
+        .await
+        .expect("Unable to query receiver stats");
+    assert_eq!(
+        stats,
+        vec![
+            ReceiverStats {
+                id: first.id,
+                address: FIRST_RECEIVER_ADDRESS.to_string(),
+                network: 1,
+                nonce: 3,
+                epoch_count: 3,
+                latest_epoch: Some(2),
+            },
+            ReceiverStats {
+                id: second.id,
+                address: SECOND_RECEIVER_ADDRESS.to_string(),
+                network: 1,
+                nonce: 5,
+                epoch_count: 2,
+                latest_epoch: Some(4),
+            },
+            ReceiverStats {
+                id: idle.id,
+                address: IDLE_RECEIVER_ADDRESS.to_string(),
+                network: 1,
+                nonce: 0,
+                epoch_count: 0,
+                latest_epoch: None,
+            },
+        ]
+    );
+
+    // Receivers of another user are not reported
+    assert!(postgres
+        .table_receiver()
+        .stats("chiro".to_string())
+        .await
+        .expect("Unable to query receiver stats")
+        .is_empty());
+}
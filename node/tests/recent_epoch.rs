@@ -0,0 +1,64 @@
+mod common;
+
+use common::{insert_operator, insert_receiver, randomness_record, NETWORK};
+use libecvrf::KeyPair;
+use node::{
+    ethereum::EthAddress,
+    postgres_sql::Postgres,
+    table::{DEFAULT_RECENT_EPOCHS, MAX_RECENT_EPOCHS},
+};
+
+#[tokio::test]
+async fn recent_epoch_count_should_be_clamped() {
+    let postgres = Postgres::new_in_memory()
+        .await
+        .expect("Unable to create in-memory database");
+    let keyring = insert_operator(&postgres, &KeyPair::new()).await;
+    let address = "0x0000000000000000000000000000000000000001"
+        .parse::<EthAddress>()
+        .expect("Unable to parse address");
+    let receiver = insert_receiver(&postgres, keyring.id, &address.to_string(), 0).await;
+
+    let randomness = postgres.table_randomness();
+    let total = MAX_RECENT_EPOCHS as i64 + 10;
+    for epoch in 0..total {
+        randomness
+            .insert(randomness_record(
+                keyring.id,
+                receiver.id,
+                epoch,
+                epoch as u8,
+            ))
+            .await
+            .expect("Unable to insert epoch");
+    }
+
+    let one = randomness
+        .find_recent_epoch(NETWORK, &address, 1)
+        .await
+        .expect("Unable to find recent epochs");
+    assert_eq!(one.len(), 1);
+    assert_eq!(one[0].epoch, total - 1);
+
+    let default = randomness
+        .find_recent_epoch(NETWORK, &address, DEFAULT_RECENT_EPOCHS)
+        .await
+        .expect("Unable to find recent epochs");
+    assert_eq!(default.len(), DEFAULT_RECENT_EPOCHS as usize);
+    assert_eq!(
+        default.last().expect("Epochs are not empty").epoch,
+        total - 20
+    );
+
+    // Over the max is clamped, zero still returns the latest epoch
+    let clamped = randomness
+        .find_recent_epoch(NETWORK, &address, 1000)
+        .await
+        .expect("Unable to find recent epochs");
+    assert_eq!(clamped.len(), MAX_RECENT_EPOCHS as usize);
+    let zero = randomness
+        .find_recent_epoch(NETWORK, &address, 0)
+        .await
+        .expect("Unable to find recent epochs");
+    assert_eq!(zero.len(), 1);
+}
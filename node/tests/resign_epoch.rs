@@ -0,0 +1,70 @@
+mod common;
+
+use common::fixture;
+use libecvrf::helper::get_address;
+use node::{
+    epoch::{decode_contract_proof, ProofFormat},
+    ethereum::{compose_operator_proof, ecvrf_proof_digest, recover_ethereum_message, EthAddress},
+};
+use std::sync::Arc;
+
+const RECEIVER_ADDRESS: &str = "0x1111111111111111111111111111111111111111";
+
+#[tokio::test]
+async fn resigned_epoch_should_recover_to_operator_with_corrected_nonce() {
+    let context = fixture(RECEIVER_ADDRESS, ProofFormat::Full).await.context;
+    let public_key = context.keypair().public_key;
+    let receiver_address: EthAddress = RECEIVER_ADDRESS.parse().expect("Invalid address");
+    let randomness = context.postgres().table_randomness();
+    let record = randomness
+        .safe_insert(
+            Arc::clone(&context),
+            "orand".to_string(),
+            1,
+            receiver_address,
+            None,
+        )
+        .await
+        .expect("Unable to create new epoch");
+
+    let corrected_nonce = 5;
+    let resigned = randomness
+        .resign_epoch(
+            Arc::clone(&context),
+            1,
+            &receiver_address,
+            record.epoch,
+            corrected_nonce,
+        )
+        .await
+        .expect("Unable to re-sign epoch");
+    assert_ne!(resigned.signature_proof, record.signature_proof);
+    assert_eq!(resigned.y, record.y);
+
+    let contract_proof =
+        decode_contract_proof(&public_key, &resigned).expect("Unable to decode stored proof");
+    let expected_message = compose_operator_proof(
+        corrected_nonce,
+        &receiver_address,
+        &ecvrf_proof_digest(&contract_proof),
+    );
+
+    let signature_proof =
+        hex::decode(&resigned.signature_proof).expect("Unable to decode signature proof");
+    let (address, message) =
+        recover_ethereum_message(&signature_proof).expect("Unable to recover signer");
+    assert_eq!(address.as_bytes(), &get_address(&public_key));
+    assert_eq!(message, expected_message);
+
+    // Missing epoch is reported as not found
+    assert!(randomness
+        .resign_epoch(
+            Arc::clone(&context),
+            1,
+            &receiver_address,
+            10,
+            corrected_nonce
+        )
+        .await
+        .is_err());
+}
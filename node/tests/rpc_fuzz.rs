@@ -0,0 +1,174 @@
+mod common;
+
+use bytes::Bytes;
+use common::fixture;
+use http_body_util::Full;
+use hyper::{Method, Request, StatusCode};
+use node::{epoch::ProofFormat, handler::orand, rpc::JSONRPCMethod, NodeContext};
+use std::{panic::catch_unwind, sync::Arc};
+
+const RECEIVER_ADDRESS: &str = "0x0000000000000000000000000000000000000001";
+
+/// Valid and malformed payloads that mutations start from
+const SEED_CORPUS: [&str; 25] = [
+    r#"{"method":"orand_getPublicEpoch","params":["1","7"]}"#,
+    r#"{"method":"orand_getPrivateEpoch","params":["1","0x0000000000000000000000000000000000000001","9223372036854775807","5","true"]}"#,
+    r#"{"method":"orand_verifyPublicEpoch","params":["56","0"]}"#,
+    r#"{"method":"orand_verifyPrivateEpoch","params":["56","0x0000000000000000000000000000000000000001","3"]}"#,
+    r#"{"method":"orand_exportChain","params":["1","0x0000000000000000000000000000000000000001"]}"#,
+    r#"{"method":"orand_newPublicEpoch","params":["1","request-1"]}"#,
+    r#"{"method":"orand_newPrivateEpoch","params":["1","0x0000000000000000000000000000000000000001"]}"#,
+    r#"{"method":"orand_getPublicKey","params":["orand"]}"#,
+    r#"{"method":"orand_getNetworks","params":[]}"#,
+    r#"{"method":"orand_getParams","params":[]}"#,
+    r#"{"method":"orand_getKeyHistory","params":["orand"]}"#,
+    r#"{"method":"admin_addReceiver","params":["orand","0x0000000000000000000000000000000000000001","1"]}"#,
+    r#"{"method":"admin_resignEpoch","params":["1","0x0000000000000000000000000000000000000001","2","3"]}"#,
+    // Missing parameters
+    r#"{"method":"orand_getPublicEpoch","params":[]}"#,
+    r#"{"method":"admin_resignEpoch","params":["1"]}"#,
+    r#"{"method":"orand_getPublicKey","params":[]}"#,
+    // Malformed parameters
+    r#"{"method":"orand_getPublicEpoch","params":["1","12abc"]}"#,
+    r#"{"method":"orand_getPublicEpoch","params":["1","99999999999999999999"]}"#,
+    r#"{"method":"orand_newPublicEpoch","params":["1","not a request id!"]}"#,
+    r#"{"method":"orand_getPublicKey","params":["Not-A-Name"]}"#,
+    r#"{"method":"orand_newPrivateEpoch","params":["1","0xé00000000000000000000000000000000000001"]}"#,
+    // Malformed JSON
+    r#"{"method":"orand_getPublicEpoch","params":[1,7]}"#,
+    r#"{"method":"orand_getPublicEpoch"}"#,
+    r#"{"method":"#,
+    "",
+];
+
+/// Tokens that are spliced into payloads to reach deeper into the decoders
+const DICTIONARY: [&str; 8] = [
+    "\"",
+    ",",
+    "[",
+    "]",
+    "0x",
+    "-1",
+    "18446744073709551616",
+    "\u{1F600}",
+];
+
+const ITERATIONS: usize = 20_000;
+
+/// xorshift64, the fuzz run is reproducible
+struct Rng(u64);
+
+impl Rng {
+    fn next(&mut self) -> u64 {
+        self.0 ^= self.0 << 13;
+        self.0 ^= self.0 >> 7;
+        self.0 ^= self.0 << 17;
+        self.0
+    }
+
+    fn below(&mut self, bound: usize) -> usize {
+        (self.next() % bound.max(1) as u64) as usize
+    }
+}
+
+fn mutate(rng: &mut Rng, input: &[u8]) -> Vec<u8> {
+    let mut output = input.to_vec();
+    for _ in 0..=rng.below(4) {
+        let position = rng.below(output.len() + 1);
+        match rng.below(5) {
+            // Flip a byte
+            0 if !output.is_empty() => {
+                let i = rng.below(output.len());
+                output[i] ^= 1 << rng.below(8);
+            }
+            // Insert a random byte
+            1 => output.insert(position, rng.next() as u8),
+            // Truncate
+            2 => output.truncate(position),
+            // Remove a byte
+            3 if position < output.len() => {
+                output.remove(position);
+            }
+            // Splice a token
+            _ => {
+                let token = DICTIONARY[rng.below(DICTIONARY.len())].as_bytes();
+                output.splice(position..position, token.iter().copied());
+            }
+        }
+    }
+    output
+}
+
+fn parse_should_not_panic(input: &[u8]) {
+    let payload = String::from_utf8_lossy(input).to_string();
+    let result = catch_unwind(|| JSONRPCMethod::from_json_string(&payload).is_ok());
+    assert!(result.is_ok(), "Parser panicked on input: {:?}", payload);
+}
+
+/// Send the raw bytes as the body of a JSON RPC request, the handler must answer
+/// with a response, invalid utf8 included
+async fn handle_should_not_panic(
+    context: &Arc<NodeContext<'static>>,
+    input: Vec<u8>,
+) -> StatusCode {
+    let request = Request::builder()
+        .method(Method::POST)
+        .uri("/")
+        .body(Full::new(Bytes::from(input.clone())))
+        .expect("Unable to build request");
+    match tokio::spawn(orand(request, Arc::clone(context))).await {
+        Ok(response) => response.expect("Unable to handle request").status(),
+        Err(err) => panic!("Handler panicked on input {:?}: {}", input, err),
+    }
+}
+
+#[test]
+fn seed_corpus_should_not_panic() {
+    for seed in SEED_CORPUS {
+        parse_should_not_panic(seed.as_bytes());
+    }
+    // Valid payloads of the corpus are still decoded
+    for seed in &SEED_CORPUS[..12] {
+        assert!(JSONRPCMethod::from_json_string(seed).is_ok(), "{}", seed);
+    }
+    for seed in &SEED_CORPUS[12..] {
+        assert!(JSONRPCMethod::from_json_string(seed).is_err(), "{}", seed);
+    }
+}
+
+#[tokio::test]
+async fn invalid_utf8_body_should_be_bad_request() {
+    let context = fixture(RECEIVER_ADDRESS, ProofFormat::Full).await.context;
+    let mut input = SEED_CORPUS[0].as_bytes().to_vec();
+    input.insert(input.len() / 2, 0xff);
+    assert_eq!(
+        handle_should_not_panic(&context, input).await,
+        StatusCode::BAD_REQUEST
+    );
+    assert_eq!(
+        handle_should_not_panic(&context, vec![0xc3, 0x28]).await,
+        StatusCode::BAD_REQUEST
+    );
+}
+
+#[tokio::test]
+async fn mutated_payloads_should_not_panic() {
+    let context = fixture(RECEIVER_ADDRESS, ProofFormat::Full).await.context;
+    let mut rng = Rng(0x5eed_0f_0a_4a_4d);
+    for _ in 0..ITERATIONS {
+        let seed = SEED_CORPUS[rng.below(SEED_CORPUS.len())].as_bytes();
+        handle_should_not_panic(&context, mutate(&mut rng, seed)).await;
+    }
+}
+
+#[tokio::test]
+async fn arbitrary_bytes_should_not_panic() {
+    let context = fixture(RECEIVER_ADDRESS, ProofFormat::Full).await.context;
+    let mut rng = Rng(0x0bad_c0de);
+    for _ in 0..ITERATIONS {
+        let input = (0..rng.below(256))
+            .map(|_| rng.next() as u8)
+            .collect::<Vec<u8>>();
+        handle_should_not_panic(&context, input).await;
+    }
+}
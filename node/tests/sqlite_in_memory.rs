@@ -0,0 +1,65 @@
+use libecvrf::{KeyPair, RawKeyPair, Zeroable};
+use node::postgres_sql::Postgres;
+use serde_json::json;
+
+#[tokio::test]
+async fn in_memory_database_should_run_migrations_and_store_keyring() {
+    let postgres = Postgres::new(":memory:".to_string()).await;
+    postgres
+        .ping()
+        .await
+        .expect("In-memory database must be reachable");
+    assert!(postgres
+        .migrations_current()
+        .await
+        .expect("Unable to read migrations"));
+
+    let keyring = postgres.table_keyring();
+    let mut raw_keypair = RawKeyPair::from(&KeyPair::new());
+    let public_key = hex::encode(raw_keypair.public_key);
+    let inserted = keyring
+        .insert(json!({
+            "username": "orand",
+            "hmac_secret": hex::encode([7u8; 32]),
+            "public_key": public_key,
+            "secret_key": hex::encode(raw_keypair.secret_key)}))
+        .await
+        .expect("Unable to insert new key to keyring table");
+    raw_keypair.zeroize();
+
+    let found = keyring
+        .find_by_name("orand".to_string())
+        .await
+        .expect("Unable to query keyring table")
+        .expect("Keyring record must exist");
+    assert_eq!(found, inserted);
+    assert_eq!(found.public_key, public_key);
+    assert!(keyring
+        .find_by_name("unknown".to_string())
+        .await
+        .expect("Unable to query keyring table")
+        .is_none());
+}
+
+#[tokio::test]
+async fn sqlite_memory_url_should_open_isolated_database() {
+    let first = Postgres::new("sqlite::memory:".to_string()).await;
+    let second = Postgres::new_in_memory()
+        .await
+        .expect("Unable to create in-memory database");
+    first
+        .table_keyring()
+        .insert(json!({
+            "username": "orand",
+            "hmac_secret": hex::encode([1u8; 32]),
+            "public_key": hex::encode([2u8; 65]),
+            "secret_key": hex::encode([3u8; 32])}))
+        .await
+        .expect("Unable to insert new key to keyring table");
+    assert!(second
+        .table_keyring()
+        .find_all()
+        .await
+        .expect("Unable to query keyring table")
+        .is_empty());
+}
@@ -0,0 +1,40 @@
+mod common;
+
+use common::{insert_operator, insert_receiver, randomness_record};
+use libecvrf::KeyPair;
+use node::{postgres_sql::Postgres, table::is_unique_violation, ErrorCode};
+
+#[tokio::test]
+async fn duplicated_receiver_epoch_should_be_rejected() {
+    let postgres = Postgres::new_in_memory()
+        .await
+        .expect("Unable to create in-memory database");
+    let keyring = insert_operator(&postgres, &KeyPair::new()).await;
+    let receiver = insert_receiver(
+        &postgres,
+        keyring.id,
+        "0x0000000000000000000000000000000000000001",
+        0,
+    )
+    .await;
+
+    let randomness = postgres.table_randomness();
+    randomness
+        .insert(randomness_record(keyring.id, receiver.id, 0, 1))
+        .await
+        .expect("First epoch must be inserted");
+
+    // Same receiver and epoch, every other unique column differs
+    let err = randomness
+        .insert(randomness_record(keyring.id, receiver.id, 0, 10))
+        .await
+        .expect_err("Duplicated epoch must be rejected");
+    assert!(is_unique_violation(&err));
+    assert_eq!(node::Error::from(err).code(), ErrorCode::Conflict);
+
+    // The next epoch is still accepted
+    randomness
+        .insert(randomness_record(keyring.id, receiver.id, 1, 20))
+        .await
+        .expect("Next epoch must be inserted");
+}
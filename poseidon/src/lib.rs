@@ -6,3 +6,5 @@ pub mod gadgets;
 pub mod poseidon_constants;
 /// The implelentation of Poseidon hash
 pub mod poseidon_hash;
+/// Poseidon parameters loaded at runtime
+pub mod runtime_spec;
@@ -189,10 +189,29 @@ pub(crate) fn permute<F: Field + PrimeField, S: Spec<F, T, R>, const T: usize, c
     state: &mut State<F, T>,
     mds: &[[F; T]; T],
     round_constants: &[[F; T]],
+) {
+    permute_with(
+        state,
+        mds,
+        round_constants,
+        S::full_rounds(),
+        S::partial_rounds(),
+        S::sbox,
+    );
+}
+
+/// Runs the Poseidon permutation with parameters given at runtime instead of a [Spec].
+pub(crate) fn permute_with<F: Field + PrimeField, const T: usize>(
+    state: &mut State<F, T>,
+    mds: &[[F; T]; T],
+    round_constants: &[[F; T]],
+    full_rounds: usize,
+    partial_rounds: usize,
+    sbox: impl Fn(F) -> F,
 ) {
     // The number of full rounds and partial rounds, respectively
-    let r_f = S::full_rounds() / 2;
-    let r_p = S::partial_rounds();
+    let r_f = full_rounds / 2;
+    let r_p = partial_rounds;
 
     // Multiply the state by mds
     let mix_layer = |state: &mut State<F, T>| {
@@ -212,7 +231,7 @@ pub(crate) fn permute<F: Field + PrimeField, S: Spec<F, T, R>, const T: usize, c
         }
         // perform sbox
         for word in state.iter_mut() {
-            *word = S::sbox(*word);
+            *word = sbox(*word);
         }
         // multiply by mds
         mix_layer(state);
@@ -224,7 +243,7 @@ pub(crate) fn permute<F: Field + PrimeField, S: Spec<F, T, R>, const T: usize, c
             *word += rc;
         }
         // In a partial round, the S-box is only applied to the first state word.
-        state[0] = S::sbox(state[0]);
+        state[0] = sbox(state[0]);
         // multiply by mds
         mix_layer(state);
     };
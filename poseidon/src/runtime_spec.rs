@@ -0,0 +1,316 @@
+//! Poseidon parameters loaded at runtime. [Spec] only exposes its parameters through
+//! associated functions, so a parameter set has to be compiled in. [RuntimeSpec] holds the
+//! same parameters as values, it can be parsed from a bytes blob and used to hash directly
+//! without recompiling for each parameter set.
+//!
+//! [RuntimeSpec] does not implement [Spec] on purpose, the associated functions of [Spec]
+//! have no receiver and a generic type can't own a static to read the loaded parameters
+//! from. It can't be used with [Hash](crate::poseidon_hash::Hash) or the circuit, its
+//! [RuntimeSpec::hash] is the [ConstantLength] sponge of `Hash` over [RuntimeSpec::permute]
+//! and the tests check that both give the same digest.
+
+extern crate alloc;
+use crate::poseidon_hash::{permute_with, ConstantLength, Domain, Mtrx, Spec};
+use alloc::vec::Vec;
+use core::fmt::{Display, Formatter};
+use ff::{Field, PrimeField};
+
+/// Size of the header of the bytes blob: full rounds (u32), partial rounds (u32) and
+/// the S-box exponent (u64), all in little endian
+const HEADER_SIZE: usize = 16;
+
+/// Errors of loading a [RuntimeSpec]
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub enum RuntimeSpecError {
+    /// The bytes blob is shorter or longer than the parameters it describes
+    InvalidLength,
+    /// A field element is not canonically encoded
+    InvalidFieldElement,
+    /// The rounds or the width and rate do not match the given constants
+    InvalidDimensions,
+    /// MDS matrix is not invertible or MDS inverse does not match it
+    NonInvertibleMds,
+    /// The S-box exponent is zero, `x^0` is constant
+    InvalidSbox,
+}
+
+impl Display for RuntimeSpecError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
+        match self {
+            RuntimeSpecError::InvalidLength => write!(f, "Invalid length"),
+            RuntimeSpecError::InvalidFieldElement => write!(f, "Invalid field element"),
+            RuntimeSpecError::InvalidDimensions => write!(f, "Invalid dimensions"),
+            RuntimeSpecError::NonInvertibleMds => write!(f, "Non invertible MDS"),
+            RuntimeSpecError::InvalidSbox => write!(f, "Invalid S-box"),
+        }
+    }
+}
+
+/// Poseidon parameters supplied at runtime, the S-box is `x^alpha`
+#[derive(Clone, Debug)]
+pub struct RuntimeSpec<F: Field + PrimeField, const T: usize, const R: usize> {
+    full_rounds: usize,
+    partial_rounds: usize,
+    alpha: u64,
+    round_constants: Vec<[F; T]>,
+    mds: Mtrx<F, T>,
+    mds_inv: Mtrx<F, T>,
+}
+
+impl<F: Field + PrimeField, const T: usize, const R: usize> RuntimeSpec<F, T, R> {
+    /// Create a new runtime spec, the dimensions, the S-box and the MDS matrix are validated
+    pub fn new(
+        full_rounds: usize,
+        partial_rounds: usize,
+        alpha: u64,
+        round_constants: Vec<[F; T]>,
+        mds: Mtrx<F, T>,
+        mds_inv: Mtrx<F, T>,
+    ) -> Result<Self, RuntimeSpecError> {
+        if R == 0 || R >= T || full_rounds % 2 != 0 {
+            return Err(RuntimeSpecError::InvalidDimensions);
+        }
+        if round_constants.len() != full_rounds + partial_rounds {
+            return Err(RuntimeSpecError::InvalidDimensions);
+        }
+        if alpha == 0 {
+            return Err(RuntimeSpecError::InvalidSbox);
+        }
+        // MDS * MDS^-1 must be the identity matrix
+        for (i, row) in mds.iter().enumerate() {
+            for j in 0..T {
+                let mut acc = F::ZERO;
+                for (k, value) in row.iter().enumerate() {
+                    acc += *value * mds_inv[k][j];
+                }
+                let expected = if i == j { F::ONE } else { F::ZERO };
+                if acc != expected {
+                    return Err(RuntimeSpecError::NonInvertibleMds);
+                }
+            }
+        }
+        Ok(Self {
+            full_rounds,
+            partial_rounds,
+            alpha,
+            round_constants,
+            mds,
+            mds_inv,
+        })
+    }
+
+    /// Copy the parameters of a compiled [Spec], `alpha` is the exponent of its S-box
+    pub fn from_spec<S: Spec<F, T, R>>(alpha: u64) -> Result<Self, RuntimeSpecError> {
+        let (round_constants, mds, mds_inv) = S::constants();
+        Self::new(
+            S::full_rounds(),
+            S::partial_rounds(),
+            alpha,
+            round_constants,
+            mds,
+            mds_inv,
+        )
+    }
+
+    /// Parse the parameters from a bytes blob, the layout is the header followed by
+    /// the round constants, the MDS matrix and its inverse, each field element is
+    /// encoded in its canonical representation
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, RuntimeSpecError> {
+        if bytes.len() < HEADER_SIZE {
+            return Err(RuntimeSpecError::InvalidLength);
+        }
+        let full_rounds = u32::from_le_bytes(bytes[0..4].try_into().expect("Size is checked"));
+        let partial_rounds = u32::from_le_bytes(bytes[4..8].try_into().expect("Size is checked"));
+        let alpha = u64::from_le_bytes(bytes[8..16].try_into().expect("Size is checked"));
+        let rounds = full_rounds as usize + partial_rounds as usize;
+
+        let element_size = F::Repr::default().as_ref().len();
+        let expected_size = rounds
+            .checked_add(2 * T)
+            .and_then(|rows| rows.checked_mul(T * element_size))
+            .and_then(|size| size.checked_add(HEADER_SIZE))
+            .ok_or(RuntimeSpecError::InvalidLength)?;
+        if bytes.len() != expected_size {
+            return Err(RuntimeSpecError::InvalidLength);
+        }
+
+        let mut rows = bytes[HEADER_SIZE..]
+            .chunks(T * element_size)
+            .map(|row| {
+                let mut result = [F::ZERO; T];
+                for (value, chunk) in result.iter_mut().zip(row.chunks(element_size)) {
+                    let mut repr = F::Repr::default();
+                    repr.as_mut().copy_from_slice(chunk);
+                    *value = Option::<F>::from(F::from_repr(repr))
+                        .ok_or(RuntimeSpecError::InvalidFieldElement)?;
+                }
+                Ok(result)
+            })
+            .collect::<Result<Vec<[F; T]>, RuntimeSpecError>>()?;
+
+        let mut mds_inv = [[F::ZERO; T]; T];
+        mds_inv.copy_from_slice(&rows[rounds + T..]);
+        let mut mds = [[F::ZERO; T]; T];
+        mds.copy_from_slice(&rows[rounds..rounds + T]);
+        rows.truncate(rounds);
+
+        Self::new(
+            full_rounds as usize,
+            partial_rounds as usize,
+            alpha,
+            rows,
+            mds,
+            mds_inv,
+        )
+    }
+
+    /// Serialize the parameters to a bytes blob that could be parsed by [RuntimeSpec::from_bytes]
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut bytes = Vec::new();
+        bytes.extend_from_slice(&(self.full_rounds as u32).to_le_bytes());
+        bytes.extend_from_slice(&(self.partial_rounds as u32).to_le_bytes());
+        bytes.extend_from_slice(&self.alpha.to_le_bytes());
+        for row in self
+            .round_constants
+            .iter()
+            .chain(self.mds.iter())
+            .chain(self.mds_inv.iter())
+        {
+            for value in row.iter() {
+                bytes.extend_from_slice(value.to_repr().as_ref());
+            }
+        }
+        bytes
+    }
+
+    /// The number of full rounds
+    pub fn full_rounds(&self) -> usize {
+        self.full_rounds
+    }
+
+    /// The number of partial rounds
+    pub fn partial_rounds(&self) -> usize {
+        self.partial_rounds
+    }
+
+    /// The S-box
+    pub fn sbox(&self, val: F) -> F {
+        val.pow_vartime([self.alpha])
+    }
+
+    /// The `(round_constants, mds, mds^-1)` of the spec
+    pub fn constants(&self) -> (Vec<[F; T]>, Mtrx<F, T>, Mtrx<F, T>) {
+        (self.round_constants.clone(), self.mds, self.mds_inv)
+    }
+
+    /// Runs the Poseidon permutation on the given state
+    pub fn permute(&self, state: &mut [F; T]) {
+        permute_with::<F, T>(
+            state,
+            &self.mds,
+            &self.round_constants,
+            self.full_rounds,
+            self.partial_rounds,
+            |val| self.sbox(val),
+        );
+    }
+
+    /// Hashes the given input, same as [Hash](crate::poseidon_hash::Hash) with [ConstantLength]
+    pub fn hash<const L: usize>(&self, message: [F; L]) -> F {
+        let mut state = [F::ZERO; T];
+        state[R] = <ConstantLength<L> as Domain<F, R>>::initial_capacity_element();
+        let input = message
+            .into_iter()
+            .chain(<ConstantLength<L> as Domain<F, R>>::padding(L))
+            .collect::<Vec<F>>();
+        for chunk in input.chunks(R) {
+            for (word, value) in state.iter_mut().zip(chunk.iter()) {
+                *word += value;
+            }
+            self.permute(&mut state);
+        }
+        state[0]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{RuntimeSpec, RuntimeSpecError};
+    use crate::poseidon_hash::{ConstantLength, Hash, OrchardNullifier};
+    use ff::Field;
+    use halo2_proofs::halo2curves::bn256::Fr;
+    use halo2curves::pasta::Fp;
+
+    #[test]
+    fn runtime_spec_should_match_orchard_spec() {
+        let spec = RuntimeSpec::<Fp, 3, 2>::from_spec::<OrchardNullifier>(5)
+            .expect("Orchard spec must be valid");
+        let loaded =
+            RuntimeSpec::<Fp, 3, 2>::from_bytes(&spec.to_bytes()).expect("Unable to load spec");
+        let message = [Fp::from(120), Fp::from(240)];
+        let expected = Hash::<Fp, OrchardNullifier, ConstantLength<2>, 3, 2>::init().hash(message);
+        assert_eq!(loaded.hash(message), expected);
+        assert_eq!(loaded.to_bytes(), spec.to_bytes());
+    }
+
+    #[test]
+    fn runtime_spec_should_match_orchard_spec_fr() {
+        let spec = RuntimeSpec::<Fr, 3, 2>::from_spec::<OrchardNullifier>(5)
+            .expect("Orchard spec must be valid");
+        let loaded =
+            RuntimeSpec::<Fr, 3, 2>::from_bytes(&spec.to_bytes()).expect("Unable to load spec");
+        let message = [Fr::from(1), Fr::from(2)];
+        let expected = Hash::<Fr, OrchardNullifier, ConstantLength<2>, 3, 2>::init().hash(message);
+        assert_eq!(loaded.hash(message), expected);
+    }
+
+    #[test]
+    fn runtime_spec_should_reject_invalid_parameters() {
+        let spec = RuntimeSpec::<Fp, 3, 2>::from_spec::<OrchardNullifier>(5)
+            .expect("Orchard spec must be valid");
+        let bytes = spec.to_bytes();
+
+        // Truncated blob
+        assert_eq!(
+            RuntimeSpec::<Fp, 3, 2>::from_bytes(&bytes[..bytes.len() - 1]).unwrap_err(),
+            RuntimeSpecError::InvalidLength
+        );
+
+        // Non canonical field element
+        let mut invalid = bytes.clone();
+        invalid[16..48].copy_from_slice(&[0xff; 32]);
+        assert_eq!(
+            RuntimeSpec::<Fp, 3, 2>::from_bytes(&invalid).unwrap_err(),
+            RuntimeSpecError::InvalidFieldElement
+        );
+
+        // MDS inverse does not match MDS
+        let (round_constants, mds, mut mds_inv) = spec.constants();
+        mds_inv[0][0] += Fp::ONE;
+        assert_eq!(
+            RuntimeSpec::<Fp, 3, 2>::new(8, 56, 5, round_constants.clone(), mds, mds_inv)
+                .unwrap_err(),
+            RuntimeSpecError::NonInvertibleMds
+        );
+
+        // Rounds do not match the round constants
+        let (_, _, mds_inv) = spec.constants();
+        assert_eq!(
+            RuntimeSpec::<Fp, 3, 2>::new(8, 55, 5, round_constants.clone(), mds, mds_inv)
+                .unwrap_err(),
+            RuntimeSpecError::InvalidDimensions
+        );
+
+        // Constant S-box
+        assert_eq!(
+            RuntimeSpec::<Fp, 3, 2>::new(8, 56, 0, round_constants, mds, mds_inv).unwrap_err(),
+            RuntimeSpecError::InvalidSbox
+        );
+        let mut invalid = bytes;
+        invalid[8..16].copy_from_slice(&0u64.to_le_bytes());
+        assert_eq!(
+            RuntimeSpec::<Fp, 3, 2>::from_bytes(&invalid).unwrap_err(),
+            RuntimeSpecError::InvalidSbox
+        );
+    }
+}
@@ -114,6 +114,10 @@ where
     fn memory(&mut self) -> &'_ mut RBTree<K, V> {
         &mut self.memory
     }
+
+    fn ro_memory(&self) -> &'_ RBTree<K, V> {
+        &self.memory
+    }
 }
 
 impl<M, K, V, const S: usize, const T: usize> AbstractInstruction<M, K, V>
@@ -309,6 +313,14 @@ where
         (self.memory_allocated.low(), self.memory_allocated.high())
     }
 
+    fn memory_capacity(&self) -> K {
+        self.memory_allocated.size(self.word_size)
+    }
+
+    fn stack_capacity(&self) -> K {
+        self.stack_allocated.size(self.word_size)
+    }
+
     fn get_stack_depth(&self) -> u64 {
         self.ro_context().stack_depth
     }
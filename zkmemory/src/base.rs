@@ -1,6 +1,11 @@
+extern crate alloc;
+use crate::error::{BaseConversionError, Error};
+use alloc::string::String;
+use core::cmp::Ordering;
 use core::fmt::{Debug, Display};
 use core::ops::{Add, Div, Mul, Rem, Sub};
 use ethnum::U256;
+use serde::{de, Deserialize, Deserializer, Serialize, Serializer};
 
 /// Base trait for memory address and value
 pub trait Base<const S: usize, T = Self>:
@@ -42,6 +47,60 @@ pub trait Base<const S: usize, T = Self>:
     fn fixed_be_bytes(&self) -> [u8; 32];
     /// To little endian bytes
     fn fixed_le_bytes(&self) -> [u8; 32];
+    /// Raise self to the power of `exp` with square-and-multiply, the result wraps
+    /// modulo `2^(8 * S)` like the EVM `EXP` opcode and `0.pow(0)` is `1`
+    fn pow(&self, exp: &Self) -> Self;
+    /// Compare as two's complement signed values, the highest bit is the sign bit.
+    /// [Ord] of [Base] is always unsigned
+    fn signed_cmp(&self, other: &Self) -> Ordering {
+        let mut lhs: [u8; S] = (*self).into();
+        let mut rhs: [u8; S] = (*other).into();
+        // Flipping the sign bit maps signed order onto unsigned big endian order
+        lhs[0] ^= 0x80;
+        rhs[0] ^= 0x80;
+        lhs.cmp(&rhs)
+    }
+    /// Get the bit at `index`, bit 0 is the least significant bit. Bits beyond the
+    /// `8 * S` bits of the cell are false
+    fn bit(&self, index: usize) -> bool {
+        if index >= S * 8 {
+            return false;
+        }
+        let bytes: [u8; S] = (*self).into();
+        (bytes[S - 1 - index / 8] >> (index % 8)) & 1 == 1
+    }
+    /// Position of the highest set bit plus one, zero has no set bit so its bit length is 0
+    fn bit_length(&self) -> usize {
+        let bytes: [u8; S] = (*self).into();
+        match bytes.iter().position(|byte| *byte != 0) {
+            Some(i) => (S - 1 - i) * 8 + (8 - bytes[i].leading_zeros() as usize),
+            None => 0,
+        }
+    }
+    /// Try to convert from big endian bytes, the length of bytes must be `S`
+    fn try_from_be_bytes(bytes: &[u8]) -> Result<Self, Error> {
+        let buf: [u8; S] = bytes.try_into().map_err(|_| Error::BaseLengthMismatch)?;
+        Ok(Self::from(buf))
+    }
+    /// Try to convert from little endian bytes, the length of bytes must be `S`
+    fn try_from_le_bytes(bytes: &[u8]) -> Result<Self, Error> {
+        let mut buf: [u8; S] = bytes.try_into().map_err(|_| Error::BaseLengthMismatch)?;
+        buf.reverse();
+        Ok(Self::from(buf))
+    }
+    /// To big endian hex string of `S` bytes, without `0x` prefix
+    fn to_hex(&self) -> String {
+        hex::encode(<Self as Into<[u8; S]>>::into(*self))
+    }
+    /// From big endian hex string of `S` bytes, the `0x` prefix is optional
+    fn from_hex(value: &str) -> Result<Self, Error> {
+        let value = value
+            .strip_prefix("0x")
+            .or_else(|| value.strip_prefix("0X"))
+            .unwrap_or(value);
+        let bytes = hex::decode(value).map_err(|_| Error::BaseInvalidHex)?;
+        Self::try_from_be_bytes(&bytes)
+    }
 }
 
 /// Convert from/to [`core::usize`]
@@ -126,6 +185,20 @@ macro_rules! new_base {
             fn fixed_le_bytes(&self) -> [u8; 32] {
                 self.0.to_le_bytes()
             }
+
+            fn pow(&self, exp: &Self) -> Self {
+                let mut result = U256::ONE;
+                let mut base = self.0;
+                let mut exp = exp.0;
+                while exp != U256::ZERO {
+                    if exp & U256::ONE == U256::ONE {
+                        result = result.wrapping_mul(base);
+                    }
+                    base = base.wrapping_mul(base);
+                    exp >>= 1;
+                }
+                Self(result)
+            }
         }
 
         impl From<i32> for Uint<U256> {
@@ -175,6 +248,21 @@ macro_rules! new_base {
                 Self(U256::from_be_bytes(value))
             }
         }
+
+        impl TryFrom<&[u8]> for Uint<U256> {
+            type Error = BaseConversionError;
+
+            fn try_from(value: &[u8]) -> Result<Self, Self::Error> {
+                let buf: [u8; $byte_size] =
+                    value
+                        .try_into()
+                        .map_err(|_| BaseConversionError::InvalidLength {
+                            expected: $byte_size,
+                            actual: value.len(),
+                        })?;
+                Ok(Self::from(buf))
+            }
+        }
     };
     ($primitive:ident, $byte_size: expr) => {
         impl Base<$byte_size> for Uint<$primitive> {
@@ -203,6 +291,20 @@ macro_rules! new_base {
                 buf[..$byte_size].copy_from_slice(&self.0.to_le_bytes());
                 buf
             }
+
+            fn pow(&self, exp: &Self) -> Self {
+                let mut result: $primitive = 1;
+                let mut base = self.0;
+                let mut exp = exp.0;
+                while exp != 0 {
+                    if exp & 1 == 1 {
+                        result = result.wrapping_mul(base);
+                    }
+                    base = base.wrapping_mul(base);
+                    exp >>= 1;
+                }
+                Self(result)
+            }
         }
 
         impl From<i32> for Uint<$primitive> {
@@ -252,6 +354,39 @@ macro_rules! new_base {
                 Self($primitive::from_be_bytes(value))
             }
         }
+
+        impl TryFrom<&[u8]> for Uint<$primitive> {
+            type Error = BaseConversionError;
+
+            fn try_from(value: &[u8]) -> Result<Self, Self::Error> {
+                let buf: [u8; $byte_size] =
+                    value
+                        .try_into()
+                        .map_err(|_| BaseConversionError::InvalidLength {
+                            expected: $byte_size,
+                            actual: value.len(),
+                        })?;
+                Ok(Self::from(buf))
+            }
+        }
+    };
+}
+
+// Serialize base as big endian hex string
+macro_rules! base_serde {
+    ($primitive:ident, $byte_size: expr) => {
+        impl Serialize for Uint<$primitive> {
+            fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+                serializer.serialize_str(&<Self as Base<$byte_size>>::to_hex(self))
+            }
+        }
+
+        impl<'de> Deserialize<'de> for Uint<$primitive> {
+            fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+                let value = String::deserialize(deserializer)?;
+                <Self as Base<$byte_size>>::from_hex(&value).map_err(de::Error::custom)
+            }
+        }
     };
 }
 
@@ -261,6 +396,12 @@ new_base!(u64, 8);
 new_base!(u32, 4);
 new_base!(u16, 2);
 
+base_serde!(U256, 32);
+base_serde!(u128, 16);
+base_serde!(u64, 8);
+base_serde!(u32, 4);
+base_serde!(u16, 2);
+
 /// Uint256 is a wrapper of [U256] to implement [Base]
 pub type B256 = Uint<U256>;
 /// Uint128 is a wrapper of [u128](core::u128) to implement [Base]
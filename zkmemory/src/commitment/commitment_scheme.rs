@@ -1,4 +1,14 @@
+extern crate alloc;
+use super::{
+    kzg::KZGVectorCommitment, merkle_tree::MerkleVectorCommitment,
+    verkle_tree::VerkleVectorCommitment,
+};
+use crate::error::Error;
+use alloc::{boxed::Box, vec::Vec};
 use ff::PrimeField;
+use group::GroupEncoding;
+use halo2_proofs::halo2curves::bn256::{Fr, G1Affine};
+use serde::{Deserialize, Serialize};
 
 /// A trait defining a common interface for commitment schemes with zk proving capabilities
 pub trait CommitmentScheme<F: PrimeField> {
@@ -28,3 +38,142 @@ pub trait CommitmentScheme<F: PrimeField> {
         witness: Self::Witness,
     ) -> bool;
 }
+
+/// Commitment to a vector of field elements, the encoding is specific to the scheme
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Commitment(pub Vec<u8>);
+
+/// Opening of one element of a committed vector, a list of chunks that are specific to
+/// the scheme
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Opening(pub Vec<Vec<u8>>);
+
+/// Object safe commitment scheme for vectors over [Fr] of Bn256. Unlike
+/// [CommitmentScheme] it has no associated types, so the scheme could be chosen at
+/// runtime behind `Box<dyn DynCommitmentScheme>`, see [CommitmentSchemeKind]
+pub trait DynCommitmentScheme {
+    /// Maximum number of elements of a committed vector
+    fn capacity(&self) -> usize;
+
+    /// Commit to a vector, [Error::CommitmentCapacityExceeded] if it's longer than
+    /// the capacity
+    fn commit(&self, data: &[Fr]) -> Result<Commitment, Error>;
+
+    /// Open the element at `index` of a vector
+    fn open(&self, data: &[Fr], index: usize) -> Result<Opening, Error>;
+
+    /// Verify that `value` is the element at `index` of the committed vector
+    fn verify(&self, commitment: &Commitment, index: usize, value: Fr, opening: &Opening) -> bool;
+}
+
+/// Commitment schemes that could be selected at runtime, e.g. from a JSON config
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum CommitmentSchemeKind {
+    /// [KZGVectorCommitment]
+    Kzg,
+    /// [MerkleVectorCommitment]
+    Merkle,
+    /// [VerkleVectorCommitment]
+    Verkle,
+}
+
+impl CommitmentSchemeKind {
+    /// Create the scheme for vectors of up to `capacity` elements, the capacity of the
+    /// KZG scheme is rounded up to a power of two
+    pub fn create(&self, capacity: usize) -> Box<dyn DynCommitmentScheme> {
+        match self {
+            CommitmentSchemeKind::Kzg => Box::new(KZGVectorCommitment::new(capacity)),
+            CommitmentSchemeKind::Merkle => Box::new(MerkleVectorCommitment::new(capacity)),
+            CommitmentSchemeKind::Verkle => Box::new(VerkleVectorCommitment::new(capacity)),
+        }
+    }
+}
+
+/// Decode a canonical 32 bytes little endian [Fr]
+pub(crate) fn fr_from_slice(bytes: &[u8]) -> Option<Fr> {
+    let bytes: [u8; 32] = bytes.try_into().ok()?;
+    Fr::from_bytes(&bytes).into()
+}
+
+/// Decode a compressed [G1Affine]
+pub(crate) fn g1_from_slice(bytes: &[u8]) -> Option<G1Affine> {
+    let mut repr = <G1Affine as GroupEncoding>::Repr::default();
+    if bytes.len() != repr.as_ref().len() {
+        return None;
+    }
+    repr.as_mut().copy_from_slice(bytes);
+    <G1Affine as GroupEncoding>::from_bytes(&repr).into()
+}
+
+#[cfg(test)]
+mod tests {
+    extern crate alloc;
+    use super::{Commitment, CommitmentSchemeKind, Opening};
+    use crate::error::Error;
+    use alloc::vec::Vec;
+    use halo2_proofs::halo2curves::bn256::Fr;
+
+    #[test]
+    fn test_all_schemes_commit_and_open() {
+        let data = (0..11u64).map(|i| Fr::from(i * 7 + 1)).collect::<Vec<Fr>>();
+        let mut other_data = data.clone();
+        other_data[3] = Fr::from(1000);
+
+        for kind in [
+            CommitmentSchemeKind::Kzg,
+            CommitmentSchemeKind::Merkle,
+            CommitmentSchemeKind::Verkle,
+        ] {
+            let scheme = kind.create(16);
+            assert!(scheme.capacity() >= 16);
+            let commitment = scheme.commit(&data).expect("Unable to commit");
+            assert_eq!(scheme.commit(&data), Ok(commitment.clone()), "{:?}", kind);
+            let other_commitment = scheme.commit(&other_data).expect("Unable to commit");
+            assert_ne!(commitment, other_commitment, "{:?}", kind);
+
+            for index in [0, 4, 10] {
+                let opening = scheme.open(&data, index).expect("Unable to open");
+                assert!(
+                    scheme.verify(&commitment, index, data[index], &opening),
+                    "{:?} rejects a valid opening of index {}",
+                    kind,
+                    index
+                );
+                // Wrong value, wrong index or another commitment
+                assert!(!scheme.verify(&commitment, index, Fr::from(999), &opening));
+                assert!(!scheme.verify(&commitment, index ^ 1, data[index], &opening));
+                assert!(!scheme.verify(&other_commitment, index, data[index], &opening));
+            }
+
+            // Malformed inputs are rejected
+            let opening = scheme.open(&data, 0).expect("Unable to open");
+            assert!(!scheme.verify(&Commitment(Vec::new()), 0, data[0], &opening));
+            assert!(!scheme.verify(&commitment, 0, data[0], &Opening(Vec::new())));
+            assert!(!scheme.verify(&commitment, usize::MAX, data[0], &opening));
+            assert_eq!(
+                scheme.open(&data, data.len()),
+                Err(Error::CommitmentIndexOutOfRange)
+            );
+            let too_long = (0..scheme.capacity() as u64 + 1)
+                .map(Fr::from)
+                .collect::<Vec<Fr>>();
+            assert_eq!(
+                scheme.commit(&too_long),
+                Err(Error::CommitmentCapacityExceeded)
+            );
+        }
+    }
+
+    #[test]
+    fn test_scheme_kind_from_config() {
+        let kind: CommitmentSchemeKind =
+            serde_json::from_str("\"verkle\"").expect("Unable to deserialize scheme");
+        assert_eq!(kind, CommitmentSchemeKind::Verkle);
+        assert_eq!(
+            serde_json::to_string(&CommitmentSchemeKind::Kzg).expect("Unable to serialize"),
+            "\"kzg\""
+        );
+        assert!(serde_json::from_str::<CommitmentSchemeKind>("\"ipa\"").is_err());
+    }
+}
@@ -3,13 +3,16 @@
 //! [PSE 's KZG implementation](https://github.com/privacy-scaling-explorations/halo2/tree/main/halo2_backend/src/poly/kzg) to commit, open and verify the polynomial
 
 extern crate alloc;
-use crate::commitment::commitment_scheme::CommitmentScheme as CommitmentSchemeTrait;
+use crate::commitment::commitment_scheme::{
+    g1_from_slice, Commitment, CommitmentScheme as CommitmentSchemeTrait, DynCommitmentScheme,
+    Opening,
+};
 use crate::{base::Base, machine::MemoryInstruction, machine::TraceRecord};
 use alloc::vec;
 use alloc::vec::Vec;
 use core::marker::PhantomData;
 use ff::{Field, WithSmallOrderMulGroup};
-use group::Curve;
+use group::{Curve, GroupEncoding};
 use halo2_proofs::{
     arithmetic::{eval_polynomial, lagrange_interpolate},
     halo2curves::bn256::{Bn256, Fr, G1Affine},
@@ -24,8 +27,8 @@ use halo2_proofs::{
         {Coeff, EvaluationDomain, Polynomial, ProverQuery, VerificationStrategy, VerifierQuery},
     },
     transcript::{
-        Blake2bRead, Blake2bWrite, Challenge255, EncodedChallenge, TranscriptReadBuffer,
-        TranscriptWriterBuffer,
+        Blake2bRead, Blake2bWrite, Challenge255, EncodedChallenge, TranscriptRead,
+        TranscriptReadBuffer, TranscriptWriterBuffer,
     },
 };
 use rand_core::OsRng;
@@ -327,6 +330,50 @@ pub fn verify_kzg_proof<
             .finalize()
 }
 
+/// Verify KZG openings over Bn256 like [verify_kzg_proof], but a malformed proof or
+/// mismatched lists are rejected instead of panic
+pub(crate) fn try_verify_kzg_proof(
+    params: &ParamsKZG<Bn256>,
+    points_list: &[Fr],
+    eval: &[Fr],
+    commitments: &[G1Affine],
+    proof: &[u8],
+) -> bool {
+    if points_list.len() != eval.len() || points_list.len() != commitments.len() {
+        return false;
+    }
+    let mut transcript = Blake2bRead::<_, G1Affine, Challenge255<G1Affine>>::init(proof);
+    for commitment in commitments {
+        match transcript.read_point() {
+            Ok(point) if point == *commitment => {}
+            _ => return false,
+        }
+    }
+    for value in eval {
+        match transcript.read_scalar() {
+            Ok(scalar) if scalar == *value => {}
+            _ => return false,
+        }
+    }
+    let queries = points_list
+        .iter()
+        .zip(eval)
+        .zip(commitments)
+        .map(|((point, value), commitment)| {
+            VerifierQuery::new_commitment(commitment, *point, *value)
+        })
+        .collect::<Vec<_>>();
+    let verifier = VerifierSHPLONK::new(params);
+    AccumulatorStrategy::new(params)
+        .process(|msm_accumulator| {
+            verifier
+                .verify_proof(&mut transcript, queries, msm_accumulator)
+                .map_err(|_| Error::Opening)
+        })
+        .map(|strategy| strategy.finalize())
+        .unwrap_or(false)
+}
+
 impl<K, V, const S: usize, const T: usize> CommitmentSchemeTrait<Fr>
     for KZGMemoryCommitment<K, V, S, T>
 where
@@ -378,6 +425,96 @@ where
     }
 }
 
+/// KZG commitment to a vector, the vector is the evaluations of a polynomial over
+/// the domain 1, omega, omega^2,... and an element is opened at its power of omega
+#[derive(Debug, Clone)]
+pub struct KZGVectorCommitment {
+    kzg_params: ParamsKZG<Bn256>,
+    domain: EvaluationDomain<Fr>,
+}
+
+impl KZGVectorCommitment {
+    /// Initialize KZG parameters for vectors of up to `capacity` elements, the
+    /// capacity is rounded up to a power of two
+    pub fn new(capacity: usize) -> Self {
+        let k = capacity.max(2).next_power_of_two().trailing_zeros();
+        Self {
+            kzg_params: ParamsKZG::<Bn256>::new(k),
+            domain: EvaluationDomain::new(1, k),
+        }
+    }
+
+    // Interpolate the zero padded vector over the domain
+    fn poly_from_vec(&self, data: &[Fr]) -> Polynomial<Fr, Coeff> {
+        let mut evals = vec![Fr::ZERO; self.capacity()];
+        evals[..data.len()].copy_from_slice(data);
+        self.domain
+            .lagrange_to_coeff(self.domain.lagrange_from_vec(evals))
+    }
+
+    // Evaluation point of the element at the index
+    fn point(&self, index: usize) -> Fr {
+        self.domain.get_omega().pow_vartime([index as u64])
+    }
+}
+
+impl DynCommitmentScheme for KZGVectorCommitment {
+    fn capacity(&self) -> usize {
+        1 << Params::k(&self.kzg_params)
+    }
+
+    fn commit(&self, data: &[Fr]) -> Result<Commitment, crate::error::Error> {
+        if data.len() > self.capacity() {
+            return Err(crate::error::Error::CommitmentCapacityExceeded);
+        }
+        let commitment = self
+            .kzg_params
+            .commit(&self.poly_from_vec(data), Blind(Fr::ZERO))
+            .to_affine();
+        Ok(Commitment(commitment.to_bytes().as_ref().to_vec()))
+    }
+
+    fn open(&self, data: &[Fr], index: usize) -> Result<Opening, crate::error::Error> {
+        if data.len() > self.capacity() {
+            return Err(crate::error::Error::CommitmentCapacityExceeded);
+        }
+        if index >= data.len() {
+            return Err(crate::error::Error::CommitmentIndexOutOfRange);
+        }
+        let poly = self.poly_from_vec(data);
+        let commitment = self.kzg_params.commit(&poly, Blind(Fr::ZERO)).to_affine();
+        let proof = create_kzg_proof::<
+            KZGCommitmentScheme<Bn256>,
+            ProverSHPLONK<'_, Bn256>,
+            Challenge255<G1Affine>,
+            Blake2bWrite<Vec<u8>, G1Affine, Challenge255<G1Affine>>,
+        >(
+            &self.kzg_params,
+            vec![self.point(index)],
+            vec![poly],
+            vec![commitment],
+        );
+        Ok(Opening(vec![proof]))
+    }
+
+    fn verify(&self, commitment: &Commitment, index: usize, value: Fr, opening: &Opening) -> bool {
+        let (Some(commitment), [proof]) = (g1_from_slice(&commitment.0), opening.0.as_slice())
+        else {
+            return false;
+        };
+        if index >= self.capacity() {
+            return false;
+        }
+        try_verify_kzg_proof(
+            &self.kzg_params,
+            &[self.point(index)],
+            &[value],
+            &[commitment],
+            proof,
+        )
+    }
+}
+
 #[cfg(test)]
 mod test {
     use super::*;
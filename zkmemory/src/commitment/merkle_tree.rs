@@ -1,13 +1,18 @@
 //! Circuit for proving the correctness of the Merkle tree commitment.
 
 extern crate alloc;
-use crate::commitment::commitment_scheme::CommitmentScheme;
+use crate::commitment::commitment_scheme::{
+    fr_from_slice, Commitment, CommitmentScheme, DynCommitmentScheme, Opening,
+};
 use alloc::{vec, vec::Vec};
 use core::marker::PhantomData;
 use ff::{Field, PrimeField};
 use halo2_proofs::{
     circuit::{Layouter, Region, SimpleFloorPlanner, Value},
-    halo2curves::pasta::{EqAffine, Fp},
+    halo2curves::{
+        bn256::Fr,
+        pasta::{EqAffine, Fp},
+    },
     plonk::{
         create_proof, keygen_pk, keygen_vk, verify_proof, Advice, Circuit, Column,
         ConstraintSystem, Error, Expression, Fixed, Instance, ProvingKey, Selector,
@@ -390,6 +395,29 @@ pub fn merkle_tree_commit_fp(leaf: &u64, elements: &[u64], indices: &[u64]) -> F
     digest
 }
 
+/// Compute the root of a full merkle tree over the given leaves, the leaves are padded
+/// with zero to a power of two and the root of an empty tree is zero
+pub fn merkle_tree_root<F: Field + PrimeField>(leaves: &[F]) -> F
+where
+    OrchardNullifier: Spec<F, 3, 2>,
+{
+    if leaves.is_empty() {
+        return F::ZERO;
+    }
+    let mut layer = leaves.to_vec();
+    layer.resize(leaves.len().next_power_of_two(), F::ZERO);
+    while layer.len() > 1 {
+        layer = layer
+            .chunks(2)
+            .map(|pair| {
+                Hash::<F, OrchardNullifier, ConstantLength<2>, 3, 2>::init()
+                    .hash([pair[0], pair[1]])
+            })
+            .collect();
+    }
+    layer[0]
+}
+
 impl<S: Spec<Fp, W, R> + Clone, const W: usize, const R: usize> CommitmentScheme<Fp>
     for MerkleTreeCircuit<S, Fp, W, R>
 {
@@ -426,6 +454,95 @@ impl<S: Spec<Fp, W, R> + Clone, const W: usize, const R: usize> CommitmentScheme
     }
 }
 
+/// Merkle tree commitment to a vector over [Fr] of Bn256, the vector is padded with
+/// zero to a power of two and an opening is the list of sibling nodes from the leaf
+#[derive(Debug, Clone, Copy)]
+pub struct MerkleVectorCommitment {
+    capacity: usize,
+}
+
+impl MerkleVectorCommitment {
+    /// Create the commitment scheme for vectors of up to `capacity` elements
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            capacity: capacity.max(1),
+        }
+    }
+
+    // Number of leaves of the full tree
+    fn width(&self) -> usize {
+        self.capacity.next_power_of_two()
+    }
+
+    // Layers of the full tree from the leaves to the root
+    fn layers(&self, data: &[Fr]) -> Vec<Vec<Fr>> {
+        let mut layer = data.to_vec();
+        layer.resize(self.width(), Fr::ZERO);
+        let mut layers = vec![layer];
+        while layers[layers.len() - 1].len() > 1 {
+            let next = layers[layers.len() - 1]
+                .chunks(2)
+                .map(|pair| {
+                    Hash::<Fr, OrchardNullifier, ConstantLength<2>, 3, 2>::init()
+                        .hash([pair[0], pair[1]])
+                })
+                .collect();
+            layers.push(next);
+        }
+        layers
+    }
+}
+
+impl DynCommitmentScheme for MerkleVectorCommitment {
+    fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    fn commit(&self, data: &[Fr]) -> Result<Commitment, crate::error::Error> {
+        if data.len() > self.capacity {
+            return Err(crate::error::Error::CommitmentCapacityExceeded);
+        }
+        let layers = self.layers(data);
+        let root = layers[layers.len() - 1][0];
+        Ok(Commitment(root.to_repr().as_ref().to_vec()))
+    }
+
+    fn open(&self, data: &[Fr], index: usize) -> Result<Opening, crate::error::Error> {
+        if data.len() > self.capacity {
+            return Err(crate::error::Error::CommitmentCapacityExceeded);
+        }
+        if index >= data.len() {
+            return Err(crate::error::Error::CommitmentIndexOutOfRange);
+        }
+        let layers = self.layers(data);
+        let siblings = layers[..layers.len() - 1]
+            .iter()
+            .enumerate()
+            .map(|(level, layer)| layer[(index >> level) ^ 1].to_repr().as_ref().to_vec())
+            .collect();
+        Ok(Opening(siblings))
+    }
+
+    fn verify(&self, commitment: &Commitment, index: usize, value: Fr, opening: &Opening) -> bool {
+        if index >= self.capacity || opening.0.len() != self.width().trailing_zeros() as usize {
+            return false;
+        }
+        let mut digest = value;
+        for (level, sibling) in opening.0.iter().enumerate() {
+            let Some(sibling) = fr_from_slice(sibling) else {
+                return false;
+            };
+            let message = if (index >> level) & 1 == 0 {
+                [digest, sibling]
+            } else {
+                [sibling, digest]
+            };
+            digest = Hash::<Fr, OrchardNullifier, ConstantLength<2>, 3, 2>::init().hash(message);
+        }
+        fr_from_slice(&commitment.0) == Some(digest)
+    }
+}
+
 #[cfg(test)]
 mod tests {
     extern crate alloc;
@@ -6,5 +6,7 @@ pub mod extends;
 pub mod kzg;
 /// Commitment scheme using Merkle Tree
 pub mod merkle_tree;
+/// Running hash chain commitment of the execution trace
+pub mod trace_commitment;
 /// Commitment scheme using Verkle Tree
 pub mod verkle_tree;
@@ -0,0 +1,47 @@
+//! Running Poseidon hash chain over the execution trace. Each record is folded into
+//! the accumulator in time order, so the commitment can be computed while the trace
+//! is streamed without storing the records.
+
+use crate::{
+    base::Base,
+    machine::{AbstractTraceRecord, MemoryInstruction, TraceRecord},
+};
+use ff::Field;
+use halo2_proofs::halo2curves::pasta::Fp;
+use poseidon::poseidon_hash::{ConstantLength, Hash, OrchardNullifier};
+
+/// Fold a trace record into the accumulator of the hash chain, the initial accumulator is zero
+pub fn fold_trace_record<K, V, const S: usize, const T: usize>(
+    acc: Fp,
+    record: &TraceRecord<K, V, S, T>,
+) -> Fp
+where
+    K: Base<S>,
+    V: Base<T>,
+    Fp: From<K> + From<V>,
+{
+    let instruction = match record.instruction() {
+        MemoryInstruction::Write => Fp::ZERO,
+        MemoryInstruction::Read => Fp::ONE,
+    };
+    Hash::<Fp, OrchardNullifier, ConstantLength<6>, 3, 2>::init().hash([
+        acc,
+        Fp::from(record.time_log()),
+        Fp::from(record.stack_depth()),
+        instruction,
+        Fp::from(record.address()),
+        Fp::from(record.value()),
+    ])
+}
+
+/// Compute the hash chain of a collected trace, the trace must be sorted by time log
+pub fn trace_commitment<K, V, const S: usize, const T: usize>(
+    trace: &[TraceRecord<K, V, S, T>],
+) -> Fp
+where
+    K: Base<S>,
+    V: Base<T>,
+    Fp: From<K> + From<V>,
+{
+    trace.iter().fold(Fp::ZERO, fold_trace_record)
+}
@@ -3,28 +3,33 @@
 //! We choose KZG as the polynomial commitment scheme for committing the messages in the children.
 //! Right now, the circuit could only support committing messages in the field Fr of Bn256, not in all finite fields.
 extern crate alloc;
-use super::kzg::verify_kzg_proof;
+use super::{
+    commitment_scheme::{fr_from_slice, g1_from_slice, Commitment, DynCommitmentScheme, Opening},
+    kzg::{create_kzg_proof, try_verify_kzg_proof, verify_kzg_proof},
+};
 use crate::constraints;
 use alloc::{vec, vec::Vec};
 use constraints::gadgets::Table;
 use core::marker::PhantomData;
-use ff::Field;
+use ff::{Field, FromUniformBytes, PrimeField};
+use group::{Curve, GroupEncoding};
 use halo2_proofs::{
+    arithmetic::lagrange_interpolate,
     circuit::{Layouter, Region, SimpleFloorPlanner, Value},
     halo2curves::bn256::{Bn256, Fr, G1Affine},
     plonk::{
         Advice, Circuit, Column, ConstraintSystem, Error, Expression, Fixed, Instance, Selector,
     },
     poly::{
-        commitment::ParamsProver,
+        commitment::{Blind, ParamsProver},
         kzg::{
             commitment::{KZGCommitmentScheme, ParamsKZG},
-            multiopen::VerifierSHPLONK,
+            multiopen::{ProverSHPLONK, VerifierSHPLONK},
             strategy::AccumulatorStrategy,
         },
-        Rotation,
+        Coeff, EvaluationDomain, Polynomial, Rotation,
     },
-    transcript::{Blake2bRead, Challenge255},
+    transcript::{Blake2bRead, Blake2bWrite, Challenge255},
 };
 use poseidon::poseidon_hash::{ConstantLength, Hash, Spec};
 use poseidon::{
@@ -330,21 +335,157 @@ impl Spec<Fr, 3, 2> for OrchardNullifier {
     }
 }
 
+/// Verkle tree commitment to a vector over [Fr] of Bn256, every parent node has 4
+/// children and its label is the hash of the KZG commitment of its children.
+/// An opening is the commitments on the path from the leaf to the root, followed by
+/// one proof of all openings
+#[derive(Debug, Clone)]
+pub struct VerkleVectorCommitment {
+    capacity: usize,
+    kzg_params: ParamsKZG<Bn256>,
+    domain: EvaluationDomain<Fr>,
+}
+
+impl VerkleVectorCommitment {
+    /// Create the commitment scheme for vectors of up to `capacity` elements
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            capacity: capacity.max(1),
+            kzg_params: ParamsKZG::<Bn256>::new(2),
+            domain: EvaluationDomain::new(1, 2),
+        }
+    }
+
+    // Depth of the tree, a tree has at least one parent node
+    fn depth(&self) -> usize {
+        let mut depth = 1;
+        while 4usize.saturating_pow(depth as u32) < self.capacity {
+            depth += 1;
+        }
+        depth
+    }
+
+    // Position of the node of the level in its parent
+    fn child_index(index: usize, level: usize) -> usize {
+        (index >> (2 * level)) & 3
+    }
+
+    fn poly_from_evals(&self, evals: &[Fr]) -> Polynomial<Fr, Coeff> {
+        self.domain
+            .coeff_from_vec(lagrange_interpolate(&OMEGA_POWER[0..4], evals))
+    }
+
+    // Hash a commitment to its label, the coordinates are reduced into Fr
+    fn label(commitment: &G1Affine) -> Fr {
+        let to_fr = |bytes: &[u8]| {
+            let mut wide = [0u8; 64];
+            wide[..bytes.len()].copy_from_slice(bytes);
+            Fr::from_uniform_bytes(&wide)
+        };
+        Hash::<Fr, OrchardNullifier, ConstantLength<2>, 3, 2>::init().hash([
+            to_fr(&commitment.x.to_bytes()),
+            to_fr(&commitment.y.to_bytes()),
+        ])
+    }
+
+    // Polynomials and commitments of every level from the leaves to the root
+    fn levels(&self, data: &[Fr]) -> Vec<(Vec<Polynomial<Fr, Coeff>>, Vec<G1Affine>)> {
+        let mut layer = data.to_vec();
+        layer.resize(4usize.pow(self.depth() as u32), Fr::ZERO);
+        let mut levels = Vec::new();
+        while layer.len() > 1 {
+            let polys = layer
+                .chunks(4)
+                .map(|children| self.poly_from_evals(children))
+                .collect::<Vec<_>>();
+            let commitments = polys
+                .iter()
+                .map(|poly| self.kzg_params.commit(poly, Blind(Fr::ZERO)).to_affine())
+                .collect::<Vec<_>>();
+            layer = commitments.iter().map(Self::label).collect();
+            levels.push((polys, commitments));
+        }
+        levels
+    }
+}
+
+impl DynCommitmentScheme for VerkleVectorCommitment {
+    fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    fn commit(&self, data: &[Fr]) -> Result<Commitment, crate::error::Error> {
+        if data.len() > self.capacity {
+            return Err(crate::error::Error::CommitmentCapacityExceeded);
+        }
+        let levels = self.levels(data);
+        let root = Self::label(&levels[levels.len() - 1].1[0]);
+        Ok(Commitment(root.to_repr().as_ref().to_vec()))
+    }
+
+    fn open(&self, data: &[Fr], index: usize) -> Result<Opening, crate::error::Error> {
+        if data.len() > self.capacity {
+            return Err(crate::error::Error::CommitmentCapacityExceeded);
+        }
+        if index >= data.len() {
+            return Err(crate::error::Error::CommitmentIndexOutOfRange);
+        }
+        let mut points = Vec::new();
+        let mut polys = Vec::new();
+        let mut commitments = Vec::new();
+        for (level, (level_polys, level_commitments)) in self.levels(data).into_iter().enumerate() {
+            let node = index >> (2 * (level + 1));
+            points.push(OMEGA_POWER[Self::child_index(index, level)]);
+            polys.push(level_polys[node].clone());
+            commitments.push(level_commitments[node]);
+        }
+        let mut opening = commitments
+            .iter()
+            .map(|commitment| commitment.to_bytes().as_ref().to_vec())
+            .collect::<Vec<_>>();
+        opening.push(create_kzg_proof::<
+            KZGCommitmentScheme<Bn256>,
+            ProverSHPLONK<'_, Bn256>,
+            Challenge255<G1Affine>,
+            Blake2bWrite<Vec<u8>, G1Affine, Challenge255<G1Affine>>,
+        >(&self.kzg_params, points, polys, commitments));
+        Ok(Opening(opening))
+    }
+
+    fn verify(&self, commitment: &Commitment, index: usize, value: Fr, opening: &Opening) -> bool {
+        let depth = self.depth();
+        if index >= self.capacity || opening.0.len() != depth + 1 {
+            return false;
+        }
+        let Some(commitments) = opening.0[..depth]
+            .iter()
+            .map(|bytes| g1_from_slice(bytes))
+            .collect::<Option<Vec<G1Affine>>>()
+        else {
+            return false;
+        };
+        // A node is opened at its position in the parent, the parent's label is the
+        // value opened at the next level
+        let points = (0..depth)
+            .map(|level| OMEGA_POWER[Self::child_index(index, level)])
+            .collect::<Vec<_>>();
+        let mut evals = vec![value];
+        evals.extend(commitments[..depth - 1].iter().map(Self::label));
+        fr_from_slice(&commitment.0) == Some(Self::label(&commitments[depth - 1]))
+            && try_verify_kzg_proof(
+                &self.kzg_params,
+                &points,
+                &evals,
+                &commitments,
+                &opening.0[depth],
+            )
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::commitment::kzg::create_kzg_proof;
-    use alloc::vec;
-    use core::marker::PhantomData;
-    use group::Curve;
-    use halo2_proofs::{
-        arithmetic::lagrange_interpolate,
-        dev::MockProver,
-        poly::{
-            commitment::Blind, kzg::multiopen::ProverSHPLONK, Coeff, EvaluationDomain, Polynomial,
-        },
-        transcript::Blake2bWrite,
-    };
+    use halo2_proofs::dev::MockProver;
     use rand::thread_rng;
     use rand_core::OsRng;
     /// A KZG struct for the purpose of testing the correctness of the Verkle tree circuit
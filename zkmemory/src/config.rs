@@ -1,8 +1,14 @@
-use crate::base::Base;
+use crate::base::{Base, B32, B64};
+use crate::error::Error;
 use crate::machine::Register;
+use core::ops::{Add, Sub};
+use serde::{Deserialize, Serialize};
+
+/// Smallest number of registers, the machine always has the registers r0 to r4
+pub const MIN_REGISTERS: usize = 5;
 
 /// Memory section
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
 pub struct AllocatedSection<T>(T, T);
 
 impl<T> AllocatedSection<T>
@@ -23,15 +29,33 @@ where
     pub fn high(&self) -> T {
         self.1
     }
+
+    /// Check if two sections share at least one address
+    pub fn overlap(&self, other: &Self) -> bool {
+        self.0 <= other.1 && other.0 <= self.1
+    }
+}
+
+impl<T> AllocatedSection<T>
+where
+    T: Add<Output = T> + Sub<Output = T> + Copy,
+{
+    /// Get the size of the section in bytes, both bounds are cell addresses
+    /// in the section so the cell at the high address is counted
+    pub fn size(&self, word_size: T) -> T {
+        self.1 - self.0 + word_size
+    }
 }
 
 /// Config for RAM machine
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
 pub struct Config<T, const S: usize> {
     /// Size of a memory cell
     pub word_size: T,
     /// Stack depth
     pub stack_depth: T,
+    /// Number of registers
+    pub no_register: T,
     /// Buffer size
     pub buffer_size: T,
     /// Base address of memory
@@ -43,7 +67,7 @@ pub struct Config<T, const S: usize> {
 }
 
 /// Config arguments for RAM machine
-#[derive(Debug)]
+#[derive(Debug, Serialize, Deserialize)]
 pub struct ConfigArgs<T> {
     /// Is head layout
     pub head_layout: bool,
@@ -68,6 +92,26 @@ impl DefaultConfig {
             buffer_size: T::from(32),
         }
     }
+
+    /// Create a default config for 64-bit machine
+    pub fn default_config_64() -> ConfigArgs<B64> {
+        ConfigArgs {
+            head_layout: true,
+            stack_depth: B64::from(1024),
+            no_register: B64::from(32),
+            buffer_size: B64::from(8),
+        }
+    }
+
+    /// Create a default config for 32-bit machine
+    pub fn default_config_32() -> ConfigArgs<B32> {
+        ConfigArgs {
+            head_layout: true,
+            stack_depth: B32::from(1024),
+            no_register: B32::from(32),
+            buffer_size: B32::from(4),
+        }
+    }
 }
 
 impl<T, const S: usize> Config<T, S>
@@ -86,6 +130,7 @@ where
             Self {
                 word_size,
                 stack_depth: args.stack_depth,
+                no_register: args.no_register,
                 buffer_size: args.buffer_size,
                 stack: AllocatedSection(stack_lo, stack_hi),
                 register: AllocatedSection(register_lo, register_hi),
@@ -107,6 +152,7 @@ where
             Self {
                 word_size,
                 stack_depth: args.stack_depth,
+                no_register: args.no_register,
                 buffer_size: args.buffer_size,
                 stack: AllocatedSection(stack_lo, stack_hi),
                 register: AllocatedSection(register_lo, register_hi),
@@ -115,6 +161,24 @@ where
         }
     }
 
+    /// Validate that each section is well formed and no section overlaps another,
+    /// there must be at least [MIN_REGISTERS] registers
+    pub fn validate(&self) -> Result<(), Error> {
+        if self.no_register < T::from(MIN_REGISTERS) {
+            return Err(Error::ConfigTooFewRegisters);
+        }
+        let sections = [self.stack, self.register, self.memory];
+        if sections.iter().any(|s| s.low() > s.high()) {
+            return Err(Error::ConfigSectionOverlap);
+        }
+        for (i, a) in sections.iter().enumerate() {
+            if sections.iter().skip(i + 1).any(|b| a.overlap(b)) {
+                return Err(Error::ConfigSectionOverlap);
+            }
+        }
+        Ok(())
+    }
+
     /// Create a new register by index
     pub fn create_register(&self, index: usize) -> Register<T> {
         Register::new(
@@ -128,7 +192,10 @@ where
 mod tests {
     use super::ConfigArgs;
     use crate::base::{Base, B256};
-    use crate::config::{Config, DefaultConfig};
+    use crate::config::{Config, DefaultConfig, MIN_REGISTERS};
+    use crate::error::Error;
+    extern crate alloc;
+    use alloc::string::String;
 
     impl PartialEq for ConfigArgs<B256> {
         fn eq(&self, other: &Self) -> bool {
@@ -172,4 +239,72 @@ mod tests {
         config.create_register(0);
         assert!(!config.register.contain(B256::from(10)));
     }
+
+    #[test]
+    fn test_config_validate() {
+        let config = Config::<B256, 32>::new(B256::from(32), DefaultConfig::default_config());
+        assert_eq!(config.validate(), Ok(()));
+
+        let config = Config::<B256, 32>::new(
+            B256::from(32),
+            ConfigArgs {
+                head_layout: false,
+                stack_depth: B256::from(1024),
+                no_register: B256::from(16),
+                buffer_size: B256::from(32),
+            },
+        );
+        assert_eq!(config.validate(), Ok(()));
+
+        // Without buffer the stack and register sections share an address
+        let config = Config::<B256, 32>::new(
+            B256::from(32),
+            ConfigArgs {
+                head_layout: true,
+                stack_depth: B256::from(1024),
+                no_register: B256::from(16),
+                buffer_size: B256::zero(),
+            },
+        );
+        assert_eq!(config.validate(), Err(Error::ConfigSectionOverlap));
+
+        // The registers r0 to r4 must be configured
+        let config = Config::<B256, 32>::new(
+            B256::from(32),
+            ConfigArgs {
+                head_layout: true,
+                stack_depth: B256::from(1024),
+                no_register: B256::from(MIN_REGISTERS - 1),
+                buffer_size: B256::from(32),
+            },
+        );
+        assert_eq!(config.validate(), Err(Error::ConfigTooFewRegisters));
+    }
+
+    #[test]
+    fn test_config_serde() {
+        let json = serde_json::to_string(&DefaultConfig::default_config::<32, B256>())
+            .expect("Unable to serialize config arguments");
+        let args: ConfigArgs<B256> =
+            serde_json::from_str(&json).expect("Unable to deserialize config arguments");
+        assert_eq!(args, DefaultConfig::default_config());
+
+        // Base is serialized as hex
+        let value: serde_json::Value = serde_json::from_str(&json).unwrap();
+        assert_eq!(
+            value["stack_depth"],
+            String::from("0000000000000000000000000000000000000000000000000000000000000400")
+        );
+
+        let config = Config::<B256, 32>::new(B256::from(32), DefaultConfig::default_config());
+        let json = serde_json::to_string(&config).expect("Unable to serialize config");
+        let decoded: Config<B256, 32> =
+            serde_json::from_str(&json).expect("Unable to deserialize config");
+        assert_eq!(decoded.memory.low(), config.memory.low());
+        assert_eq!(decoded.memory.high(), config.memory.high());
+        assert_eq!(decoded.register.low(), config.register.low());
+        assert_eq!(decoded.stack.high(), config.stack.high());
+        assert_eq!(decoded.word_size, config.word_size);
+        assert_eq!(decoded.stack_depth, config.stack_depth);
+    }
 }
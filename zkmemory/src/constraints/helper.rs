@@ -3,15 +3,18 @@ use core::marker::PhantomData;
 use crate::{
     base::{Base, B256},
     constraints::consistency_check_circuit::MemoryConsistencyCircuit,
-    machine::{AbstractTraceRecord, TraceRecord},
+    machine::{AbstractTraceRecord, MemoryInstruction, TraceRecord},
 };
 extern crate alloc;
-use alloc::{vec, vec::Vec};
+use alloc::{string::String, vec, vec::Vec};
+use core::fmt::Write;
 use halo2_proofs::dev::MockProver;
 use halo2curves::pasta::Fp;
 
-// Sort the trace by address -> time_log as keys
-fn sort_trace<K, V, const S: usize, const T: usize>(
+/// Sort the trace by address -> time_log as keys, it's the order of the sorted trace
+/// consumed by [SortedMemoryCircuit](crate::constraints::sorted_memory_circuit::SortedMemoryCircuit).
+/// The sort is stable, records with the same address and time_log keep their order.
+pub fn sort_trace<K, V, const S: usize, const T: usize>(
     trace: Vec<TraceRecord<K, V, S, T>>,
 ) -> Vec<TraceRecord<K, V, S, T>>
 where
@@ -29,6 +32,79 @@ where
     buffer
 }
 
+/// Render the trace one record per line, useful to inspect the sorted trace
+pub fn format_trace<K, V, const S: usize, const T: usize>(
+    trace: &[TraceRecord<K, V, S, T>],
+) -> String
+where
+    K: Base<S>,
+    V: Base<T>,
+{
+    let mut output = String::new();
+    for record in trace.iter() {
+        writeln!(output, "{}", record).expect("Unable to write to string");
+    }
+    output
+}
+
+/// Memory consistency violation found in a trace
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ConsistencyError<K> {
+    /// The first access to an address is a read
+    ReadBeforeWrite {
+        /// Time log of the offending read
+        time_log: u64,
+        /// Address of the offending read
+        address: K,
+    },
+    /// A read does not return the last written value of the address
+    StaleRead {
+        /// Time log of the offending read
+        time_log: u64,
+        /// Address of the offending read
+        address: K,
+    },
+}
+
+/// Check the memory consistency of a trace natively, with the same rules as the sorted
+/// memory circuit: the first access to an address must be a write and each read must
+/// return the last written value. It's much cheaper than proving an inconsistent trace.
+pub fn validate_trace<K, V, const S: usize, const T: usize>(
+    trace: &[TraceRecord<K, V, S, T>],
+) -> Result<(), ConsistencyError<K>>
+where
+    K: Base<S>,
+    V: Base<T>,
+{
+    let sorted_trace = sort_trace::<K, V, S, T>(trace.to_vec());
+    let mut previous: Option<(K, V)> = None;
+    for record in sorted_trace.iter() {
+        let last_value = match previous {
+            Some((address, value)) if address == record.address() => Some(value),
+            _ => None,
+        };
+        if record.instruction() == MemoryInstruction::Read {
+            match last_value {
+                None => {
+                    return Err(ConsistencyError::ReadBeforeWrite {
+                        time_log: record.time_log(),
+                        address: record.address(),
+                    })
+                }
+                Some(value) if value != record.value() => {
+                    return Err(ConsistencyError::StaleRead {
+                        time_log: record.time_log(),
+                        address: record.address(),
+                    })
+                }
+                _ => {}
+            }
+        }
+        previous = Some((record.address(), record.value()));
+    }
+    Ok(())
+}
+
 /// Common test function to build and check the consistency circuit
 pub fn build_and_test_circuit(trace: Vec<TraceRecord<B256, B256, 32, 32>>, k: u32) {
     // Sort this trace (already sorted by time_log) in address and time_log order
@@ -47,7 +123,7 @@ pub fn build_and_test_circuit(trace: Vec<TraceRecord<B256, B256, 32, 32>>, k: u3
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::machine::MemoryInstruction;
+    use alloc::format;
 
     #[test]
     #[should_panic]
@@ -382,4 +458,155 @@ mod tests {
             10,
         );
     }
+
+    #[test]
+    fn validate_valid_trace() {
+        let trace = vec![
+            TraceRecord::<B256, B256, 32, 32>::new(
+                0,
+                0,
+                MemoryInstruction::Write,
+                B256::from(0x20),
+                B256::from(7),
+            ),
+            TraceRecord::new(1, 0, MemoryInstruction::Write, B256::from(0), B256::from(1)),
+            TraceRecord::new(
+                2,
+                0,
+                MemoryInstruction::Read,
+                B256::from(0x20),
+                B256::from(7),
+            ),
+            TraceRecord::new(
+                3,
+                0,
+                MemoryInstruction::Write,
+                B256::from(0x20),
+                B256::from(9),
+            ),
+            TraceRecord::new(
+                4,
+                0,
+                MemoryInstruction::Read,
+                B256::from(0x20),
+                B256::from(9),
+            ),
+            TraceRecord::new(5, 0, MemoryInstruction::Read, B256::from(0), B256::from(1)),
+        ];
+        assert_eq!(validate_trace(&trace), Ok(()));
+    }
+
+    #[test]
+    fn validate_read_before_write() {
+        let trace = vec![
+            TraceRecord::<B256, B256, 32, 32>::new(
+                0,
+                0,
+                MemoryInstruction::Write,
+                B256::from(0),
+                B256::from(1),
+            ),
+            TraceRecord::new(
+                1,
+                0,
+                MemoryInstruction::Read,
+                B256::from(0x20),
+                B256::from(0),
+            ),
+        ];
+        assert_eq!(
+            validate_trace(&trace),
+            Err(ConsistencyError::ReadBeforeWrite {
+                time_log: 1,
+                address: B256::from(0x20)
+            })
+        );
+    }
+
+    #[test]
+    fn validate_stale_read() {
+        let trace = vec![
+            TraceRecord::<B256, B256, 32, 32>::new(
+                0,
+                0,
+                MemoryInstruction::Write,
+                B256::from(0),
+                B256::from(1),
+            ),
+            TraceRecord::new(1, 0, MemoryInstruction::Write, B256::from(0), B256::from(2)),
+            TraceRecord::new(2, 0, MemoryInstruction::Read, B256::from(0), B256::from(1)),
+        ];
+        assert_eq!(
+            validate_trace(&trace),
+            Err(ConsistencyError::StaleRead {
+                time_log: 2,
+                address: B256::from(0)
+            })
+        );
+    }
+
+    #[test]
+    fn sort_trace_should_be_stable_and_start_with_write() {
+        let trace = vec![
+            TraceRecord::<B256, B256, 32, 32>::new(
+                0,
+                0,
+                MemoryInstruction::Write,
+                B256::from(0x20),
+                B256::from(1),
+            ),
+            TraceRecord::new(1, 0, MemoryInstruction::Write, B256::from(0), B256::from(2)),
+            TraceRecord::new(
+                2,
+                0,
+                MemoryInstruction::Read,
+                B256::from(0x20),
+                B256::from(1),
+            ),
+            TraceRecord::new(3, 0, MemoryInstruction::Read, B256::from(0), B256::from(2)),
+            // Same address and time_log, must keep the original order
+            TraceRecord::new(4, 0, MemoryInstruction::Write, B256::from(0), B256::from(3)),
+            TraceRecord::new(4, 0, MemoryInstruction::Write, B256::from(0), B256::from(4)),
+        ];
+        let sorted_trace = sort_trace(trace.clone());
+        assert_eq!(
+            sorted_trace,
+            vec![trace[1], trace[3], trace[4], trace[5], trace[0], trace[2]]
+        );
+
+        // First access of each address is a write
+        let mut previous_address = None;
+        for record in sorted_trace.iter() {
+            if previous_address != Some(record.address()) {
+                assert_eq!(record.instruction(), MemoryInstruction::Write);
+            }
+            previous_address = Some(record.address());
+        }
+    }
+
+    #[test]
+    fn format_trace_should_print_one_record_per_line() {
+        let trace = vec![
+            TraceRecord::<B256, B256, 32, 32>::new(
+                0,
+                0,
+                MemoryInstruction::Write,
+                B256::from(0x20),
+                B256::from(1),
+            ),
+            TraceRecord::new(
+                1,
+                0,
+                MemoryInstruction::Read,
+                B256::from(0x20),
+                B256::from(1),
+            ),
+        ];
+        let output = format_trace(&sort_trace(trace.clone()));
+        assert_eq!(output.lines().count(), 2);
+        assert_eq!(
+            output.lines().next(),
+            Some(format!("{}", trace[0]).as_str())
+        );
+    }
 }
@@ -15,11 +15,61 @@ pub enum Error {
     StackOverflow,
     /// Stack underflow
     StackUnderflow,
+    /// Time log overflow
+    TimeLogOverflow,
+    /// Number of bytes does not match the size of base
+    BaseLengthMismatch,
+    /// Base is not encoded as a valid hex string
+    BaseInvalidHex,
+    /// Config sections are overlapped or out of range
+    ConfigSectionOverlap,
+    /// Config is not a valid JSON
+    ConfigInvalidJson,
+    /// Config has fewer registers than the named registers r0 to r4
+    ConfigTooFewRegisters,
+    /// Step budget of the machine is exhausted
+    StepLimitExceeded,
+    /// Vector is longer than the capacity of the commitment scheme
+    CommitmentCapacityExceeded,
+    /// Index is out of the committed vector
+    CommitmentIndexOutOfRange,
 }
 
 #[cfg(feature = "std")]
 impl std::error::Error for Error {}
 
+/// Error of converting bytes to base
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub enum BaseConversionError {
+    /// Number of bytes does not match the size of base
+    InvalidLength {
+        /// Expected number of bytes
+        expected: usize,
+        /// Actual number of bytes
+        actual: usize,
+    },
+}
+
+impl core::fmt::Display for BaseConversionError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            BaseConversionError::InvalidLength { expected, actual } => write!(
+                f,
+                "Base conversion expected {} bytes, got {} bytes",
+                expected, actual
+            ),
+        }
+    }
+}
+
+impl From<BaseConversionError> for Error {
+    fn from(value: BaseConversionError) -> Self {
+        match value {
+            BaseConversionError::InvalidLength { .. } => Error::BaseLengthMismatch,
+        }
+    }
+}
+
 impl core::fmt::Display for Error {
     fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
         match self {
@@ -30,6 +80,15 @@ impl core::fmt::Display for Error {
             Error::RegisterUnableToAssign => write!(f, "Register unable to assign"),
             Error::StackOverflow => write!(f, "Stack overflow"),
             Error::StackUnderflow => write!(f, "Stack underflow"),
+            Error::TimeLogOverflow => write!(f, "Time log overflow"),
+            Error::BaseLengthMismatch => write!(f, "Base length mismatch"),
+            Error::BaseInvalidHex => write!(f, "Base invalid hex"),
+            Error::ConfigSectionOverlap => write!(f, "Config section overlap"),
+            Error::ConfigInvalidJson => write!(f, "Config invalid JSON"),
+            Error::ConfigTooFewRegisters => write!(f, "Config too few registers"),
+            Error::StepLimitExceeded => write!(f, "Step limit exceeded"),
+            Error::CommitmentCapacityExceeded => write!(f, "Commitment capacity exceeded"),
+            Error::CommitmentIndexOutOfRange => write!(f, "Commitment index out of range"),
         }
     }
 }
@@ -65,5 +124,35 @@ mod tests {
         );
         assert_eq!(format!("{}", Error::StackOverflow), "Stack overflow");
         assert_eq!(format!("{}", Error::StackUnderflow), "Stack underflow");
+        assert_eq!(format!("{}", Error::TimeLogOverflow), "Time log overflow");
+        assert_eq!(
+            format!("{}", Error::BaseLengthMismatch),
+            "Base length mismatch"
+        );
+        assert_eq!(format!("{}", Error::BaseInvalidHex), "Base invalid hex");
+        assert_eq!(
+            format!("{}", Error::ConfigSectionOverlap),
+            "Config section overlap"
+        );
+        assert_eq!(
+            format!("{}", Error::ConfigInvalidJson),
+            "Config invalid JSON"
+        );
+        assert_eq!(
+            format!("{}", Error::ConfigTooFewRegisters),
+            "Config too few registers"
+        );
+        assert_eq!(
+            format!("{}", Error::StepLimitExceeded),
+            "Step limit exceeded"
+        );
+        assert_eq!(
+            format!("{}", Error::CommitmentCapacityExceeded),
+            "Commitment capacity exceeded"
+        );
+        assert_eq!(
+            format!("{}", Error::CommitmentIndexOutOfRange),
+            "Commitment index out of range"
+        );
     }
 }
@@ -0,0 +1,599 @@
+extern crate alloc;
+use crate::{
+    base::{Base, B256, B32, B64},
+    commitment::{merkle_tree::merkle_tree_root, trace_commitment::*},
+    config::{AllocatedSection, Config, ConfigArgs},
+    error::Error,
+    machine::{
+        AbstractContext, AbstractInstruction, AbstractMachine, AbstractMemoryMachine,
+        AbstractRegisterMachine, AbstractStackMachine, CellInteraction, Register, TraceRecord,
+    },
+};
+use alloc::vec::Vec;
+use core::{cmp::Ordering, fmt, marker::PhantomData};
+use ff::{Field, PrimeField};
+use halo2_proofs::halo2curves::pasta::Fp;
+use poseidon::poseidon_hash::{ConstantLength, Hash, OrchardNullifier, Spec};
+use rbtree::RBTree;
+use serde::de::DeserializeOwned;
+
+/// My instruction set for the machine
+#[derive(Debug, Clone, Copy)]
+pub enum MyInstruction<M, K, V, const S: usize, const T: usize>
+where
+    K: Base<S>,
+    V: Base<T>,
+{
+    /// Read from memory
+    Read(K),
+    /// Write to memory
+    Write(K, V),
+    /// Push to stack
+    Push(V),
+    /// Pop from stack
+    Pop(),
+    /// Move from register to register (Mov(r2, r1) moves the value of r1 to r2)
+    Mov(Register<K>, Register<K>),
+    /// Swap value from top stack  to register
+    Swap(Register<K>),
+    /// Load from memory to register
+    Load(Register<K>, K),
+    /// Save from register to memory
+    Save(K, Register<K>),
+    /// Invalid instruction
+    Invalid(PhantomData<M>),
+    /// Add two registers, register 1 = register 1 + register 2
+    Add(Register<K>, Register<K>),
+    /// Load immediate value to register
+    Li(Register<K>, V),
+    /// Compare two registers, Eq(r1, r2, r3) sets r1 to 1 if r2 == r3, otherwise 0
+    Eq(Register<K>, Register<K>, Register<K>),
+    /// Compare two registers, Lt(r1, r2, r3) sets r1 to 1 if r2 < r3, otherwise 0
+    Lt(Register<K>, Register<K>, Register<K>),
+    /// Signed compare, Slt(r1, r2, r3) sets r1 to 1 if r2 < r3 as two's complement, otherwise 0
+    Slt(Register<K>, Register<K>, Register<K>),
+    /// Signed compare, Sgt(r1, r2, r3) sets r1 to 1 if r2 > r3 as two's complement, otherwise 0
+    Sgt(Register<K>, Register<K>, Register<K>),
+}
+
+/// Type alias Instruction
+pub type Instruction = MyInstruction<StateMachine<B256, B256, 32, 32>, B256, B256, 32, 32>;
+
+/// 32-bit RAM machine
+pub type StateMachine32 = StateMachine<B32, B32, 4, 4>;
+
+/// 64-bit RAM machine
+pub type StateMachine64 = StateMachine<B64, B64, 8, 8>;
+
+/// RAM Machine
+#[derive(Debug, Clone)]
+pub struct StateMachine<K, V, const S: usize, const T: usize>
+where
+    K: Base<S>,
+    V: Base<T>,
+{
+    // Memory
+    pub(crate) memory: RBTree<K, V>,
+    pub(crate) memory_allocated: AllocatedSection<K>,
+    pub(crate) word_size: K,
+    pub(crate) time_log: u64,
+
+    // Stack
+    pub(crate) stack_allocated: AllocatedSection<K>,
+    pub(crate) max_stack_depth: u64,
+    pub(crate) stack_depth: u64,
+    pub(crate) stack_ptr: K,
+
+    // Register
+    pub(crate) register_allocated: AllocatedSection<K>,
+    pub(crate) registers: Vec<Register<K>>,
+
+    // Step budget, `None` means unlimited
+    pub(crate) step_limit: Option<u64>,
+    pub(crate) steps: u64,
+
+    /// Register r0
+    pub r0: Register<K>,
+    /// Register r1
+    pub r1: Register<K>,
+    /// Register r2
+    pub r2: Register<K>,
+    /// Register r3
+    pub r3: Register<K>,
+    /// Register r4
+    pub r4: Register<K>,
+
+    // Trace
+    #[cfg(not(feature = "commit-only"))]
+    pub(crate) execution_trace: RBTree<TraceRecord<K, V, S, T>, PhantomData<()>>,
+    #[cfg(feature = "commit-only")]
+    pub(crate) execution_commitment: Fp,
+}
+
+impl<M, K, V, const S: usize, const T: usize> AbstractContext<M, K, V> for StateMachine<K, V, S, T>
+where
+    Self: core::fmt::Debug
+        + Sized
+        + AbstractMachine<K, V, Context = M::Context, Instruction = M::Instruction>,
+    K: Base<S>,
+    V: Base<T>,
+    M: AbstractMachine<K, V, Machine = StateMachine<K, V, S, T>>,
+{
+    fn set_stack_depth(&mut self, stack_depth: u64) {
+        self.stack_depth = stack_depth;
+    }
+
+    fn stack_depth(&self) -> u64 {
+        self.stack_depth
+    }
+
+    fn stack_ptr(&self) -> K {
+        self.stack_ptr
+    }
+
+    fn time_log(&self) -> u64 {
+        self.time_log
+    }
+
+    fn set_time_log(&mut self, time_log: u64) {
+        self.time_log = time_log;
+    }
+
+    fn set_stack_ptr(&mut self, stack_ptr: K) {
+        self.stack_ptr = stack_ptr;
+    }
+
+    fn memory(&mut self) -> &'_ mut RBTree<K, V> {
+        &mut self.memory
+    }
+
+    fn ro_memory(&self) -> &'_ RBTree<K, V> {
+        &self.memory
+    }
+}
+
+impl<M, K, V, const S: usize, const T: usize> AbstractInstruction<M, K, V>
+    for MyInstruction<M, K, V, S, T>
+where
+    Self: core::fmt::Debug + Sized,
+    K: Base<S>,
+    V: Base<T>,
+    Fp: From<K> + From<V>,
+    M: AbstractMachine<K, V, Machine = StateMachine<K, V, S, T>>,
+{
+    fn exec(&self, machine: &mut M::Machine) {
+        match self {
+            MyInstruction::Invalid(_) => {
+                panic!("Invalid instruction")
+            }
+            MyInstruction::Read(addr) => {
+                if !machine.memory_allocated.contain(*addr) {
+                    panic!("{}", Error::MemoryAccessDeinied);
+                } else {
+                    machine.read(*addr).expect("Unable to read to memory");
+                }
+            }
+            MyInstruction::Write(addr, val) => {
+                if !machine.memory_allocated.contain(*addr) {
+                    panic!("{}", Error::MemoryAccessDeinied);
+                } else {
+                    machine
+                        .write(*addr, *val)
+                        .expect("Unable to write to memory");
+                }
+            }
+            MyInstruction::Push(value) => {
+                machine.push(*value).expect("Unable to push value to stack");
+            }
+            MyInstruction::Pop() => {
+                machine.pop().expect("Unable to pop value from stack");
+            }
+            MyInstruction::Mov(reg1, reg2) => {
+                match machine.get(*reg2).expect("Unable to access register 1") {
+                    CellInteraction::SingleCell(_, _, value) => {
+                        machine.set(*reg1, value).expect("Unable to set register 2");
+                    }
+                    _ => panic!("Register unable to be two cells"),
+                }
+                // Mov value from register 2 to register 1
+            }
+            MyInstruction::Swap(reg) => {
+                match machine.pop().expect("Unable to pop value from stack") {
+                    (_, CellInteraction::SingleCell(_op, _addr, value)) => {
+                        machine
+                            .push(value)
+                            .expect("Unable to push register's value to stack");
+                        machine.set(*reg, value).expect("Unable to set register");
+                    }
+                    _ => panic!("Stack unable to be two cells"),
+                };
+            }
+            MyInstruction::Load(reg, addr) => {
+                match machine.read(*addr).expect("Unable to read memory") {
+                    CellInteraction::SingleCell(_, _, value) => {
+                        machine.set(*reg, value).expect("Unable to set register");
+                    }
+                    CellInteraction::DoubleCell(_, _, cvalue, _, _, _, _) => {
+                        machine.set(*reg, cvalue).expect("Unable to set register");
+                    }
+                };
+            }
+            MyInstruction::Save(address, reg) => {
+                match machine.get(*reg).expect("Unable to access register") {
+                    CellInteraction::SingleCell(_, _, value) => {
+                        machine
+                            .write(*address, value)
+                            .expect("Unable to write to memory");
+                    }
+                    _ => panic!("Register unable to be two cells"),
+                }
+            }
+            MyInstruction::Li(reg, value) => {
+                machine.set(*reg, *value).expect("Unable to set register");
+            }
+            MyInstruction::Eq(dst, reg1, reg2) => {
+                match (
+                    machine.get(*reg1).expect("Unable to access register 1"),
+                    machine.get(*reg2).expect("Unable to access register 2"),
+                ) {
+                    (
+                        CellInteraction::SingleCell(_, _, value1),
+                        CellInteraction::SingleCell(_, _, value2),
+                    ) => {
+                        let result = if value1 == value2 { 1 } else { 0 };
+                        machine
+                            .set(*dst, V::from(result))
+                            .expect("Unable to set destination register");
+                    }
+                    _ => panic!("Register unable to be two cells"),
+                }
+            }
+            MyInstruction::Lt(dst, reg1, reg2) => {
+                match (
+                    machine.get(*reg1).expect("Unable to access register 1"),
+                    machine.get(*reg2).expect("Unable to access register 2"),
+                ) {
+                    (
+                        CellInteraction::SingleCell(_, _, value1),
+                        CellInteraction::SingleCell(_, _, value2),
+                    ) => {
+                        let result = if value1 < value2 { 1 } else { 0 };
+                        machine
+                            .set(*dst, V::from(result))
+                            .expect("Unable to set destination register");
+                    }
+                    _ => panic!("Register unable to be two cells"),
+                }
+            }
+            MyInstruction::Slt(dst, reg1, reg2) | MyInstruction::Sgt(dst, reg1, reg2) => {
+                let expected = match self {
+                    MyInstruction::Slt(..) => Ordering::Less,
+                    _ => Ordering::Greater,
+                };
+                match (
+                    machine.get(*reg1).expect("Unable to access register 1"),
+                    machine.get(*reg2).expect("Unable to access register 2"),
+                ) {
+                    (
+                        CellInteraction::SingleCell(_, _, value1),
+                        CellInteraction::SingleCell(_, _, value2),
+                    ) => {
+                        let result = if value1.signed_cmp(&value2) == expected {
+                            1
+                        } else {
+                            0
+                        };
+                        machine
+                            .set(*dst, V::from(result))
+                            .expect("Unable to set destination register");
+                    }
+                    _ => panic!("Register unable to be two cells"),
+                }
+            }
+            MyInstruction::Add(reg1, reg2) => {
+                match machine.get(*reg1).expect("Unable to access register 1") {
+                    CellInteraction::SingleCell(_, _, value1) => {
+                        match machine.get(*reg2).expect("Unable to access register 2") {
+                            CellInteraction::SingleCell(_, _, value2) => {
+                                machine
+                                    .set(*reg1, value1 + value2)
+                                    .expect("Unable to set register 1");
+                            }
+                            _ => panic!("Register unable to be two cells"),
+                        }
+                    }
+                    _ => panic!("Register unable to be two cells"),
+                }
+            }
+        }
+    }
+}
+
+impl<K, V, const S: usize, const T: usize> StateMachine<K, V, S, T>
+where
+    K: Base<S>,
+    V: Base<T>,
+    Fp: From<K> + From<V>,
+{
+    /// Create a new RAM machine, panic if the config sections are overlapped
+    pub fn new(config: ConfigArgs<K>) -> Self {
+        Self::try_new(config).expect("Invalid config")
+    }
+
+    /// Create a new RAM machine from config arguments encoded in JSON
+    pub fn new_from_config_json(json: &str) -> Result<Self, Error>
+    where
+        K: DeserializeOwned,
+    {
+        let config: ConfigArgs<K> =
+            serde_json::from_str(json).map_err(|_| Error::ConfigInvalidJson)?;
+        Self::try_new(config)
+    }
+
+    /// Create a new RAM machine, the config sections must not overlap
+    pub fn try_new(config: ConfigArgs<K>) -> Result<Self, Error> {
+        let config = Config::new(K::WORD_SIZE, config);
+        config.validate()?;
+        let no_register: usize = config.no_register.into();
+        Ok(Self {
+            // Memory section
+            memory: RBTree::new(),
+            memory_allocated: config.memory,
+            word_size: config.word_size,
+            time_log: 0,
+
+            // Stack
+            stack_allocated: config.stack,
+            max_stack_depth: config.stack_depth.into(),
+            stack_depth: 0,
+            stack_ptr: K::zero(),
+
+            // Register
+            register_allocated: config.register,
+            registers: (0..no_register)
+                .map(|i| config.create_register(i))
+                .collect(),
+            r0: config.create_register(0),
+            r1: config.create_register(1),
+            r2: config.create_register(2),
+            r3: config.create_register(3),
+            r4: config.create_register(4),
+
+            // Step budget
+            step_limit: None,
+            steps: 0,
+
+            // Execution trace
+            #[cfg(not(feature = "commit-only"))]
+            execution_trace: RBTree::new(),
+            #[cfg(feature = "commit-only")]
+            execution_commitment: Fp::ZERO,
+        })
+    }
+
+    /// Get the hash chain commitment of the execution trace
+    #[cfg(not(feature = "commit-only"))]
+    pub fn trace_commitment(&self) -> Fp {
+        trace_commitment(&self.trace())
+    }
+
+    /// Get the hash chain commitment of the execution trace
+    #[cfg(feature = "commit-only")]
+    pub fn trace_commitment(&self) -> Fp {
+        self.execution_commitment
+    }
+
+    /// Write the execution trace to `w` in CSV, a header row then one row per trace
+    /// record in trace order, address and value are hex encoded
+    pub fn write_trace_csv(&self, mut w: impl fmt::Write) -> fmt::Result {
+        writeln!(w, "time_log,stack_depth,instruction,address,value")?;
+        for record in self.trace() {
+            let (time_log, stack_depth, instruction, address, value) = record.get_tuple();
+            writeln!(
+                w,
+                "{},{},{:?},0x{},0x{}",
+                time_log,
+                stack_depth,
+                instruction,
+                address.to_hex(),
+                value.to_hex()
+            )?;
+        }
+        Ok(())
+    }
+
+    /// Inject a crafted trace record, e.g. to test the consistency circuits with a hand
+    /// built trace. Neither memory nor the time log of the machine is changed
+    pub fn insert_raw_trace(&mut self, record: TraceRecord<K, V, S, T>) {
+        self.track(record);
+    }
+
+    /// Compute the Poseidon merkle root of the current memory, each leaf is the hash of
+    /// an address and its value in address order. Zero cells are skipped since they can't
+    /// be distinguished from unwritten cells. The whole tree is recomputed on each call.
+    pub fn current_root<F>(&self) -> F
+    where
+        F: Field + PrimeField + From<K> + From<V>,
+        OrchardNullifier: Spec<F, 3, 2>,
+    {
+        let leaves = self
+            .memory
+            .iter()
+            .filter(|(_, value)| !value.is_zero())
+            .map(|(address, value)| {
+                Hash::<F, OrchardNullifier, ConstantLength<2>, 3, 2>::init()
+                    .hash([F::from(*address), F::from(*value)])
+            })
+            .collect::<Vec<F>>();
+        merkle_tree_root(&leaves)
+    }
+
+    /// Set the maximum number of instructions the machine executes, `None` removes the limit.
+    /// Instructions executed before are counted against the new limit.
+    pub fn set_step_limit(&mut self, step_limit: Option<u64>) {
+        self.step_limit = step_limit;
+    }
+
+    /// Get the number of executed instructions
+    pub fn steps(&self) -> u64 {
+        self.steps
+    }
+
+    /// Execute an instruction, [Error::StepLimitExceeded] is returned without executing it
+    /// if the step budget is exhausted
+    pub fn try_exec(&mut self, instruction: &MyInstruction<Self, K, V, S, T>) -> Result<(), Error> {
+        if let Some(step_limit) = self.step_limit {
+            if self.steps >= step_limit {
+                return Err(Error::StepLimitExceeded);
+            }
+        }
+        self.steps += 1;
+        instruction.exec(self);
+        Ok(())
+    }
+
+    /// Get the register by index, `None` if the index is out of the configured registers
+    pub fn register(&self, index: usize) -> Option<Register<K>> {
+        self.registers.get(index).copied()
+    }
+
+    /// Load a contiguous byte image into memory starting at `base`, each cell is
+    /// written with a [Write](crate::machine::MemoryInstruction::Write) trace record.
+    /// `base` must be aligned to the word size and the image must fit in the memory section.
+    /// If the image does not fill the last cell, the remaining bytes of that cell are kept.
+    pub fn load_image(&mut self, base: K, bytes: &[u8]) -> Result<(), Error> {
+        if bytes.is_empty() {
+            return Ok(());
+        }
+        if !(base % self.word_size).is_zero() {
+            return Err(Error::MemoryInvalidInteraction);
+        }
+        let cells = bytes.len().div_ceil(T);
+        let last = base + K::from(cells - 1) * self.word_size;
+        if last < base
+            || !self.memory_allocated.contain(base)
+            || !self.memory_allocated.contain(last)
+        {
+            return Err(Error::MemoryAccessDeinied);
+        }
+        for (i, chunk) in bytes.chunks(T).enumerate() {
+            let address = base + K::from(i) * self.word_size;
+            let mut buf: [u8; T] = self.dummy_read(address).into();
+            buf[..chunk.len()].copy_from_slice(chunk);
+            self.write(address, V::from(buf))?;
+        }
+        Ok(())
+    }
+}
+
+impl<K, V, const S: usize, const T: usize> AbstractMachine<K, V> for StateMachine<K, V, S, T>
+where
+    K: Base<S>,
+    V: Base<T>,
+    Fp: From<K> + From<V>,
+{
+    type Machine = Self;
+    type Context = Self;
+    type Instruction = MyInstruction<Self, K, V, S, T>;
+    type TraceRecord = TraceRecord<K, V, S, T>;
+
+    fn context(&mut self) -> &'_ mut Self::Context {
+        self
+    }
+
+    fn word_size(&self) -> K {
+        self.word_size
+    }
+
+    fn register_start(&self) -> K {
+        self.register_allocated.low()
+    }
+
+    fn ro_context(&self) -> &'_ Self::Context {
+        self
+    }
+
+    #[cfg(not(feature = "commit-only"))]
+    fn track(&mut self, trace: Self::TraceRecord) {
+        self.execution_trace.insert(trace, PhantomData);
+    }
+
+    #[cfg(feature = "commit-only")]
+    fn track(&mut self, trace: Self::TraceRecord) {
+        self.execution_commitment = fold_trace_record(self.execution_commitment, &trace);
+    }
+
+    #[cfg(not(feature = "commit-only"))]
+    fn trace(&self) -> Vec<Self::TraceRecord> {
+        self.execution_trace.keys().copied().collect()
+    }
+
+    /// The trace is not stored in commit-only mode, use
+    /// [trace_commitment](StateMachine::trace_commitment) instead
+    #[cfg(feature = "commit-only")]
+    fn trace(&self) -> Vec<Self::TraceRecord> {
+        Vec::new()
+    }
+
+    /// Panic with [Error::StepLimitExceeded] if the step budget is exhausted, use
+    /// [try_exec](StateMachine::try_exec) to handle it
+    fn exec(&mut self, instruction: &Self::Instruction) {
+        if let Err(err) = self.try_exec(instruction) {
+            panic!("{}", err);
+        }
+    }
+
+    fn base_address(&self) -> K {
+        self.memory_allocated.low()
+    }
+
+    fn get_memory_address(&self) -> (K, K) {
+        (self.memory_allocated.low(), self.memory_allocated.high())
+    }
+
+    fn memory_capacity(&self) -> K {
+        self.memory_allocated.size(self.word_size)
+    }
+
+    fn stack_capacity(&self) -> K {
+        self.stack_allocated.size(self.word_size)
+    }
+
+    fn get_stack_depth(&self) -> u64 {
+        self.ro_context().stack_depth
+    }
+
+    fn max_stack_depth(&self) -> u64 {
+        self.ro_context().max_stack_depth
+    }
+}
+
+impl<K, V, const S: usize, const T: usize> AbstractMemoryMachine<K, V, S, T>
+    for StateMachine<K, V, S, T>
+where
+    K: Base<S>,
+    V: Base<T>,
+    Self: AbstractMachine<K, V>,
+{
+}
+
+impl<K, V, const S: usize, const T: usize> AbstractRegisterMachine<K, V, S, T>
+    for StateMachine<K, V, S, T>
+where
+    K: Base<S>,
+    V: Base<T>,
+    Self: AbstractMemoryMachine<K, V, S, T>,
+{
+    fn new_register(&self, register_index: usize) -> Option<Register<K>> {
+        self.register(register_index)
+    }
+}
+
+impl<K, V, const S: usize, const T: usize> AbstractStackMachine<K, V, S, T>
+    for StateMachine<K, V, S, T>
+where
+    K: Base<S>,
+    V: Base<T>,
+    Self: AbstractMemoryMachine<K, V, S, T>,
+{
+}
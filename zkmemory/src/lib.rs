@@ -23,6 +23,8 @@ pub mod config;
 pub mod constraints;
 /// Define all errors of `StateMachine`
 pub mod error;
+/// Example instruction set and RAM machine built on top of the abstract machine
+pub mod instruction;
 /// Definition of abstract machine (instruction, trace and context)
 pub mod machine;
 /// Memory consistency circuit using Nova proof system
@@ -32,8 +34,12 @@ pub mod supernova;
 #[cfg(test)]
 mod tests {
     extern crate alloc;
-    use crate::base::{Base, B128, B256, B32, B64};
-    use alloc::format;
+    use crate::{
+        base::{Base, B128, B16, B256, B32, B64},
+        error::{BaseConversionError, Error},
+    };
+    use alloc::{format, string::ToString};
+    use core::cmp::Ordering;
 
     #[test]
     fn base_struct_test() {
@@ -121,6 +127,48 @@ mod tests {
         assert_eq!(chunk_4 % chunk_3, B32::from(156 % 5));
     }
 
+    #[test]
+    fn base_pow_test() {
+        assert_eq!(B256::from(2).pow(&B256::from(8)), B256::from(256));
+        assert_eq!(B256::zero().pow(&B256::zero()), B256::from(1));
+        assert_eq!(B32::from(7).pow(&B32::zero()), B32::from(1));
+        assert_eq!(B64::from(2).pow(&B64::from(63)), B64::from(1u64 << 63));
+
+        // Overflow wraps modulo 2^(8 * S)
+        assert_eq!(B256::from(2).pow(&B256::from(256)), B256::zero());
+        assert_eq!(B16::from(2).pow(&B16::from(17)), B16::zero());
+
+        // Large exponents wrap the same way as repeated multiplication
+        let (mut b256, mut b128, mut b64, mut b32) = (B256::from(1).0, 1u128, 1u64, 1u32);
+        for _ in 0..300 {
+            b256 = b256.wrapping_mul(B256::from(3).0);
+            b128 = b128.wrapping_mul(3);
+            b64 = b64.wrapping_mul(3);
+            b32 = b32.wrapping_mul(3);
+        }
+        assert_eq!(B256::from(3).pow(&B256::from(300)).0, b256);
+        assert_eq!(B128::from(3).pow(&B128::from(300)).0, b128);
+        assert_eq!(B64::from(3).pow(&B64::from(300)).0, b64);
+        assert_eq!(B32::from(3).pow(&B32::from(300)).0, b32);
+    }
+
+    #[test]
+    fn base_signed_cmp_test() {
+        // B256::MAX is -1 in two's complement
+        assert_eq!(B256::MAX.signed_cmp(&B256::from(1)), Ordering::Less);
+        assert_eq!(B256::MAX.cmp(&B256::from(1)), Ordering::Greater);
+        assert_eq!(B256::MAX.signed_cmp(&B256::MAX), Ordering::Equal);
+        assert_eq!(B256::zero().signed_cmp(&B256::MAX), Ordering::Greater);
+
+        // Most negative and most positive values
+        let min = B32::from(0x8000_0000u64);
+        let max = B32::from(0x7fff_ffffu64);
+        assert_eq!(min.signed_cmp(&max), Ordering::Less);
+        assert_eq!(min.cmp(&max), Ordering::Greater);
+        assert_eq!(B32::MAX.signed_cmp(&min), Ordering::Greater);
+        assert_eq!(B16::from(2).signed_cmp(&B16::from(3)), Ordering::Less);
+    }
+
     #[test]
     fn base_display_test() {
         let chunk = B32::from([0x23u8; 4]);
@@ -179,4 +227,111 @@ mod tests {
         assert_eq!(num.fixed_be_bytes(), chunk_be);
         assert_eq!(num.fixed_le_bytes(), chunk_le);
     }
+
+    #[test]
+    fn base_try_from_bytes_test() {
+        let be_bytes = [1u8, 2, 3, 4, 5, 6, 7, 8];
+        let mut le_bytes = be_bytes;
+        le_bytes.reverse();
+        let chunk = B64::from(0x0102030405060708u64);
+        assert_eq!(B64::try_from_be_bytes(&be_bytes), Ok(chunk));
+        assert_eq!(B64::try_from_le_bytes(&le_bytes), Ok(chunk));
+
+        let bytes = [7u8; 32];
+        assert_eq!(B256::try_from_be_bytes(&bytes), Ok(B256::from(bytes)));
+
+        // Short and long inputs must be rejected
+        assert_eq!(
+            B64::try_from_be_bytes(&be_bytes[..7]),
+            Err(Error::BaseLengthMismatch)
+        );
+        assert_eq!(
+            B32::try_from_le_bytes(&be_bytes),
+            Err(Error::BaseLengthMismatch)
+        );
+        assert_eq!(
+            B256::try_from_be_bytes(&[0u8; 33]),
+            Err(Error::BaseLengthMismatch)
+        );
+        assert_eq!(B128::try_from_le_bytes(&[]), Err(Error::BaseLengthMismatch));
+    }
+
+    #[test]
+    fn base_hex_test() {
+        let chunk = B256::from(255);
+        let hex_string = chunk.to_hex();
+        assert_eq!(hex_string.len(), 64);
+        assert!(hex_string.ends_with("ff"));
+        assert_eq!(B256::from_hex(&hex_string), Ok(chunk));
+        assert_eq!(B256::from_hex(&format!("0x{}", hex_string)), Ok(chunk));
+
+        let chunk = B256::from([0xabu8; 32]);
+        assert_eq!(B256::from_hex(&chunk.to_hex()), Ok(chunk));
+
+        let chunk = B32::from(0x0badf00d);
+        assert_eq!(chunk.to_hex(), "0badf00d".to_string());
+        assert_eq!(B32::from_hex("0x0BADF00D"), Ok(chunk));
+
+        // Invalid inputs
+        assert_eq!(B32::from_hex("0xzzzzzzzz"), Err(Error::BaseInvalidHex));
+        assert_eq!(B32::from_hex("0x123"), Err(Error::BaseInvalidHex));
+        assert_eq!(B32::from_hex("0x0011"), Err(Error::BaseLengthMismatch));
+    }
+
+    #[test]
+    fn base_bit_test() {
+        let chunk = B256::from(5);
+        assert!(chunk.bit(0));
+        assert!(!chunk.bit(1));
+        assert!(chunk.bit(2));
+        assert!(!chunk.bit(3));
+        assert_eq!(chunk.bit_length(), 3);
+
+        // Out of range bits are false
+        assert!(!B256::MAX.bit(256));
+        assert!(!B32::MAX.bit(32));
+        assert!(B32::MAX.bit(31));
+
+        // Bits across byte boundaries
+        let chunk = B64::from(0x0100u64);
+        assert!(chunk.bit(8));
+        assert!(!chunk.bit(7));
+        assert_eq!(chunk.bit_length(), 9);
+
+        assert_eq!(B256::zero().bit_length(), 0);
+        assert_eq!(B128::from(1).bit_length(), 1);
+        assert_eq!(B256::MAX.bit_length(), 256);
+        assert_eq!(B16::MAX.bit_length(), 16);
+    }
+
+    #[test]
+    fn base_try_from_slice_test() {
+        let bytes = [7u8; 32];
+        assert_eq!(B256::try_from(&bytes[..]), Ok(B256::from(bytes)));
+        assert_eq!(B64::try_from(&bytes[..8]), Ok(B64::from([7u8; 8])));
+
+        let err = B256::try_from(&bytes[..31]).unwrap_err();
+        assert_eq!(
+            err,
+            BaseConversionError::InvalidLength {
+                expected: 32,
+                actual: 31
+            }
+        );
+        assert_eq!(
+            format!("{}", err),
+            "Base conversion expected 32 bytes, got 31 bytes"
+        );
+        assert_eq!(
+            B16::try_from(&bytes[..3]).unwrap_err(),
+            BaseConversionError::InvalidLength {
+                expected: 2,
+                actual: 3
+            }
+        );
+
+        // Propagate to the machine error
+        let convert = |bytes: &[u8]| -> Result<B32, Error> { Ok(B32::try_from(bytes)?) };
+        assert_eq!(convert(&bytes[..5]), Err(Error::BaseLengthMismatch));
+    }
 }
@@ -1,6 +1,7 @@
 extern crate alloc;
 use crate::{base::Base, error::Error};
 use alloc::vec::Vec;
+use core::fmt::{Display, Formatter};
 use rbtree::RBTree;
 
 /// Basic Memory Instruction
@@ -65,6 +66,9 @@ where
     /// Get the memory
     fn memory(&mut self) -> &'_ mut RBTree<K, V>;
 
+    /// Get the read-only memory
+    fn ro_memory(&self) -> &'_ RBTree<K, V>;
+
     /// Set the stack depth
     fn set_stack_depth(&mut self, stack_depth: u64);
 
@@ -172,6 +176,12 @@ where
     /// Get the range allocated of the memory section
     fn get_memory_address(&self) -> (K, K);
 
+    /// Get the size in bytes of the memory section
+    fn memory_capacity(&self) -> K;
+
+    /// Get the size in bytes of the stack section
+    fn stack_capacity(&self) -> K;
+
     /// Get the current stack depth of the machine
     fn get_stack_depth(&self) -> u64;
 
@@ -191,8 +201,9 @@ where
         let remain = address % self.word_size();
         if remain.is_zero() {
             // Read on a cell
-            let result = self.dummy_read(address);
             let time_log = self.ro_context().time_log();
+            let next_time_log = time_log.checked_add(1).ok_or(Error::TimeLogOverflow)?;
+            let result = self.dummy_read(address);
             self.track(Self::TraceRecord::new(
                 time_log,
                 self.ro_context().stack_depth(),
@@ -200,7 +211,7 @@ where
                 address,
                 result,
             ));
-            self.context().set_time_log(time_log + 1);
+            self.context().set_time_log(next_time_log);
 
             // Return single cell read
             Ok(CellInteraction::SingleCell(
@@ -212,6 +223,7 @@ where
             // Get the address of 2 cells
             let (addr_lo, addr_hi) = self.compute_address(address, remain);
             let time_log = self.ro_context().time_log();
+            let next_time_log = time_log.checked_add(2).ok_or(Error::TimeLogOverflow)?;
             // Get the 2 cells
             let val_lo = self.dummy_read(addr_lo);
             let val_hi = self.dummy_read(addr_hi);
@@ -243,7 +255,7 @@ where
                 val_hi,
             ));
 
-            self.context().set_time_log(time_log + 2);
+            self.context().set_time_log(next_time_log);
 
             // Return double cells read
             Ok(CellInteraction::DoubleCell(
@@ -263,6 +275,7 @@ where
         let remain = address % self.word_size();
         if remain.is_zero() {
             let time_log = self.ro_context().time_log();
+            let next_time_log = time_log.checked_add(1).ok_or(Error::TimeLogOverflow)?;
             // Write on a cell
             self.context().memory().insert(address, value);
             self.track(Self::TraceRecord::new(
@@ -273,7 +286,7 @@ where
                 value,
             ));
 
-            self.context().set_time_log(time_log + 1);
+            self.context().set_time_log(next_time_log);
 
             // Return single cell write
             Ok(CellInteraction::SingleCell(
@@ -285,6 +298,7 @@ where
             // Get the address of 2 cells
             let (addr_lo, addr_hi) = self.compute_address(address, remain);
             let time_log = self.ro_context().time_log();
+            let next_time_log = time_log.checked_add(2).ok_or(Error::TimeLogOverflow)?;
             // Calculate memory address and offset
             let cell_size = self.word_size().into();
             let part_lo: usize = (address - addr_lo).into();
@@ -322,7 +336,7 @@ where
                 val_hi,
             ));
 
-            self.context().set_time_log(time_log + 2);
+            self.context().set_time_log(next_time_log);
 
             // Return double cells write
             Ok(CellInteraction::DoubleCell(
@@ -345,6 +359,23 @@ where
         }
     }
 
+    /// Read a whole cell (nothing is tracked) and whether it was ever written,
+    /// memory only holds written cells so an unwritten cell reads as zero and false
+    fn read_initialized(&mut self, address: K) -> (V, bool) {
+        match self.context().memory().get(&address) {
+            Some(r) => (*r, true),
+            None => (V::zero(), false),
+        }
+    }
+
+    /// Read a whole cell through the read-only context, nothing is tracked
+    fn peek(&self, address: K) -> V {
+        match self.ro_context().ro_memory().get(&address) {
+            Some(r) => *r,
+            None => V::zero(),
+        }
+    }
+
     /// Compute the addresses
     fn compute_address(&self, address: K, remain: K) -> (K, K) {
         let base = address - remain;
@@ -440,6 +471,11 @@ where
         self.read(register.address())
     }
 
+    /// Get the value of the register without tracking, it does not change the trace
+    fn peek_register(&self, register: Register<K>) -> V {
+        self.peek(register.address())
+    }
+
     /// Create new register from index
     fn new_register(&self, register_index: usize) -> Option<Register<K>>;
 }
@@ -486,6 +522,24 @@ where
     }
 }
 
+impl<K, V, const S: usize, const T: usize> Display for TraceRecord<K, V, S, T>
+where
+    K: Base<S>,
+    V: Base<T>,
+{
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
+        write!(
+            f,
+            "time_log: {}, stack_depth: {}, instruction: {:?}, address: 0x{}, value: 0x{}",
+            self.time_log,
+            self.stack_depth,
+            self.instruction,
+            self.address.to_hex(),
+            self.value.to_hex()
+        )
+    }
+}
+
 impl<K, V, const S: usize, const T: usize> PartialOrd for TraceRecord<K, V, S, T>
 where
     K: Base<S>,
@@ -501,13 +555,16 @@ where
     K: Base<S>,
     V: Base<T>,
 {
+    /// Records are ordered by time_log, a machine never logs two records at the same time
+    /// but crafted traces may, so ties are broken by address, instruction, stack depth
+    /// and value to keep the order deterministic
     fn cmp(&self, other: &Self) -> core::cmp::Ordering {
-        match self.time_log.cmp(&other.time_log) {
-            core::cmp::Ordering::Equal => {
-                panic!("Time log never been equal")
-            }
-            ord => ord,
-        }
+        self.time_log
+            .cmp(&other.time_log)
+            .then_with(|| self.address.cmp(&other.address))
+            .then_with(|| self.instruction.cmp(&other.instruction))
+            .then_with(|| self.stack_depth.cmp(&other.stack_depth))
+            .then_with(|| self.value.cmp(&other.value))
     }
 }
 
@@ -576,343 +633,21 @@ macro_rules! impl_stack_machine {
 #[cfg(test)]
 mod tests {
     use crate::{
-        base::{Base, B256},
-        config::{AllocatedSection, Config, ConfigArgs, DefaultConfig},
+        base::{Base, B256, B32, B64},
+        commitment::trace_commitment::trace_commitment,
+        config::{ConfigArgs, DefaultConfig},
         error::Error,
+        instruction::{Instruction, StateMachine, StateMachine32, StateMachine64},
         machine::{
-            AbstractContext, AbstractInstruction, AbstractMachine, AbstractMemoryMachine,
-            AbstractRegisterMachine, AbstractStackMachine, CellInteraction, Register, TraceRecord,
+            AbstractMachine, AbstractMemoryMachine, AbstractRegisterMachine, AbstractTraceRecord,
+            MemoryInstruction, TraceRecord,
         },
     };
     extern crate alloc;
     extern crate std;
-    use alloc::{vec, vec::Vec};
+    use alloc::vec;
     use core::marker::PhantomData;
-    use rbtree::RBTree;
-
-    /// My instruction set for the machine
-    #[derive(Debug, Clone, Copy)]
-    pub enum MyInstruction<M, K, V, const S: usize, const T: usize>
-    where
-        K: Base<S>,
-        V: Base<T>,
-    {
-        /// Read from memory
-        Read(K),
-        /// Write to memory
-        Write(K, V),
-        /// Push to stack
-        Push(V),
-        /// Pop from stack
-        Pop(),
-        /// Move from register to register (Mov(r2, r1) moves the value of r1 to r2)
-        Mov(Register<K>, Register<K>),
-        /// Swap value from top stack  to register
-        Swap(Register<K>),
-        /// Load from memory to register
-        Load(Register<K>, K),
-        /// Save from register to memory
-        Save(K, Register<K>),
-        /// Invalid instruction
-        Invalid(PhantomData<M>),
-        /// Add two registers, register 1 = register 1 + register 2
-        Add(Register<K>, Register<K>),
-    }
-
-    /// Type alias Instruction
-    pub type Instruction = MyInstruction<StateMachine<B256, B256, 32, 32>, B256, B256, 32, 32>;
-
-    /// RAM Machine
-    #[derive(Debug, Clone)]
-    pub struct StateMachine<K, V, const S: usize, const T: usize>
-    where
-        K: Base<S>,
-        V: Base<T>,
-    {
-        // Memory
-        memory: RBTree<K, V>,
-        memory_allocated: AllocatedSection<K>,
-        word_size: K,
-        time_log: u64,
-
-        // Stack
-        stack_allocated: AllocatedSection<K>,
-        max_stack_depth: u64,
-        stack_depth: u64,
-        stack_ptr: K,
-
-        // Register
-        register_allocated: AllocatedSection<K>,
-
-        /// Register r0
-        pub r0: Register<K>,
-        /// Register r1
-        pub r1: Register<K>,
-        /// Register r2
-        pub r2: Register<K>,
-        /// Register r3
-        pub r3: Register<K>,
-        /// Register r4
-        pub r4: Register<K>,
-
-        // Trace
-        execution_trace: RBTree<TraceRecord<K, V, S, T>, PhantomData<()>>,
-    }
-
-    impl<M, K, V, const S: usize, const T: usize> AbstractContext<M, K, V> for StateMachine<K, V, S, T>
-    where
-        Self: core::fmt::Debug
-            + Sized
-            + AbstractMachine<K, V, Context = M::Context, Instruction = M::Instruction>,
-        K: Base<S>,
-        V: Base<T>,
-        M: AbstractMachine<K, V, Machine = StateMachine<K, V, S, T>>,
-    {
-        fn set_stack_depth(&mut self, stack_depth: u64) {
-            self.stack_depth = stack_depth;
-        }
-
-        fn stack_depth(&self) -> u64 {
-            self.stack_depth
-        }
-
-        fn stack_ptr(&self) -> K {
-            self.stack_ptr
-        }
-
-        fn time_log(&self) -> u64 {
-            self.time_log
-        }
-
-        fn set_time_log(&mut self, time_log: u64) {
-            self.time_log = time_log;
-        }
-
-        fn set_stack_ptr(&mut self, stack_ptr: K) {
-            self.stack_ptr = stack_ptr;
-        }
-
-        fn memory(&mut self) -> &'_ mut RBTree<K, V> {
-            &mut self.memory
-        }
-    }
-
-    impl<M, K, V, const S: usize, const T: usize> AbstractInstruction<M, K, V>
-        for MyInstruction<M, K, V, S, T>
-    where
-        Self: core::fmt::Debug + Sized,
-        K: Base<S>,
-        V: Base<T>,
-        M: AbstractMachine<K, V, Machine = StateMachine<K, V, S, T>>,
-    {
-        fn exec(&self, machine: &mut M::Machine) {
-            match self {
-                MyInstruction::Invalid(_) => {
-                    panic!("Invalid instruction")
-                }
-                MyInstruction::Read(addr) => {
-                    if !machine.memory_allocated.contain(*addr) {
-                        panic!("{}", Error::MemoryAccessDeinied);
-                    } else {
-                        machine.read(*addr).expect("Unable to read to memory");
-                    }
-                }
-                MyInstruction::Write(addr, val) => {
-                    if !machine.memory_allocated.contain(*addr) {
-                        panic!("{}", Error::MemoryAccessDeinied);
-                    } else {
-                        machine
-                            .write(*addr, *val)
-                            .expect("Unable to write to memory");
-                    }
-                }
-                MyInstruction::Push(value) => {
-                    machine.push(*value).expect("Unable to push value to stack");
-                }
-                MyInstruction::Pop() => {
-                    machine.pop().expect("Unable to pop value from stack");
-                }
-                MyInstruction::Mov(reg1, reg2) => {
-                    match machine.get(*reg2).expect("Unable to access register 1") {
-                        CellInteraction::SingleCell(_, _, value) => {
-                            machine.set(*reg1, value).expect("Unable to set register 2");
-                        }
-                        _ => panic!("Register unable to be two cells"),
-                    }
-                    // Mov value from register 2 to register 1
-                }
-                MyInstruction::Swap(reg) => {
-                    match machine.pop().expect("Unable to pop value from stack") {
-                        (_, CellInteraction::SingleCell(_op, _addr, value)) => {
-                            machine
-                                .push(value)
-                                .expect("Unable to push register's value to stack");
-                            machine.set(*reg, value).expect("Unable to set register");
-                        }
-                        _ => panic!("Stack unable to be two cells"),
-                    };
-                }
-                MyInstruction::Load(reg, addr) => {
-                    match machine.read(*addr).expect("Unable to read memory") {
-                        CellInteraction::SingleCell(_, _, value) => {
-                            machine.set(*reg, value).expect("Unable to set register");
-                        }
-                        CellInteraction::DoubleCell(_, _, cvalue, _, _, _, _) => {
-                            machine.set(*reg, cvalue).expect("Unable to set register");
-                        }
-                    };
-                }
-                MyInstruction::Save(address, reg) => {
-                    match machine.get(*reg).expect("Unable to access register") {
-                        CellInteraction::SingleCell(_, _, value) => {
-                            machine
-                                .write(*address, value)
-                                .expect("Unable to write to memory");
-                        }
-                        _ => panic!("Register unable to be two cells"),
-                    }
-                }
-                MyInstruction::Add(reg1, reg2) => {
-                    match machine.get(*reg1).expect("Unable to access register 1") {
-                        CellInteraction::SingleCell(_, _, value1) => {
-                            match machine.get(*reg2).expect("Unable to access register 2") {
-                                CellInteraction::SingleCell(_, _, value2) => {
-                                    machine
-                                        .set(*reg1, value1 + value2)
-                                        .expect("Unable to set register 1");
-                                }
-                                _ => panic!("Register unable to be two cells"),
-                            }
-                        }
-                        _ => panic!("Register unable to be two cells"),
-                    }
-                }
-            }
-        }
-    }
-
-    impl<K, V, const S: usize, const T: usize> StateMachine<K, V, S, T>
-    where
-        K: Base<S>,
-        V: Base<T>,
-    {
-        /// Create a new RAM machine
-        pub fn new(config: ConfigArgs<K>) -> Self {
-            let config = Config::new(K::WORD_SIZE, config);
-            Self {
-                // Memory section
-                memory: RBTree::new(),
-                memory_allocated: config.memory,
-                word_size: config.word_size,
-                time_log: 0,
-
-                // Stack
-                stack_allocated: config.stack,
-                max_stack_depth: config.stack_depth.into(),
-                stack_depth: 0,
-                stack_ptr: K::zero(),
-
-                // Register
-                register_allocated: config.register,
-                r0: config.create_register(0),
-                r1: config.create_register(1),
-                r2: config.create_register(2),
-                r3: config.create_register(3),
-                r4: config.create_register(4),
-
-                // Execution trace
-                execution_trace: RBTree::new(),
-            }
-        }
-    }
-
-    impl<K, V, const S: usize, const T: usize> AbstractMachine<K, V> for StateMachine<K, V, S, T>
-    where
-        K: Base<S>,
-        V: Base<T>,
-    {
-        type Machine = Self;
-        type Context = Self;
-        type Instruction = MyInstruction<Self, K, V, S, T>;
-        type TraceRecord = TraceRecord<K, V, S, T>;
-
-        fn context(&mut self) -> &'_ mut Self::Context {
-            self
-        }
-
-        fn word_size(&self) -> K {
-            self.word_size
-        }
-
-        fn register_start(&self) -> K {
-            self.register_allocated.low()
-        }
-
-        fn ro_context(&self) -> &'_ Self::Context {
-            self
-        }
-
-        fn track(&mut self, trace: Self::TraceRecord) {
-            self.execution_trace.insert(trace, PhantomData);
-        }
-
-        fn trace(&self) -> Vec<Self::TraceRecord> {
-            self.execution_trace.keys().copied().collect()
-        }
-
-        fn exec(&mut self, instruction: &Self::Instruction) {
-            instruction.exec(self);
-        }
-
-        fn base_address(&self) -> K {
-            self.memory_allocated.low()
-        }
-
-        fn get_memory_address(&self) -> (K, K) {
-            (self.memory_allocated.low(), self.memory_allocated.high())
-        }
-
-        fn get_stack_depth(&self) -> u64 {
-            self.ro_context().stack_depth
-        }
-
-        fn max_stack_depth(&self) -> u64 {
-            self.ro_context().max_stack_depth
-        }
-    }
-
-    impl<K, V, const S: usize, const T: usize> AbstractMemoryMachine<K, V, S, T>
-        for StateMachine<K, V, S, T>
-    where
-        K: Base<S>,
-        V: Base<T>,
-        Self: AbstractMachine<K, V>,
-    {
-    }
-
-    impl<K, V, const S: usize, const T: usize> AbstractRegisterMachine<K, V, S, T>
-        for StateMachine<K, V, S, T>
-    where
-        K: Base<S>,
-        V: Base<T>,
-        Self: AbstractMemoryMachine<K, V, S, T>,
-    {
-        fn new_register(&self, register_index: usize) -> Option<crate::machine::Register<K>> {
-            Some(Register::new(
-                register_index,
-                self.register_start() + K::from(register_index) * K::WORD_SIZE,
-            ))
-        }
-    }
-
-    impl<K, V, const S: usize, const T: usize> AbstractStackMachine<K, V, S, T>
-        for StateMachine<K, V, S, T>
-    where
-        K: Base<S>,
-        V: Base<T>,
-        Self: AbstractMemoryMachine<K, V, S, T>,
-    {
-    }
+    use halo2_proofs::halo2curves::pasta::Fp;
 
     #[test]
     fn test_read_write_one_cell() {
@@ -964,6 +699,28 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_read_initialized() {
+        let mut sm = StateMachine::<B256, B256, 32, 32>::new(DefaultConfig::default_config());
+        let base = sm.base_address();
+        sm.exec(&Instruction::Write(base + B256::from(32), B256::zero()));
+
+        // Explicitly written zero
+        assert_eq!(
+            sm.read_initialized(base + B256::from(32)),
+            (B256::zero(), true)
+        );
+        // Never written
+        assert_eq!(
+            sm.read_initialized(base + B256::from(64)),
+            (B256::zero(), false)
+        );
+        assert_eq!(
+            sm.dummy_read(base + B256::from(32)),
+            sm.dummy_read(base + B256::from(64))
+        );
+    }
+
     #[test]
     fn test_arithmetics() {
         let chunk1 = [5u8; 32];
@@ -989,6 +746,91 @@ mod tests {
         assert_eq!(sm.dummy_read(base + B256::from(64)), B256::from(add_chunk));
     }
 
+    #[cfg(not(feature = "commit-only"))]
+    #[test]
+    fn test_write_trace_csv() {
+        let mut sm = StateMachine::<B256, B256, 32, 32>::new(DefaultConfig::default_config());
+        let base = sm.base_address();
+        let program = vec![
+            Instruction::Write(base + B256::from(0), B256::from([5u8; 32])),
+            Instruction::Write(base + B256::from(32), B256::from([190u8; 32])),
+            Instruction::Load(sm.r0, base + B256::from(0)),
+            Instruction::Load(sm.r1, base + B256::from(32)),
+            Instruction::Add(sm.r0, sm.r1),
+            Instruction::Save(base + B256::from(64), sm.r0),
+        ];
+        for instruction in program {
+            sm.exec(&instruction);
+        }
+
+        let mut csv = alloc::string::String::new();
+        sm.write_trace_csv(&mut csv).expect("Unable to write trace");
+        let rows = csv.lines().collect::<alloc::vec::Vec<&str>>();
+        assert_eq!(rows[0], "time_log,stack_depth,instruction,address,value");
+        assert_eq!(rows.len(), sm.trace().len() + 1);
+
+        let first = sm.trace()[0];
+        assert_eq!(
+            rows[1],
+            std::format!(
+                "{},{},{:?},0x{},0x{}",
+                first.time_log(),
+                first.stack_depth(),
+                first.instruction(),
+                first.address().to_hex(),
+                first.value().to_hex()
+            )
+        );
+        assert!(rows[1..].iter().all(|row| row.split(',').count() == 5));
+    }
+
+    #[cfg(not(feature = "commit-only"))]
+    #[test]
+    fn test_insert_raw_trace_with_tied_time_log() {
+        let mut sm = StateMachine::<B256, B256, 32, 32>::new(DefaultConfig::default_config());
+        let base = sm.base_address();
+        let write = TraceRecord::<B256, B256, 32, 32>::new(
+            7,
+            0,
+            MemoryInstruction::Write,
+            base + B256::from(32),
+            B256::from(1),
+        );
+        let read = TraceRecord::<B256, B256, 32, 32>::new(
+            7,
+            0,
+            MemoryInstruction::Read,
+            base + B256::from(32),
+            B256::from(1),
+        );
+        let lower = TraceRecord::<B256, B256, 32, 32>::new(
+            7,
+            0,
+            MemoryInstruction::Read,
+            base,
+            B256::from(2),
+        );
+        let earlier = TraceRecord::<B256, B256, 32, 32>::new(
+            6,
+            0,
+            MemoryInstruction::Read,
+            base + B256::from(64),
+            B256::from(3),
+        );
+        for record in [read, write, lower, earlier, write] {
+            sm.insert_raw_trace(record);
+        }
+
+        // Same order whatever the insertion order is, duplicates are kept
+        assert_eq!(sm.trace(), vec![earlier, lower, write, write, read]);
+        let mut other = StateMachine::<B256, B256, 32, 32>::new(DefaultConfig::default_config());
+        for record in [write, earlier, read, write, lower] {
+            other.insert_raw_trace(record);
+        }
+        assert_eq!(other.trace(), sm.trace());
+        assert_eq!(write.cmp(&write), core::cmp::Ordering::Equal);
+    }
+
     #[test]
     fn test_stack_machine() {
         let mut sm = StateMachine::<B256, B256, 32, 32>::new(DefaultConfig::default_config());
@@ -1046,6 +888,47 @@ mod tests {
         assert_eq!(sm.dummy_read(base + B256::from(192)), B256::from(170));
     }
 
+    #[test]
+    fn test_time_log_overflow() {
+        let mut sm = StateMachine::<B256, B256, 32, 32>::new(DefaultConfig::default_config());
+        let base = sm.base_address();
+
+        // The last representable time log can still be used once
+        sm.time_log = u64::MAX - 1;
+        assert!(sm.write(base, B256::from(1)).is_ok());
+        assert_eq!(sm.time_log, u64::MAX);
+
+        // Any further access must fail without touching memory or the trace
+        let trace_len = sm.trace().len();
+        assert_eq!(
+            sm.write(base, B256::from(2)).unwrap_err(),
+            Error::TimeLogOverflow
+        );
+        assert_eq!(sm.read(base).unwrap_err(), Error::TimeLogOverflow);
+        assert_eq!(sm.dummy_read(base), B256::from(1));
+        assert_eq!(sm.trace().len(), trace_len);
+        assert_eq!(sm.time_log, u64::MAX);
+    }
+
+    #[test]
+    fn test_time_log_overflow_two_cells() {
+        let mut sm = StateMachine::<B256, B256, 32, 32>::new(DefaultConfig::default_config());
+        let base = sm.base_address();
+
+        // Unaligned access needs two time log slots
+        sm.time_log = u64::MAX - 1;
+        assert_eq!(
+            sm.write(base + B256::from(1), B256::from(1)).unwrap_err(),
+            Error::TimeLogOverflow
+        );
+        assert_eq!(
+            sm.read(base + B256::from(1)).unwrap_err(),
+            Error::TimeLogOverflow
+        );
+        assert_eq!(sm.dummy_read(base), B256::zero());
+        assert!(sm.trace().is_empty());
+    }
+
     #[test]
     #[should_panic]
     fn test_invalid_instruction() {
@@ -1056,4 +939,327 @@ mod tests {
             sm.exec(&instruction);
         }
     }
+
+    #[test]
+    fn test_load_immediate() {
+        let mut sm = StateMachine::<B256, B256, 32, 32>::new(DefaultConfig::default_config());
+        let base = sm.base_address();
+
+        sm.exec(&Instruction::Li(sm.r0, B256::from(42)));
+        // Load immediate only writes to the register cell
+        let trace = sm.trace();
+        assert_eq!(trace.len(), 1);
+        assert_eq!(trace[0].instruction(), MemoryInstruction::Write);
+        assert_eq!(trace[0].address(), sm.r0.address());
+        assert_eq!(trace[0].value(), B256::from(42));
+
+        sm.exec(&Instruction::Save(base + B256::from(32), sm.r0));
+        assert_eq!(sm.dummy_read(base + B256::from(32)), B256::from(42));
+    }
+
+    #[test]
+    fn test_compare_registers() {
+        let mut sm = StateMachine::<B256, B256, 32, 32>::new(DefaultConfig::default_config());
+        let base = sm.base_address();
+        let program = vec![
+            Instruction::Li(sm.r0, B256::from(7)),
+            Instruction::Li(sm.r1, B256::from(7)),
+            Instruction::Li(sm.r2, B256::from(9)),
+            Instruction::Eq(sm.r3, sm.r0, sm.r1),
+            Instruction::Save(base, sm.r3),
+            Instruction::Eq(sm.r3, sm.r0, sm.r2),
+            Instruction::Save(base + B256::from(32), sm.r3),
+            Instruction::Lt(sm.r3, sm.r0, sm.r2),
+            Instruction::Save(base + B256::from(64), sm.r3),
+            Instruction::Lt(sm.r3, sm.r2, sm.r0),
+            Instruction::Save(base + B256::from(96), sm.r3),
+            Instruction::Lt(sm.r3, sm.r0, sm.r1),
+            Instruction::Save(base + B256::from(128), sm.r3),
+        ];
+        for instruction in program {
+            sm.exec(&instruction);
+        }
+
+        assert_eq!(sm.dummy_read(base), B256::from(1));
+        assert_eq!(sm.dummy_read(base + B256::from(32)), B256::zero());
+        assert_eq!(sm.dummy_read(base + B256::from(64)), B256::from(1));
+        assert_eq!(sm.dummy_read(base + B256::from(96)), B256::zero());
+        assert_eq!(sm.dummy_read(base + B256::from(128)), B256::zero());
+    }
+
+    #[test]
+    fn test_signed_compare_registers() {
+        let mut sm = StateMachine::<B256, B256, 32, 32>::new(DefaultConfig::default_config());
+        let base = sm.base_address();
+        let program = vec![
+            // r0 = -1, r1 = 1
+            Instruction::Li(sm.r0, B256::MAX),
+            Instruction::Li(sm.r1, B256::from(1)),
+            Instruction::Slt(sm.r3, sm.r0, sm.r1),
+            Instruction::Save(base, sm.r3),
+            Instruction::Sgt(sm.r3, sm.r0, sm.r1),
+            Instruction::Save(base + B256::from(32), sm.r3),
+            Instruction::Lt(sm.r3, sm.r0, sm.r1),
+            Instruction::Save(base + B256::from(64), sm.r3),
+            Instruction::Sgt(sm.r3, sm.r1, sm.r0),
+            Instruction::Save(base + B256::from(96), sm.r3),
+            Instruction::Slt(sm.r3, sm.r0, sm.r0),
+            Instruction::Save(base + B256::from(128), sm.r3),
+        ];
+        for instruction in program {
+            sm.exec(&instruction);
+        }
+
+        assert_eq!(sm.dummy_read(base), B256::from(1));
+        assert_eq!(sm.dummy_read(base + B256::from(32)), B256::zero());
+        assert_eq!(sm.dummy_read(base + B256::from(64)), B256::zero());
+        assert_eq!(sm.dummy_read(base + B256::from(96)), B256::from(1));
+        assert_eq!(sm.dummy_read(base + B256::from(128)), B256::zero());
+    }
+
+    #[test]
+    fn test_load_image() {
+        let mut sm = StateMachine::<B256, B256, 32, 32>::new(DefaultConfig::default_config());
+        let base = sm.base_address();
+        let image: [u8; 64] = core::array::from_fn(|i| i as u8);
+
+        sm.load_image(base + B256::from(64), &image).unwrap();
+
+        let mut low = [0u8; 32];
+        low.copy_from_slice(&image[..32]);
+        let mut high = [0u8; 32];
+        high.copy_from_slice(&image[32..]);
+        assert_eq!(sm.dummy_read(base + B256::from(64)), B256::from(low));
+        assert_eq!(sm.dummy_read(base + B256::from(96)), B256::from(high));
+        assert_eq!(sm.dummy_read(base), B256::zero());
+        assert_eq!(sm.dummy_read(base + B256::from(128)), B256::zero());
+
+        // One write per cell
+        let trace = sm.trace();
+        assert_eq!(trace.len(), 2);
+        assert!(trace
+            .iter()
+            .all(|record| record.instruction() == MemoryInstruction::Write));
+        assert_eq!(trace[0].address(), base + B256::from(64));
+        assert_eq!(trace[1].address(), base + B256::from(96));
+
+        // Unaligned base and out of section image are rejected
+        assert_eq!(
+            sm.load_image(base + B256::from(1), &image),
+            Err(Error::MemoryInvalidInteraction)
+        );
+        assert_eq!(
+            sm.load_image(B256::zero(), &image),
+            Err(Error::MemoryAccessDeinied)
+        );
+        assert_eq!(sm.trace().len(), 2);
+    }
+
+    #[test]
+    fn test_memory_and_stack_capacity() {
+        let sm = StateMachine::<B256, B256, 32, 32>::new(DefaultConfig::default_config());
+        // Stack is [0, 1024 * 32] and memory is [33856, MAX], both bounds are included
+        assert_eq!(sm.stack_capacity(), B256::from(1025 * 32));
+        assert_eq!(sm.memory_capacity(), B256::MAX - B256::from(33824));
+        assert_eq!(sm.get_memory_address(), (B256::from(33856), B256::MAX));
+    }
+
+    #[test]
+    fn test_peek_register() {
+        let mut sm = StateMachine::<B256, B256, 32, 32>::new(DefaultConfig::default_config());
+        let program = vec![
+            Instruction::Li(sm.r0, B256::from(42)),
+            Instruction::Mov(sm.r1, sm.r0),
+        ];
+        for instruction in program {
+            sm.exec(&instruction);
+        }
+
+        let trace_len = sm.trace().len();
+        assert_eq!(sm.peek_register(sm.r0), B256::from(42));
+        assert_eq!(sm.peek_register(sm.r1), B256::from(42));
+        assert_eq!(sm.peek_register(sm.r2), B256::zero());
+        assert_eq!(sm.peek(sm.r0.address()), B256::from(42));
+        assert_eq!(sm.trace().len(), trace_len);
+    }
+
+    #[test]
+    fn test_configurable_registers() {
+        let mut sm = StateMachine::<B256, B256, 32, 32>::new(ConfigArgs {
+            head_layout: true,
+            stack_depth: B256::from(1024),
+            no_register: B256::from(16),
+            buffer_size: B256::from(32),
+        });
+        let base = sm.base_address();
+        let r15 = sm.register(15).expect("Register 15 must be configured");
+        assert!(sm.register(16).is_none());
+        assert_eq!(r15.index(), 15);
+
+        let program = vec![
+            Instruction::Li(r15, B256::from(40)),
+            Instruction::Li(sm.r0, B256::from(2)),
+            Instruction::Add(r15, sm.r0),
+            Instruction::Save(base, r15),
+        ];
+        for instruction in program {
+            sm.exec(&instruction);
+        }
+        assert_eq!(sm.dummy_read(base), B256::from(42));
+
+        // The registers r0 to r4 always exist
+        let too_few = StateMachine::<B256, B256, 32, 32>::try_new(ConfigArgs {
+            head_layout: true,
+            stack_depth: B256::from(1024),
+            no_register: B256::from(4),
+            buffer_size: B256::from(32),
+        });
+        assert_eq!(too_few.err(), Some(Error::ConfigTooFewRegisters));
+    }
+
+    #[test]
+    fn test_step_limit_under_budget() {
+        let mut sm = StateMachine::<B256, B256, 32, 32>::new(DefaultConfig::default_config());
+        let base = sm.base_address();
+        sm.set_step_limit(Some(3));
+        let program = vec![
+            Instruction::Write(base, B256::from(7)),
+            Instruction::Read(base),
+            Instruction::Push(B256::from(1)),
+        ];
+        for instruction in program {
+            assert_eq!(sm.try_exec(&instruction), Ok(()));
+        }
+        assert_eq!(sm.steps(), 3);
+        assert_eq!(sm.dummy_read(base), B256::from(7));
+    }
+
+    #[test]
+    fn test_step_limit_exceeded() {
+        let mut sm = StateMachine::<B256, B256, 32, 32>::new(DefaultConfig::default_config());
+        sm.set_step_limit(Some(2));
+        // A program that never halts on its own
+        let mut result = Ok(());
+        for _ in 0..10 {
+            result = sm.try_exec(&Instruction::Push(B256::from(1)));
+            if result.is_err() {
+                break;
+            }
+        }
+        assert_eq!(result, Err(Error::StepLimitExceeded));
+        assert_eq!(sm.steps(), 2);
+        // The rejected instruction was not executed
+        assert_eq!(sm.get_stack_depth(), 2);
+
+        // Removing the limit resumes the machine
+        sm.set_step_limit(None);
+        assert_eq!(sm.try_exec(&Instruction::Push(B256::from(1))), Ok(()));
+    }
+
+    #[test]
+    #[should_panic(expected = "Step limit exceeded")]
+    fn test_exec_should_panic_when_step_limit_exceeded() {
+        let mut sm = StateMachine::<B256, B256, 32, 32>::new(DefaultConfig::default_config());
+        sm.set_step_limit(Some(0));
+        sm.exec(&Instruction::Push(B256::from(1)));
+    }
+
+    #[test]
+    fn test_new_from_config_json() {
+        let json = serde_json::to_string(&DefaultConfig::default_config::<32, B256>())
+            .expect("Unable to serialize config");
+        let mut sm = StateMachine::<B256, B256, 32, 32>::new_from_config_json(&json)
+            .expect("Unable to create machine from config");
+        let expected = StateMachine::<B256, B256, 32, 32>::new(DefaultConfig::default_config());
+
+        assert_eq!(sm.get_memory_address(), expected.get_memory_address());
+        assert_eq!(sm.r4.address(), expected.r4.address());
+        assert_eq!(sm.max_stack_depth(), expected.max_stack_depth());
+
+        let base = sm.base_address();
+        sm.exec(&Instruction::Write(base, B256::from(7)));
+        assert_eq!(sm.peek(base), B256::from(7));
+
+        assert_eq!(
+            StateMachine::<B256, B256, 32, 32>::new_from_config_json("{").unwrap_err(),
+            Error::ConfigInvalidJson
+        );
+    }
+
+    #[test]
+    fn test_state_machine_32() {
+        let mut sm = StateMachine32::new(DefaultConfig::default_config_32());
+        let base = sm.base_address();
+        sm.write(base + B32::from(4), B32::from(0x0badf00d))
+            .unwrap();
+        sm.read(base + B32::from(4)).unwrap();
+        assert_eq!(sm.dummy_read(base + B32::from(4)), B32::from(0x0badf00d));
+        assert_eq!(sm.trace().len(), 2);
+        assert!(sm.register(31).is_some());
+    }
+
+    #[test]
+    fn test_state_machine_64() {
+        let mut sm = StateMachine64::new(DefaultConfig::default_config_64());
+        let base = sm.base_address();
+        sm.write(base + B64::from(8), B64::from(u64::MAX)).unwrap();
+        sm.read(base + B64::from(8)).unwrap();
+        assert_eq!(sm.dummy_read(base + B64::from(8)), B64::from(u64::MAX));
+        assert_eq!(sm.trace().len(), 2);
+        assert!(sm.register(31).is_some());
+    }
+
+    #[test]
+    fn test_current_root() {
+        let mut sm = StateMachine::<B256, B256, 32, 32>::new(DefaultConfig::default_config());
+        let base = sm.base_address();
+        let empty_root = sm.current_root::<Fp>();
+
+        sm.write(base, B256::from(1)).unwrap();
+        let root = sm.current_root::<Fp>();
+        assert_ne!(root, empty_root);
+
+        // Writing a cell changes the root, writing back the old value restores it
+        sm.write(base + B256::from(32), B256::from(5)).unwrap();
+        assert_ne!(sm.current_root::<Fp>(), root);
+        sm.write(base + B256::from(32), B256::zero()).unwrap();
+        assert_eq!(sm.current_root::<Fp>(), root);
+
+        sm.write(base, B256::from(2)).unwrap();
+        assert_ne!(sm.current_root::<Fp>(), root);
+        sm.write(base, B256::from(1)).unwrap();
+        assert_eq!(sm.current_root::<Fp>(), root);
+    }
+
+    #[test]
+    fn test_trace_commitment() {
+        let mut sm = StateMachine::<B256, B256, 32, 32>::new(DefaultConfig::default_config());
+        let base = sm.base_address();
+        let program = vec![
+            Instruction::Write(base, B256::from(7)),
+            Instruction::Li(sm.r0, B256::from(9)),
+            Instruction::Read(base),
+        ];
+        for instruction in program {
+            sm.exec(&instruction);
+        }
+
+        let expected_trace = [
+            TraceRecord::new(0, 0, MemoryInstruction::Write, base, B256::from(7)),
+            TraceRecord::new(
+                1,
+                0,
+                MemoryInstruction::Write,
+                sm.r0.address(),
+                B256::from(9),
+            ),
+            TraceRecord::new(2, 0, MemoryInstruction::Read, base, B256::from(7)),
+        ];
+        // The streamed commitment must match hashing the collected trace
+        assert_eq!(sm.trace_commitment(), trace_commitment(&expected_trace));
+        #[cfg(not(feature = "commit-only"))]
+        assert_eq!(sm.trace(), expected_trace);
+        #[cfg(feature = "commit-only")]
+        assert!(sm.trace().is_empty());
+    }
 }